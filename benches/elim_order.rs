@@ -0,0 +1,65 @@
+//! Compare the elimination orders of `FMESolver` on random systems by the number of relations
+//! they generate, and by time.
+//!
+//! Run with `cargo bench --bench elim_order`. Results are written to stderr.
+
+use presburger::elim_order::{EliminationOrder, FirstFound, MinFillIn, MinProduct};
+use presburger::fme::{FMESolver, SolverStats};
+use presburger::lin_expr::LinExpr;
+use presburger::lin_rel::LinRel;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// A random system of `nrels` inequalities over `nvars` variables with small integer coefficients.
+/// Each coefficient is zero with probability `1/2`, so that variables have unbalanced bounds.
+fn random_system(nvars: usize, nrels: usize, rng: &mut StdRng) -> Vec<LinRel> {
+    (0..nrels)
+        .map(|_| {
+            let coeffs: Vec<i64> = (0..=nvars)
+                .map(|_| {
+                    if rng.random_bool(0.5) {
+                        0
+                    } else {
+                        rng.random_range(-9..=9)
+                    }
+                })
+                .collect();
+            LinRel::mk_le(LinExpr::new(coeffs).unwrap())
+        })
+        .collect()
+}
+
+fn run(system: &[LinRel], order: impl EliminationOrder + Send + Sync + 'static) -> SolverStats {
+    let mut solver = FMESolver::new();
+    solver.set_elimination_order(order);
+    for r in system {
+        solver.assert(r.clone());
+    }
+    solver.check();
+    solver.stats().clone()
+}
+
+fn main() {
+    eprintln!(
+        "{:>12} {:>12} {:>10} {:>10} {:>12}",
+        "system", "order", "peak", "generated", "time"
+    );
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    for (nvars, nrels) in [(4, 12), (5, 12), (6, 14), (4, 16)] {
+        let system = random_system(nvars, nrels, &mut rng);
+        let name = format!("rand_{nvars}x{nrels}");
+        let results = [
+            ("first", run(&system, FirstFound)),
+            ("min_product", run(&system, MinProduct)),
+            ("min_fill_in", run(&system, MinFillIn)),
+        ];
+        for (order, stats) in results {
+            eprintln!(
+                "{name:>12} {order:>12} {:>10} {:>10} {:>12.2?}",
+                stats.peak_relations,
+                stats.generated.iter().sum::<usize>(),
+                stats.wall_time
+            );
+        }
+    }
+}
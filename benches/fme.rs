@@ -0,0 +1,114 @@
+//! Time `FMESolver::check` on the systems from the FME unit tests, and on random systems large
+//! enough that arithmetic on coefficients dominates, with and without certificates.
+//!
+//! Run with `cargo bench --bench fme`. Results are written to stderr. The baseline without the
+//! machine integer fast path of `Rational` is `cargo bench --bench fme --features rbig-only`.
+
+use presburger::fme::FMESolver;
+use presburger::lin_expr::LinExpr;
+use presburger::lin_rel::LinRel;
+use presburger::types::Rational;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+fn le(coeffs: &[i64]) -> LinRel {
+    LinRel::mk_le(LinExpr::new(coeffs.to_vec()).unwrap())
+}
+
+fn eq(coeffs: &[i64]) -> LinRel {
+    LinRel::mk_eq(LinExpr::new(coeffs.to_vec()).unwrap())
+}
+
+/// The systems of the FME unit tests
+fn unit_test_systems() -> Vec<(&'static str, Vec<LinRel>)> {
+    let three_quarters = Rational::from(3) / Rational::from(4);
+    vec![
+        (
+            "wikipedia",
+            vec![
+                le(&[-10, 2, -5, 4]),
+                le(&[-9, 3, -6, 3]),
+                le(&[7, -1, 5, -2]),
+                le(&[-12, -3, 2, 6]),
+            ],
+        ),
+        (
+            "dec_proc_1",
+            vec![
+                le(&[0, -1, 0, 0]),
+                le(&[-1, 1, 0, 0]),
+                le(&[0, 0, -1, 0]),
+                le(&[-1, 0, 1, 0]),
+                LinRel::mk_le(
+                    LinExpr::new(vec![three_quarters, 0.into(), 0.into(), 1.into()]).unwrap(),
+                ),
+                le(&[-1, 0, 0, 1]),
+            ],
+        ),
+        (
+            "dec_proc_2",
+            vec![
+                le(&[0, 1, -1, 0]),
+                le(&[0, 1, 0, -1]),
+                le(&[0, -1, 1, 2]),
+                le(&[1, 0, 0, -1]),
+            ],
+        ),
+        (
+            "4eq",
+            vec![
+                eq(&[0, 0, 1, 7, 0]),
+                eq(&[0, 0, 0, 0, 15]),
+                eq(&[0, 0, 0, 11, 13]),
+                eq(&[0, 2, 0, 3, 5]),
+            ],
+        ),
+    ]
+}
+
+/// A random system of `nrels` inequalities over `nvars` variables with small integer coefficients
+fn random_system(nvars: usize, nrels: usize, rng: &mut StdRng) -> Vec<LinRel> {
+    (0..nrels)
+        .map(|_| {
+            let coeffs: Vec<i64> = (0..=nvars).map(|_| rng.random_range(-9..=9)).collect();
+            le(&coeffs)
+        })
+        .collect()
+}
+
+/// Average time of `check` on `system` over `iters` runs
+fn time_check(system: &[LinRel], iters: u32, certificates: bool) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iters {
+        let mut solver = FMESolver::new();
+        solver.set_produce_certificates(certificates);
+        for r in system {
+            solver.assert(r.clone());
+        }
+        black_box(solver.check());
+    }
+    start.elapsed() / iters
+}
+
+/// Report the time of `check` on `system` with and without certificates
+fn report(name: &str, system: &[LinRel], iters: u32) {
+    eprintln!(
+        "{name:>12} {:>12.2?} {:>12.2?}",
+        time_check(system, iters, true),
+        time_check(system, iters, false)
+    );
+}
+
+fn main() {
+    eprintln!("{:>12} {:>12} {:>12}", "system", "certificates", "none");
+    for (name, system) in unit_test_systems() {
+        report(name, &system, 2000);
+    }
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    for (nvars, nrels) in [(4, 12), (4, 16), (4, 20), (5, 8)] {
+        let system = random_system(nvars, nrels, &mut rng);
+        report(&format!("rand_{nvars}x{nrels}"), &system, 5);
+    }
+}
@@ -0,0 +1,199 @@
+//! Compare the sparse `LinExpr` with dense coefficient vectors on wide systems with few non-zeros
+//! per relation, the shape of MILP-style instances.
+//!
+//! The dense baseline stores `[b, a_1, ..., a_n]` in a `Vec<Rational>`, as `LinExpr` did before it
+//! switched to sparse storage, and implements the same operations by walking every coefficient.
+//!
+//! Run with `cargo bench --bench sparse_vs_dense`. Results are written to stderr. Each line of the
+//! first table reports the average time of one round of Fourier-Motzkin style combinations (every
+//! lower bound relation with every upper bound relation of a variable), and of substituting one
+//! equality into every relation.
+//!
+//! The second table runs the solvers on the same small system embedded in spaces of increasing
+//! dimension. The extra variables do not occur in any relation, so the times should stay flat if
+//! no step of the solvers walks every coefficient.
+
+use presburger::fme::FMESolver;
+use presburger::lin_expr::LinExpr;
+use presburger::lin_rel::LinRel;
+use presburger::simplex::SimplexSolver;
+use presburger::solver::Solver;
+use presburger::space::Space;
+use presburger::types::Rational;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Number of relations in each generated system
+const NROWS: usize = 200;
+/// Non-zero variable coefficients per relation
+const NNZ: usize = 4;
+
+/// Dense coefficients `[b, a_1, ..., a_n]`
+type DenseRow = Vec<Rational>;
+
+/// Random rows over `nvars` variables with `NNZ` non-zeros each, all mentioning x_1
+fn random_rows(nvars: usize, rng: &mut StdRng) -> Vec<LinExpr> {
+    (0..NROWS)
+        .map(|r| {
+            let mut row = LinExpr::constant(rng.random_range(-10..10), nvars);
+            // alternate the sign of x_1 so that there are lower and upper bounds
+            let a1 = if r % 2 == 0 { 1 } else { -1 };
+            row.set_coeff_unchecked(1, Rational::from(a1 * rng.random_range(1..5)));
+            for _ in 1..NNZ {
+                let i = rng.random_range(2..=nvars);
+                row.set_coeff_unchecked(i, Rational::from(rng.random_range(-5..5)));
+            }
+            row
+        })
+        .collect()
+}
+
+fn to_dense(row: &LinExpr) -> DenseRow {
+    std::iter::once(row.const_().clone())
+        .chain(row.coeffs().iter().cloned())
+        .collect()
+}
+
+/// `u * a + l * b`, coefficient by coefficient
+fn dense_lin_comb(u: &DenseRow, a: &Rational, l: &DenseRow, b: &Rational) -> DenseRow {
+    u.iter().zip(l.iter()).map(|(x, y)| x * a + y * b).collect()
+}
+
+/// Average time of `f` over `iters` runs
+fn time<T>(iters: u32, mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iters {
+        black_box(f());
+    }
+    start.elapsed() / iters
+}
+
+/// Eliminate x_1 from all pairs of rows with opposite signs: |a_l| * upper + a_u * lower
+fn combine_dense(rows: &[DenseRow]) -> usize {
+    let (lower, upper): (Vec<_>, Vec<_>) = rows.iter().partition(|r| r[1] < Rational::ZERO);
+    let mut count = 0;
+    for l in lower.iter() {
+        for u in upper.iter() {
+            let al = -&l[1];
+            let combined = dense_lin_comb(u, &al, l, &u[1]);
+            count += combined[1..].iter().filter(|a| !a.is_zero()).count();
+        }
+    }
+    count
+}
+
+fn combine_sparse(rows: &[LinExpr]) -> usize {
+    let (lower, upper): (Vec<_>, Vec<_>) = rows
+        .iter()
+        .partition(|r| r.coeff_unchecked(1) < &Rational::ZERO);
+    let mut count = 0;
+    for l in lower.iter() {
+        for u in upper.iter() {
+            let al = -l.coeff_unchecked(1);
+            let au = u.coeff_unchecked(1);
+            let combined = *u * &al + *l * au;
+            count += combined.nnz();
+        }
+    }
+    count
+}
+
+/// Substitute the first row, solved for x_1, into every other row
+fn subs_dense(rows: &[DenseRow]) -> Vec<DenseRow> {
+    let eq = &rows[0];
+    let m = -Rational::ONE / &eq[1];
+    rows[1..]
+        .iter()
+        .map(|r| dense_lin_comb(r, &Rational::ONE, eq, &(&m * &r[1])))
+        .collect()
+}
+
+fn subs_sparse(rows: &[LinExpr]) -> Vec<LinExpr> {
+    let eq = &rows[0];
+    let m = -Rational::ONE / eq.coeff_unchecked(1);
+    rows[1..]
+        .iter()
+        .map(|r| r + eq * (&m * r.coeff_unchecked(1)))
+        .collect()
+}
+
+/// Number of relations of the system given to the solvers
+const SOLVER_ROWS: usize = 24;
+/// Number of variables that occur in the system given to the solvers
+const SOLVER_VARS: usize = 8;
+
+/// Random inequalities over `x_1 ... x_SOLVER_VARS` with 3 non-zeros each, boxed by
+/// `-10 <= x_i <= 10`, padded to `nvars` variables
+fn random_system(nvars: usize, rng: &mut StdRng) -> Vec<LinRel> {
+    let mut relations: Vec<LinRel> = (0..SOLVER_ROWS)
+        .map(|_| {
+            let mut row = LinExpr::constant(rng.random_range(-10..10), nvars);
+            for _ in 0..3 {
+                let i = rng.random_range(1..=SOLVER_VARS);
+                row.set_coeff_unchecked(i, Rational::from(rng.random_range(-5..5)));
+            }
+            LinRel::mk_le(row)
+        })
+        .collect();
+    for i in 1..=SOLVER_VARS {
+        let x = LinExpr::var(i, nvars);
+        relations.push(LinRel::mk_le(&x - LinExpr::constant(10, nvars)));
+        relations.push(LinRel::mk_le(-x - LinExpr::constant(10, nvars)));
+    }
+    relations
+}
+
+/// Assert `relations` in a fresh solver over `nvars` variables and check them
+fn solve(mut solver: impl Solver, relations: &[LinRel]) -> bool {
+    for r in relations {
+        solver.assert(r.clone());
+    }
+    solver.check() == presburger::solver::SatResult::SAT
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    eprintln!(
+        "{:>6} {:>14} {:>14} {:>14} {:>14}",
+        "nvars", "dense comb", "sparse comb", "dense subs", "sparse subs"
+    );
+    for (nvars, iters) in [
+        (10, 20),
+        (50, 20),
+        (100, 10),
+        (200, 10),
+        (500, 5),
+        (1000, 3),
+    ] {
+        let sparse = random_rows(nvars, &mut rng);
+        let dense: Vec<DenseRow> = sparse.iter().map(to_dense).collect();
+        assert_eq!(combine_dense(&dense), combine_sparse(&sparse));
+
+        let dc = time(iters, || combine_dense(&dense));
+        let sc = time(iters, || combine_sparse(&sparse));
+        let ds = time(iters * 10, || subs_dense(&dense));
+        let ss = time(iters * 10, || subs_sparse(&sparse));
+        eprintln!("{nvars:>6} {dc:>14.2?} {sc:>14.2?} {ds:>14.2?} {ss:>14.2?}");
+    }
+
+    eprintln!();
+    eprintln!("{:>6} {:>14} {:>14}", "nvars", "fme", "simplex");
+    let template = random_system(SOLVER_VARS, &mut rng);
+    for (nvars, iters) in [(10, 10), (100, 10), (1000, 10), (10000, 5)] {
+        let relations: Vec<LinRel> = template
+            .iter()
+            .map(|r| r.clone().extend_to(nvars))
+            .collect();
+        let space = || Space::anonymous(nvars);
+        let sat = solve(FMESolver::with_space(space()), &relations);
+        assert_eq!(sat, solve(SimplexSolver::with_space(space()), &relations));
+
+        let fme = time(iters, || solve(FMESolver::with_space(space()), &relations));
+        let simplex = time(iters, || {
+            solve(SimplexSolver::with_space(space()), &relations)
+        });
+        eprintln!("{nvars:>6} {fme:>14.2?} {simplex:>14.2?}");
+    }
+}
@@ -9,6 +9,7 @@
 //! 2) 0 <= x /\ x <= 10
 //! 3) forall y. (exists x. x <= y ==> x + 1 <= y)
 //! 4) ((P ==> Q) ==> P) ==> Q
+//! 5) exists y. x = 2 y <==> 2 | x
 //!
 //! The AST is produced by the parser/grammer defined in `grammer.lalrpop`.
 //!
@@ -115,6 +116,8 @@ pub enum Atom {
     Equality(Box<Term>, Box<Term>),
     /// t1 <= t2
     LessEq(Box<Term>, Box<Term>),
+    /// d | t, i.e. t is an integer multiple of d
+    Divides(Integer, Box<Term>),
     // TODO finish rest of atoms
 }
 
@@ -135,6 +138,32 @@ impl Atom {
     pub fn less_eq(t1: Term, t2: Term) -> Self {
         Atom::LessEq(Box::new(t1), Box::new(t2))
     }
+
+    pub fn divides(d: impl Into<Integer>, t: Term) -> Self {
+        Atom::Divides(d.into(), Box::new(t))
+    }
+}
+
+impl Atom {
+    /// Evaluate the atom, where `value` gives the values of the variables and a logical variable
+    /// is true iff its value is non-zero. `d | t` holds iff `t` is an integer multiple of `d`, so
+    /// `0 | t` iff `t = 0`. Returns the first variable without a value as an error.
+    pub fn eval(&self, value: &impl Fn(&Var) -> Option<Rational>) -> Result<bool, Var> {
+        match self {
+            Atom::TruthValue(b) => Ok(*b),
+            Atom::LogicalVar(v) => value(v).map(|x| !x.is_zero()).ok_or_else(|| v.clone()),
+            Atom::Equality(t1, t2) => Ok(t1.eval(value)? == t2.eval(value)?),
+            Atom::LessEq(t1, t2) => Ok(t1.eval(value)? <= t2.eval(value)?),
+            Atom::Divides(d, t) => {
+                let t = t.eval(value)?;
+                Ok(if *d == Integer::ZERO {
+                    t.is_zero()
+                } else {
+                    (t / Rational::from(d.clone())).is_int()
+                })
+            }
+        }
+    }
 }
 
 impl fmt::Display for Atom {
@@ -144,6 +173,7 @@ impl fmt::Display for Atom {
             Atom::LogicalVar(x) => write!(f, "{x}"),
             Atom::Equality(a, b) => write!(f, "{} == {}", *a, *b),
             Atom::LessEq(a, b) => write!(f, "{} <= {}", *a, *b),
+            Atom::Divides(d, t) => write!(f, "{} | {}", d, *t),
         }
     }
 }
@@ -159,6 +189,7 @@ impl PartialEq for Atom {
             (Atom::LessEq(lhs1, rhs1), Atom::LessEq(lhs2, rhs2)) => {
                 *lhs1 == *lhs2 && *rhs1 == *rhs2
             }
+            (Atom::Divides(d1, t1), Atom::Divides(d2, t2)) => d1 == d2 && *t1 == *t2,
             _ => false,
         }
     }
@@ -194,6 +225,18 @@ impl Term {
     }
 }
 
+impl Term {
+    /// Evaluate the term, where `value` gives the values of the variables. Returns the first
+    /// variable without a value as an error.
+    pub fn eval(&self, value: &impl Fn(&Var) -> Option<Rational>) -> Result<Rational, Var> {
+        match self {
+            Term::Num(c) => Ok(c.clone()),
+            Term::ScalarVar(a, v) => value(v).map(|x| a * x).ok_or_else(|| v.clone()),
+            Term::Add(t1, t2) => Ok(t1.eval(value)? + t2.eval(value)?),
+        }
+    }
+}
+
 impl fmt::Display for Term {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -295,6 +338,37 @@ mod test {
         assert!(a1 != a4);
     }
 
+    #[test]
+    fn atom_eval() {
+        // x = 3/2, y = 0, P = 1
+        let value = |v: &Var| match v.0.as_str() {
+            "x" => Some(Rational::from(3) / Rational::from(2)),
+            "y" => Some(Rational::ZERO),
+            "P" => Some(Rational::ONE),
+            _ => None,
+        };
+        let x = |a: i64| Term::scalar_var(Rational::from(a), "x");
+        let y = Term::scalar_var(Rational::ONE, "y");
+        let t = Term::tadd(x(2), Term::num(Rational::from(1)));
+        assert_eq!(t.eval(&value), Ok(Rational::from(4)));
+        assert_eq!(Atom::less_eq(x(1), y.clone()).eval(&value), Ok(false));
+        assert_eq!(Atom::equality(x(2), Term::num(3)).eval(&value), Ok(true));
+        assert_eq!(Atom::var("P").eval(&value), Ok(true));
+
+        // d | t iff t is an integer multiple of d, so 0 | t iff t = 0
+        assert_eq!(Atom::divides(2, t.clone()).eval(&value), Ok(true));
+        assert_eq!(Atom::divides(-4, t.clone()).eval(&value), Ok(true));
+        assert_eq!(Atom::divides(3, t.clone()).eval(&value), Ok(false));
+        assert_eq!(Atom::divides(1, x(1)).eval(&value), Ok(false));
+        assert_eq!(Atom::divides(0, t).eval(&value), Ok(false));
+        assert_eq!(Atom::divides(0, y).eval(&value), Ok(true));
+
+        // variables without a value
+        let z = Term::tadd(x(1), Term::scalar_var(Rational::ONE, "z"));
+        assert_eq!(Atom::divides(0, z).eval(&value), Err(Var::new("z")));
+        assert_eq!(Atom::var("Q").eval(&value), Err(Var::new("Q")));
+    }
+
     #[test]
     fn pred_eq() {
         // Note: sub-predicates can't be shared since the Box takes ownership.
@@ -22,6 +22,16 @@ pub fn arb_term(max_depth: u32, max_size: u32) -> impl Strategy<Value = ast::Ter
     })
 }
 
+/// Divisors of `d | t` atoms: mostly small positive integers, with some `0` and negative ones,
+/// for which `d | t` means `t = 0` and `-d | t` respectively
+pub fn arb_divisor() -> impl Strategy<Value = i64> {
+    prop_oneof![
+        8 => 1i64..=12,
+        1 => Just(0i64),
+        1 => -12i64..=-1,
+    ]
+}
+
 pub fn arb_atom(max_depth: u32, max_size: u32) -> impl Strategy<Value = ast::Atom> {
     let new_depth = if max_depth > 0 { max_depth - 1 } else { 0 };
     prop_oneof![
@@ -32,6 +42,7 @@ pub fn arb_atom(max_depth: u32, max_size: u32) -> impl Strategy<Value = ast::Ato
             .prop_map(|(t1, t2)| ast::Atom::equality(t1, t2)),
         (arb_term(new_depth, max_size), arb_term(new_depth, max_size))
             .prop_map(|(t1, t2)| ast::Atom::less_eq(t1, t2)),
+        (arb_divisor(), arb_term(new_depth, max_size)).prop_map(|(d, t)| ast::Atom::divides(d, t)),
     ]
 }
 
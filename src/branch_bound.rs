@@ -0,0 +1,328 @@
+//! Branch and bound for the satisfiability of linear systems over the integers.
+//!
+//! The relations are normalized for integer variables, see `LinRel::normalize_integer()`, and
+//! their rational relaxation is decided by [`SimplexSolver`]. A node of the search is the
+//! relaxation together with bounds on some variables. If its model assigns a fractional value `v`
+//! to some variable `x_i`, the node branches into `x_i <= floor(v)` and `x_i >= ceil(v)`, which
+//! exclude `v` but no integer value of `x_i`. A node whose model is integral gives an integer
+//! model of the system, and a node whose relaxation is unsatisfiable is closed.
+//!
+//! The search terminates when the relaxation is bounded, but it may branch forever when it is
+//! not, e.g. on `x = 2 y = 2 z + 1`, which has rational solutions for all bounds on `y` and `z`
+//! but no integer ones. `set_max_nodes` bounds the search, which then returns `UNKNOWN`. For
+//! these systems the Omega test, see [`crate::omega`], is complete.
+
+use crate::certificate::UnsatCertificate;
+use crate::lin_expr::LinExpr;
+use crate::lin_rel::LinRel;
+use crate::lin_sys::LinSys;
+use crate::model::Model;
+use crate::simplex::SimplexSolver;
+use crate::solver::{SatResult, Solver};
+use crate::space::{Space, SpaceError};
+use crate::types::Rational;
+use std::collections::VecDeque;
+
+/// The order in which the nodes of the search are explored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchOrder {
+    /// Explore the last node created first, trying `x_i <= floor(v)` before `x_i >= ceil(v)`
+    #[default]
+    DepthFirst,
+    /// Explore the nodes in the order they are created
+    BreadthFirst,
+}
+
+#[derive(Debug)]
+pub struct BranchAndBound {
+    state: SatResult,
+    system: LinSys,
+    model: Option<Model>,
+    max_nodes: Option<usize>,
+    order: SearchOrder,
+    nodes: usize,
+}
+
+impl BranchAndBound {
+    /// Create a fresh solver
+    pub fn new() -> Self {
+        Self::with_space(Space::new())
+    }
+
+    /// Create a fresh solver over the given space of variables
+    pub fn with_space(space: Space) -> Self {
+        Self {
+            state: SatResult::UNKNOWN,
+            system: LinSys::with_space(space),
+            model: None,
+            max_nodes: None,
+            order: SearchOrder::default(),
+            nodes: 0,
+        }
+    }
+
+    /// Get the space of variables of the solver
+    pub fn space(&self) -> &Space {
+        self.system.space()
+    }
+
+    /// Get the asserted relations
+    pub fn assertions(&self) -> &LinSys {
+        &self.system
+    }
+
+    /// Assert a new relation. If it ranges over more variables than the space has, the space is
+    /// grown, see `try_assert()` to reject such relations instead.
+    pub fn assert(&mut self, rel: LinRel) {
+        self.invalidate();
+        self.system.add_relation(rel);
+    }
+
+    /// Assert a new relation, checking that it belongs to the solver's space
+    pub fn try_assert(&mut self, rel: LinRel) -> Result<(), SpaceError> {
+        self.invalidate();
+        self.system.try_add_relation(rel)
+    }
+
+    /// Reset the solver state and clear all assertions, keeping the space and the configuration
+    pub fn reset(&mut self) {
+        self.invalidate();
+        self.system.clear();
+    }
+
+    /// Give up after exploring `max_nodes` nodes, or never if `None`, which is the default
+    pub fn set_max_nodes(&mut self, max_nodes: Option<usize>) {
+        self.max_nodes = max_nodes;
+    }
+
+    /// Explore the nodes in the given order. The default is [`SearchOrder::DepthFirst`].
+    pub fn set_search_order(&mut self, order: SearchOrder) {
+        self.order = order;
+    }
+
+    /// The number of nodes explored by the last call to `check`
+    pub fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    /// Forget the result of the last `check`
+    fn invalidate(&mut self) {
+        self.state = SatResult::UNKNOWN;
+        self.model = None;
+        self.nodes = 0;
+    }
+
+    /// An integer model of the assertions, if the last call to `check` returned `SAT`
+    pub fn model(&self) -> Option<&Model> {
+        self.model.as_ref()
+    }
+
+    /// Search for an integer solution of the assertions. Returns `UNKNOWN` if the node limit is
+    /// reached first.
+    ///
+    /// ```
+    /// # use presburger::branch_bound::*;
+    /// # use presburger::solver::SatResult;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # fn main () {
+    /// let mut solver = BranchAndBound::new();
+    /// // 2 x_1 + 2 x_2 = 3 has no integer solutions
+    /// solver.assert(LinRel::mk_eq(LinExpr::new(vec![-3, 2, 2]).unwrap()));
+    /// assert_eq!(solver.check(), SatResult::UNSAT);
+    ///
+    /// // 1 <= 2 x_1 - 4 x_2 <= 5, 0 <= x_2 <= 1
+    /// solver.reset();
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![1, -2, 4]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![-5, 2, -4]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 0, -1]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![-1, 0, 1]).unwrap()));
+    /// assert_eq!(solver.check(), SatResult::SAT);
+    /// let model = solver.model().unwrap();
+    /// assert!(model.values().iter().all(|v| v.is_int()));
+    /// assert_eq!(solver.assertions().satisfied_by(model), Ok(true));
+    /// # }
+    /// ```
+    pub fn check(&mut self) -> SatResult {
+        self.invalidate();
+        let nvars = self.space().dim();
+        let mut relaxation = SimplexSolver::with_space(self.space().clone());
+        for r in self.system.relations() {
+            relaxation.assert(r.clone().normalize_integer());
+        }
+        // each node is given by its bounds on the variables
+        let mut queue: VecDeque<Vec<LinRel>> = VecDeque::from([Vec::new()]);
+        loop {
+            let next = match self.order {
+                SearchOrder::DepthFirst => queue.pop_back(),
+                SearchOrder::BreadthFirst => queue.pop_front(),
+            };
+            let Some(bounds) = next else {
+                self.state = SatResult::UNSAT;
+                break;
+            };
+            if self.max_nodes.is_some_and(|m| self.nodes >= m) {
+                break;
+            }
+            self.nodes += 1;
+
+            relaxation.push();
+            for b in bounds.iter() {
+                relaxation.assert(b.clone());
+            }
+            let model = match relaxation.check() {
+                SatResult::SAT => relaxation.model().cloned(),
+                _ => None,
+            };
+            relaxation.pop(1);
+            let Some(model) = model else {
+                continue;
+            };
+            let Some(i) = model.values().iter().position(|v| !v.is_int()) else {
+                self.state = SatResult::SAT;
+                self.model = Some(model);
+                break;
+            };
+
+            // x_i <= floor(v) and ceil(v) <= x_i
+            let v = &model.values()[i];
+            let i = i + 1;
+            let x = LinExpr::var(i, nvars);
+            let mut down = bounds.clone();
+            down.push(LinRel::mk_le(
+                &x - LinExpr::constant(Rational::from(v.floor()), nvars),
+            ));
+            let mut up = bounds;
+            up.push(LinRel::mk_le(
+                LinExpr::constant(Rational::from(v.ceil()), nvars) - x,
+            ));
+            match self.order {
+                SearchOrder::DepthFirst => queue.extend([up, down]),
+                SearchOrder::BreadthFirst => queue.extend([down, up]),
+            }
+        }
+        self.state.clone()
+    }
+}
+
+impl Default for BranchAndBound {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Solver for BranchAndBound {
+    fn assertions(&self) -> &LinSys {
+        BranchAndBound::assertions(self)
+    }
+
+    fn assert(&mut self, rel: LinRel) {
+        BranchAndBound::assert(self, rel);
+    }
+
+    fn try_assert(&mut self, rel: LinRel) -> Result<(), SpaceError> {
+        BranchAndBound::try_assert(self, rel)
+    }
+
+    fn check(&mut self) -> SatResult {
+        BranchAndBound::check(self)
+    }
+
+    fn model(&self) -> Option<&Model> {
+        BranchAndBound::model(self)
+    }
+
+    fn unsat_certificate(&self) -> Option<&UnsatCertificate> {
+        None
+    }
+
+    fn reset(&mut self) {
+        BranchAndBound::reset(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::int_testing::{arb_system, bounding_box, check_valid};
+    use crate::omega::OmegaSolver;
+    use crate::{eq, le};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_branch_and_bound() {
+        // 2 x - 2 y = 1 has no integer solutions, and normalization turns it into a contradiction
+        let mut solver = BranchAndBound::new();
+        solver.assert(eq!(-1, 2, -2));
+        assert_eq!(check_valid(&mut solver), SatResult::UNSAT);
+        assert_eq!(solver.nodes(), 1);
+
+        // 3 <= 2 x <= 5 and 3 <= 2 y <= 3 + 2 x: the relaxation allows x = 3/2
+        solver.reset();
+        solver.assert(le!(3, -2, 0));
+        solver.assert(le!(-5, 2, 0));
+        solver.assert(le!(3, 0, -2));
+        solver.assert(le!(-3, -2, 2));
+        for order in [SearchOrder::DepthFirst, SearchOrder::BreadthFirst] {
+            solver.set_search_order(order);
+            assert_eq!(check_valid(&mut solver), SatResult::SAT);
+            assert_eq!(solver.model().unwrap().value(1), Some(&Rational::from(2)));
+        }
+    }
+
+    #[test]
+    fn test_branch_and_bound_node_limit() {
+        // x = 2 y = 2 z + 1 has no integer solutions, but branching on y or z never closes a node
+        let mut solver = BranchAndBound::new();
+        solver.assert(eq!(0, 1, -2, 0));
+        solver.assert(eq!(-1, 1, 0, -2));
+        solver.set_max_nodes(Some(20));
+        for order in [SearchOrder::DepthFirst, SearchOrder::BreadthFirst] {
+            solver.set_search_order(order);
+            assert_eq!(check_valid(&mut solver), SatResult::UNKNOWN);
+            assert_eq!(solver.nodes(), 20);
+            assert!(solver.model().is_none());
+        }
+
+        // within a box, the search finishes
+        solver.assert(le!(-3, 1, 0, 0));
+        solver.assert(le!(-3, -1, 0, 0));
+        solver.set_max_nodes(None);
+        assert_eq!(check_valid(&mut solver), SatResult::UNSAT);
+    }
+
+    #[test]
+    fn test_branch_and_bound_try_assert() {
+        let space = Space::from_names(["x", "y"]).unwrap();
+        let mut solver = BranchAndBound::with_space(space.clone());
+        assert_eq!(solver.try_assert(eq!(-1, 2, -2)), Ok(())); // 2 x - 2 y = 1
+        assert_eq!(
+            solver.try_assert(le!(1, 0, 0, 1)), // 1 + x_3 <= 0
+            Err(SpaceError::DimensionMismatch { nvars: 3, dim: 2 })
+        );
+        assert_eq!(solver.assertions().len(), 1);
+        assert_eq!(solver.space(), &space);
+        assert_eq!(check_valid(&mut solver), SatResult::UNSAT);
+    }
+
+    proptest! {
+        /// Both search orders agree with the Omega test on systems within a box
+        #[test]
+        fn branch_and_bound_agrees_with_omega((nvars, relations) in arb_system()) {
+            let mut omega = OmegaSolver::new();
+            let mut bb = BranchAndBound::new();
+            let mut assert = |r: LinRel| {
+                omega.assert(r.clone());
+                bb.assert(r);
+            };
+            for r in relations.into_iter().chain(bounding_box(nvars, 4)) {
+                assert(r);
+            }
+            let expected = omega.check();
+            for order in [SearchOrder::DepthFirst, SearchOrder::BreadthFirst] {
+                bb.set_search_order(order);
+                prop_assert_eq!(check_valid(&mut bb), expected.clone());
+            }
+        }
+    }
+}
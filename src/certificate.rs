@@ -0,0 +1,398 @@
+//! Farkas certificates of unsatisfiability and a standalone checker for them.
+//!
+//! A [`Combination`] of asserted relations `e_j rel_j 0` with multipliers `m_j` stands for the
+//! relation `sum_j m_j e_j rel 0`. If the multipliers of inequalities are nonnegative, the relation
+//! is implied by the assertions, and it is strict if some strict inequality has a positive
+//! multiplier. Multipliers of equalities may have any sign, and disequalities cannot be combined.
+//! When the combined expression is a constant `c` and `c rel 0` is false, e.g. `1 <= 0` or `0 < 0`,
+//! the assertions are unsatisfiable. Checking this only requires rational arithmetic, independent
+//! of the solver that produced the combination.
+
+use crate::lin_expr::LinExpr;
+use crate::lin_rel::{Constraint, LinRel};
+use crate::lin_sys::LinSys;
+use crate::types::Rational;
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CertificateError {
+    /// A multiplier refers to a relation that is not asserted
+    IndexOutOfBounds(usize),
+    /// A disequality certificate refers to a relation that is not a disequality
+    NotADisequality(usize),
+    /// The multiplier of an inequality is negative
+    NegativeMultiplier(usize),
+    /// A disequality has a non-zero multiplier
+    Disequality(usize),
+    /// The combined expression has non-zero variable coefficients
+    NotConstant,
+    /// The combined relation between constants holds
+    NotContradiction,
+}
+
+impl fmt::Display for CertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IndexOutOfBounds(j) => {
+                write!(f, "Multiplier for relation {j}, which is not asserted")
+            }
+            Self::NotADisequality(j) => {
+                write!(f, "Relation {j} is not a disequality")
+            }
+            Self::NegativeMultiplier(j) => {
+                write!(f, "Negative multiplier for inequality {j}")
+            }
+            Self::Disequality(j) => {
+                write!(f, "Non-zero multiplier for disequality {j}")
+            }
+            Self::NotConstant => {
+                write!(f, "Combination has non-zero variable coefficients")
+            }
+            Self::NotContradiction => {
+                write!(f, "Combination is not a contradiction")
+            }
+        }
+    }
+}
+
+impl Error for CertificateError {}
+
+/// A linear combination of asserted relations, given by the multiplier of each relation's index
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Combination {
+    // (index, multiplier) pairs sorted by index, with only non-zero multipliers
+    multipliers: Vec<(usize, Rational)>,
+}
+
+impl fmt::Display for Combination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<String> = self
+            .multipliers
+            .iter()
+            .map(|(j, m)| format!("{m} * [{j}]"))
+            .collect();
+        write!(f, "{}", terms.join(" + "))
+    }
+}
+
+impl Combination {
+    /// Create the empty combination
+    pub fn new() -> Self {
+        Self {
+            multipliers: Vec::new(),
+        }
+    }
+
+    /// Create the combination consisting of relation `j` with multiplier 1
+    pub fn unit(j: usize) -> Self {
+        Self::from_multipliers([(j, Rational::ONE)])
+    }
+
+    /// Create a combination from `(index, multiplier)` pairs, summing repeated indices
+    pub fn from_multipliers(multipliers: impl IntoIterator<Item = (usize, Rational)>) -> Self {
+        let mut c = Self::new();
+        for (j, m) in multipliers {
+            c.add_term(j, &m);
+        }
+        c
+    }
+
+    /// Get the non-zero multipliers as `(index, multiplier)` pairs, sorted by index
+    pub fn multipliers(&self) -> &[(usize, Rational)] {
+        &self.multipliers
+    }
+
+    /// Get the multiplier of relation `j`
+    pub fn multiplier(&self, j: usize) -> Rational {
+        match self.multipliers.binary_search_by_key(&j, |(k, _)| *k) {
+            Ok(pos) => self.multipliers[pos].1.clone(),
+            Err(_) => Rational::ZERO,
+        }
+    }
+
+    /// Indices of the relations with a non-zero multiplier, in increasing order
+    pub fn support(&self) -> impl Iterator<Item = usize> + '_ {
+        self.multipliers.iter().map(|(j, _)| *j)
+    }
+
+    /// Add `m` times relation `j`
+    fn add_term(&mut self, j: usize, m: &Rational) {
+        self.add_scaled(
+            &Self {
+                multipliers: vec![(j, m.clone())],
+            },
+            &Rational::ONE,
+        );
+    }
+
+    /// Add `c` times `other` to `self`
+    pub fn add_scaled(&mut self, other: &Combination, c: &Rational) {
+        if c.is_zero() || other.multipliers.is_empty() {
+            return;
+        }
+        let lhs = std::mem::take(&mut self.multipliers);
+        let mut merged = Vec::with_capacity(lhs.len() + other.multipliers.len());
+        let mut lhs = lhs.into_iter().peekable();
+        let mut rhs = other.multipliers.iter().peekable();
+        loop {
+            let order = match (lhs.peek(), rhs.peek()) {
+                (Some((j, _)), Some((k, _))) => j.cmp(k),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            match order {
+                Ordering::Less => merged.push(lhs.next().unwrap()),
+                Ordering::Greater => {
+                    let (k, m) = rhs.next().unwrap();
+                    merged.push((*k, m * c));
+                }
+                Ordering::Equal => {
+                    let (j, a) = lhs.next().unwrap();
+                    let (_, m) = rhs.next().unwrap();
+                    let sum = a + m * c;
+                    if !sum.is_zero() {
+                        merged.push((j, sum));
+                    }
+                }
+            }
+        }
+        self.multipliers = merged;
+    }
+
+    /// Multiply every multiplier by `c`
+    pub fn scale(&mut self, c: &Rational) {
+        if c.is_zero() {
+            self.multipliers.clear();
+        } else {
+            for (_, m) in self.multipliers.iter_mut() {
+                *m *= c;
+            }
+        }
+    }
+
+    /// Check the combination of `relations`, returning the combined relation.
+    ///
+    /// The combined relation is an equality if only equalities are used, strict if some strict
+    /// inequality is used, and non-strict otherwise.
+    pub fn combine(&self, relations: &[LinRel]) -> Result<LinRel, CertificateError> {
+        let mut lhs = LinExpr::new_zeros(0);
+        let mut constraint = Constraint::Eq;
+        for (j, m) in self.multipliers.iter() {
+            let r = relations
+                .get(*j)
+                .ok_or(CertificateError::IndexOutOfBounds(*j))?;
+            match r.constraint() {
+                Constraint::Eq => {}
+                Constraint::Le | Constraint::Lt if m.is_negative() => {
+                    return Err(CertificateError::NegativeMultiplier(*j));
+                }
+                Constraint::Le => {
+                    if constraint == Constraint::Eq {
+                        constraint = Constraint::Le;
+                    }
+                }
+                Constraint::Lt => constraint = Constraint::Lt,
+                Constraint::Ne => return Err(CertificateError::Disequality(*j)),
+            }
+            lhs += r.lhs() * m;
+        }
+        Ok(match constraint {
+            Constraint::Eq => LinRel::mk_eq(lhs),
+            Constraint::Le => LinRel::mk_le(lhs),
+            _ => LinRel::mk_lt(lhs),
+        })
+    }
+
+    /// Verify that the combination of `relations` is a contradiction between constants
+    pub fn verify(&self, relations: &[LinRel]) -> Result<(), CertificateError> {
+        let combined = self.combine(relations)?;
+        if !combined.lhs().is_const() {
+            Err(CertificateError::NotConstant)
+        } else if combined.is_trivial_contradiction() {
+            Ok(())
+        } else {
+            Err(CertificateError::NotContradiction)
+        }
+    }
+}
+
+/// A certificate that a set of asserted relations is unsatisfiable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnsatCertificate {
+    /// A combination of the assertions that is a contradiction between constants
+    Farkas(Combination),
+    /// The assertions other than disequalities imply `e = 0` for the disequality `e != 0` with
+    /// the given index. This is shown by two contradictory combinations, where the disequality
+    /// is replaced by `e < 0` in `below` and by `-e < 0` in `above`.
+    Disequality {
+        index: usize,
+        below: Combination,
+        above: Combination,
+    },
+}
+
+impl UnsatCertificate {
+    /// Indices of the assertions used by the certificate, in increasing order. The assertions
+    /// with these indices are unsatisfiable on their own.
+    pub fn support(&self) -> Vec<usize> {
+        match self {
+            Self::Farkas(c) => c.support().collect(),
+            Self::Disequality {
+                index,
+                below,
+                above,
+            } => {
+                let mut support: Vec<usize> = below
+                    .support()
+                    .chain(above.support())
+                    .chain(std::iter::once(*index))
+                    .collect();
+                support.sort_unstable();
+                support.dedup();
+                support
+            }
+        }
+    }
+
+    /// Check that the certificate proves that `assertions` are unsatisfiable
+    ///
+    /// ```
+    /// # use presburger::certificate::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # use presburger::lin_sys::LinSys;
+    /// # use presburger::types::Rational;
+    /// # fn main () {
+    /// // 1 - x_1 <= 0, x_1 - 2 x_2 <= 0, x_2 <= 0
+    /// let assertions = LinSys::from_relations(vec![
+    ///     LinRel::mk_le(LinExpr::new(vec![1, -1, 0]).unwrap()),
+    ///     LinRel::mk_le(LinExpr::new(vec![0, 1, -2]).unwrap()),
+    ///     LinRel::mk_le(LinExpr::new(vec![0, 0, 1]).unwrap()),
+    /// ]);
+    /// // [0] + [1] + 2 * [2] is 1 <= 0
+    /// let c = Combination::from_multipliers([
+    ///     (0, Rational::ONE),
+    ///     (1, Rational::ONE),
+    ///     (2, Rational::from(2)),
+    /// ]);
+    /// assert_eq!(UnsatCertificate::Farkas(c).verify(&assertions), Ok(()));
+    /// # }
+    /// ```
+    pub fn verify(&self, assertions: &LinSys) -> Result<(), CertificateError> {
+        match self {
+            Self::Farkas(c) => c.verify(assertions.relations()),
+            Self::Disequality {
+                index,
+                below,
+                above,
+            } => {
+                let ne = assertions
+                    .relations()
+                    .get(*index)
+                    .ok_or(CertificateError::IndexOutOfBounds(*index))?;
+                if !ne.is_disequality() {
+                    return Err(CertificateError::NotADisequality(*index));
+                }
+                let mut relations = assertions.relations().to_vec();
+                relations[*index] = LinRel::mk_lt(ne.lhs().clone());
+                below.verify(&relations)?;
+                relations[*index] = LinRel::mk_lt(-ne.lhs());
+                above.verify(&relations)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eq, le};
+
+    #[test]
+    fn test_combination_arith() {
+        let mut c = Combination::unit(0);
+        c.add_scaled(&Combination::unit(2), &Rational::from(3));
+        c.add_scaled(&Combination::unit(0), &Rational::from(-1));
+        assert_eq!(c.multipliers().len(), 1);
+        assert_eq!(c.support().collect::<Vec<_>>(), vec![2]);
+        c.scale(&Rational::from(2));
+        assert_eq!(c, Combination::from_multipliers([(2, Rational::from(6))]));
+        assert_eq!(c.to_string(), "6 * [2]");
+    }
+
+    #[test]
+    fn test_verify_errors() {
+        // x1 <= 0, 1 - x1 <= 0, x1 = 2, x1 != 0
+        let relations = vec![
+            le!(0, 1),
+            le!(1, -1),
+            eq!(-2, 1),
+            LinRel::mk_ne(LinExpr::new(vec![0, 1]).unwrap()),
+        ];
+        let c = |ms: Vec<(usize, i32)>| {
+            Combination::from_multipliers(ms.into_iter().map(|(j, m)| (j, Rational::from(m))))
+        };
+        assert_eq!(c(vec![(0, 1), (1, 1)]).verify(&relations), Ok(()));
+        // x1 - 2 = 0 and -x1 + 1 <= 0 give -1 <= 0, while -(x1 - 2) = 0 gives 1 <= 0
+        assert_eq!(
+            c(vec![(1, 1), (2, 1)]).verify(&relations),
+            Err(CertificateError::NotContradiction)
+        );
+        assert_eq!(c(vec![(0, 1), (2, -1)]).verify(&relations), Ok(()));
+        assert_eq!(
+            c(vec![(0, 1)]).verify(&relations),
+            Err(CertificateError::NotConstant)
+        );
+        assert_eq!(
+            c(vec![(0, -1), (1, -1)]).verify(&relations),
+            Err(CertificateError::NegativeMultiplier(0))
+        );
+        assert_eq!(
+            c(vec![(3, 1)]).verify(&relations),
+            Err(CertificateError::Disequality(3))
+        );
+        assert_eq!(
+            c(vec![(4, 1)]).verify(&relations),
+            Err(CertificateError::IndexOutOfBounds(4))
+        );
+    }
+
+    #[test]
+    fn test_verify_disequality_errors() {
+        // x1 != 0, x1 <= 0
+        let assertions = LinSys::from_relations(vec![
+            LinRel::mk_ne(LinExpr::new(vec![0, 1]).unwrap()),
+            le!(0, 1),
+        ]);
+        let certificate = |index| UnsatCertificate::Disequality {
+            index,
+            below: Combination::unit(0),
+            above: Combination::unit(0),
+        };
+        assert_eq!(
+            certificate(1).verify(&assertions),
+            Err(CertificateError::NotADisequality(1))
+        );
+        assert_eq!(
+            certificate(2).verify(&assertions),
+            Err(CertificateError::IndexOutOfBounds(2))
+        );
+    }
+
+    #[test]
+    fn test_verify_strict() {
+        // x1 < 0, -x1 <= 0 combine to 0 < 0
+        let relations = vec![LinRel::mk_lt(LinExpr::new(vec![0, 1]).unwrap()), le!(0, -1)];
+        let c = Combination::from_multipliers([(0, Rational::ONE), (1, Rational::ONE)]);
+        assert_eq!(c.verify(&relations), Ok(()));
+        // x1 <= 0, -x1 <= 0 combine to 0 <= 0
+        let relations = vec![le!(0, 1), le!(0, -1)];
+        assert_eq!(
+            c.verify(&relations),
+            Err(CertificateError::NotContradiction)
+        );
+    }
+}
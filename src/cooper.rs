@@ -0,0 +1,528 @@
+//! Cooper's quantifier elimination for Presburger arithmetic.
+//!
+//! Quantifiers are eliminated innermost first, so that the body of each quantifier is
+//! quantifier-free. `forall x. p` is treated as `~(exists x. ~p)`, and `exists x. p` is eliminated
+//! as follows, where `p` is in NNF with atoms `0 < e`, `e = 0`, `e != 0`, `d | e` and `~(d | e)`
+//! over the integers:
+//!
+//! 1. The coefficients of `x` are scaled to `+/- l`, where `l` is their lcm, and `l x` is replaced
+//!    by a fresh `x'`, adding the conjunct `l | x'`. Every coefficient of `x'` is now `+/- 1`.
+//! 2. `p(-inf)` replaces the atoms that bound `x'` by their value for arbitrarily small `x'`. It
+//!    only depends on `x'` through divisibility atoms, so it is periodic with period `D`, the lcm
+//!    of their divisors.
+//! 3. The B-set collects the terms `b` such that the smallest solution `x'` of `p`, if there is
+//!    one, is `b + j` for some `1 <= j <= D`.
+//!
+//! Then `exists x. p` is equivalent to the finite disjunction over `1 <= j <= D` of `p(-inf)[j/x']`
+//! and of `p[b + j/x']` for `b` in the B-set. If the A-set, the analogous set of upper bounds, is
+//! smaller, the same is done for `+inf` by negating `x'`.
+//!
+//! Rational coefficients are cleared by scaling each atom, and quantifiers over logical variables
+//! are expanded to `p[T/P] \/ p[F/P]`.
+
+use crate::ast::{Atom, Formula, Term, Var};
+use crate::lin_expr::LinExpr;
+use crate::lower::lower_term;
+use crate::qe::{self, Literal, to_term};
+use crate::space::Space;
+use crate::sym_mod::{div_floor, lcm};
+use crate::types::{Integer, Rational};
+use dashu::base::Abs;
+use std::convert::Infallible;
+
+/// A linear atom with integer coefficients
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Lit {
+    /// 0 < e
+    Pos(LinExpr),
+    /// e = 0
+    Zero(LinExpr),
+    /// e != 0
+    NonZero(LinExpr),
+    /// d | e, with d > 0
+    Dvd(Integer, LinExpr),
+    /// ~(d | e), with d > 0
+    NotDvd(Integer, LinExpr),
+}
+
+impl Lit {
+    fn expr(&self) -> &LinExpr {
+        match self {
+            Lit::Pos(e) | Lit::Zero(e) | Lit::NonZero(e) | Lit::Dvd(_, e) | Lit::NotDvd(_, e) => e,
+        }
+    }
+
+    /// Apply `f` to the expression of the literal
+    fn map_expr(self, f: impl FnOnce(LinExpr) -> LinExpr) -> Lit {
+        match self {
+            Lit::Pos(e) => Lit::Pos(f(e)),
+            Lit::Zero(e) => Lit::Zero(f(e)),
+            Lit::NonZero(e) => Lit::NonZero(f(e)),
+            Lit::Dvd(d, e) => Lit::Dvd(d, f(e)),
+            Lit::NotDvd(d, e) => Lit::NotDvd(d, f(e)),
+        }
+    }
+
+    /// The coefficient of `x_k`
+    fn coeff(&self, k: usize) -> &Rational {
+        self.expr().coeff_unchecked(k)
+    }
+
+    /// Make the coefficient of `x_k` non-negative, unless the literal is a strict inequality
+    fn orient(self, k: usize) -> Lit {
+        if matches!(self, Lit::Pos(_)) || !self.coeff(k).is_negative() {
+            self
+        } else {
+            self.map_expr(|e| -e)
+        }
+    }
+
+    /// Evaluate constant literals and reduce the constant of divisibility literals modulo `d`
+    fn simplify(self) -> Qf {
+        match self {
+            Lit::Dvd(d, mut e) => match divisible(&d, &mut e) {
+                Some(b) => Qf::Bool(b),
+                None => Qf::Lit(Lit::Dvd(d, e)),
+            },
+            Lit::NotDvd(d, mut e) => match divisible(&d, &mut e) {
+                Some(b) => Qf::Bool(!b),
+                None => Qf::Lit(Lit::NotDvd(d, e)),
+            },
+            lit if lit.expr().is_const() => {
+                let c = lit.expr().const_();
+                Qf::Bool(match lit {
+                    Lit::Pos(_) => *c > Rational::ZERO,
+                    Lit::Zero(_) => c.is_zero(),
+                    _ => !c.is_zero(),
+                })
+            }
+            lit => Qf::Lit(lit),
+        }
+    }
+}
+
+/// Decide `d | e` if `e` is constant or `d = 1`, otherwise reduce the constant of `e` modulo `d`
+fn divisible(d: &Integer, e: &mut LinExpr) -> Option<bool> {
+    if *d == Integer::ONE {
+        return Some(true);
+    }
+    let c = e.const_().numerator();
+    let r = &c - d * div_floor(c.clone(), d.clone());
+    if e.is_const() {
+        Some(r == Integer::ZERO)
+    } else {
+        e.set_const(Rational::from(r));
+        None
+    }
+}
+
+/// A quantifier-free formula in NNF over linear atoms
+type Qf = qe::Qf<Lit>;
+
+impl Literal for Lit {
+    type Error = Infallible;
+
+    /// Convert the atom `a`, or its negation if `negated`, clearing the denominators of its terms
+    fn from_atom(a: &Atom, negated: bool, space: &mut Space) -> Result<Qf, Infallible> {
+        Ok(match a {
+            Atom::Equality(t1, t2) => {
+                let lhs = lower_term(t1, space);
+                let (e, _) = integral(lhs - lower_term(t2, space));
+                if negated {
+                    Lit::NonZero(e).simplify()
+                } else {
+                    Lit::Zero(e).simplify()
+                }
+            }
+            Atom::LessEq(t1, t2) => {
+                let lhs = lower_term(t1, space);
+                let e = lower_term(t2, space) - lhs;
+                if negated {
+                    // t2 < t1
+                    Lit::Pos(integral(-e).0).simplify()
+                } else {
+                    // 0 <= t2 - t1 iff 0 < t2 - t1 + 1
+                    let (mut e, _) = integral(e);
+                    e.set_const(e.const_() + Rational::ONE);
+                    Lit::Pos(e).simplify()
+                }
+            }
+            Atom::Divides(d, t) => {
+                let (e, m) = integral(lower_term(t, space));
+                let d = d.clone().abs() * m;
+                match (d == Integer::ZERO, negated) {
+                    (true, false) => Lit::Zero(e).simplify(),
+                    (true, true) => Lit::NonZero(e).simplify(),
+                    (false, false) => Lit::Dvd(d, e).simplify(),
+                    (false, true) => Lit::NotDvd(d, e).simplify(),
+                }
+            }
+            Atom::TruthValue(_) | Atom::LogicalVar(_) => unreachable!("not a linear atom: {a}"),
+        })
+    }
+
+    fn negate(&self) -> Lit {
+        match self.clone() {
+            // ~(0 < e) iff 0 < 1 - e
+            Lit::Pos(e) => Lit::Pos(LinExpr::constant(1, 0) - e),
+            Lit::Zero(e) => Lit::NonZero(e),
+            Lit::NonZero(e) => Lit::Zero(e),
+            Lit::Dvd(d, e) => Lit::NotDvd(d, e),
+            Lit::NotDvd(d, e) => Lit::Dvd(d, e),
+        }
+    }
+
+    fn extend_to(self, nvars: usize) -> Lit {
+        self.map_expr(|mut e| {
+            e.extend_to(nvars);
+            e
+        })
+    }
+
+    fn to_formula(&self, space: &Space) -> Formula {
+        match self {
+            // 0 < e iff 1 <= e
+            Lit::Pos(e) => Formula::atom(Atom::less_eq(Term::num(1), to_term(e, space))),
+            Lit::Zero(e) => Formula::atom(Atom::equality(to_term(e, space), Term::num(0))),
+            Lit::NonZero(e) => Formula::fnot(Formula::atom(Atom::equality(
+                to_term(e, space),
+                Term::num(0),
+            ))),
+            Lit::Dvd(d, e) => Formula::atom(Atom::divides(d.clone(), to_term(e, space))),
+            Lit::NotDvd(d, e) => {
+                Formula::fnot(Formula::atom(Atom::divides(d.clone(), to_term(e, space))))
+            }
+        }
+    }
+
+    fn exists(p: Qf, k: usize, nvars: usize) -> Qf {
+        cooper(p, k, nvars)
+    }
+}
+
+/// Scale `e` by the positive lcm `m` of its denominators, returning `m e` and `m`
+fn integral(e: LinExpr) -> (LinExpr, Integer) {
+    let m = e
+        .terms()
+        .fold(Integer::ONE, |m, (_, c)| lcm(&m, &c.denominator()));
+    (e * Rational::from(m.clone()), m)
+}
+
+/// Substitute `s` for `x_k` in `e`
+fn subs_expr(mut e: LinExpr, k: usize, s: &LinExpr) -> LinExpr {
+    let c = e.coeff_unchecked(k).clone();
+    if c.is_zero() {
+        return e;
+    }
+    e.set_coeff_unchecked(k, Rational::ZERO);
+    e + s * c
+}
+
+/// Substitute `s` for `x_k` in `p`
+fn subs(p: &Qf, k: usize, s: &LinExpr) -> Qf {
+    p.map_lits(&mut |l| l.clone().map_expr(|e| subs_expr(e, k, s)).simplify())
+}
+
+/// The terms `b` such that the smallest solution `x_k` of `p`, whose coefficients of `x_k` are
+/// all `+/- 1`, is `b + j` for some `1 <= j <= D`, unless `p(-inf)` has a solution.
+fn bset(p: &Qf, k: usize) -> Vec<LinExpr> {
+    let mut bs: Vec<LinExpr> = Vec::new();
+    p.for_each_lit(&mut |l| {
+        if *l.coeff(k) != Rational::ONE {
+            return;
+        }
+        let mut rest = l.expr().clone();
+        rest.set_coeff_unchecked(k, Rational::ZERO);
+        let b = match l {
+            // x_k > -rest
+            Lit::Pos(_) | Lit::NonZero(_) => -rest,
+            // x_k = -rest
+            Lit::Zero(_) => -rest - LinExpr::constant(1, 0),
+            Lit::Dvd(_, _) | Lit::NotDvd(_, _) => return,
+        };
+        if !bs.contains(&b) {
+            bs.push(b);
+        }
+    });
+    bs
+}
+
+/// Eliminate `x_k` from `exists x_k. p`, where `p` ranges over `nvars` variables
+fn cooper(p: Qf, k: usize, nvars: usize) -> Qf {
+    let mut l = Integer::ONE;
+    let mut occurs = false;
+    p.for_each_lit(&mut |lit| {
+        if !lit.coeff(k).is_zero() {
+            occurs = true;
+            l = lcm(&l, &lit.coeff(k).numerator());
+        }
+    });
+    if !occurs {
+        return p;
+    }
+
+    // scale the coefficients of x_k to +/- l and substitute x' = l x_k
+    let mut unit = |lit: &Lit| {
+        let c = lit.coeff(k).clone();
+        if c.is_zero() {
+            return Qf::Lit(lit.clone());
+        }
+        let m = Rational::from(l.clone()) / c.clone().abs();
+        let sign = if c.is_negative() {
+            Rational::NEG_ONE
+        } else {
+            Rational::ONE
+        };
+        let lit = match lit.clone() {
+            Lit::Dvd(d, e) => Lit::Dvd(d * m.numerator(), e),
+            Lit::NotDvd(d, e) => Lit::NotDvd(d * m.numerator(), e),
+            lit => lit,
+        };
+        let lit = lit.map_expr(|e| {
+            let mut e = e * &m;
+            e.set_coeff_unchecked(k, sign.clone());
+            e
+        });
+        Qf::Lit(lit.orient(k))
+    };
+    let mut p = p.map_lits(&mut unit);
+    if l != Integer::ONE {
+        p = Qf::and(p, Qf::Lit(Lit::Dvd(l, LinExpr::var(k, nvars))));
+    }
+
+    // use the smaller of the B-set and the A-set
+    let mut b_set = bset(&p, k);
+    let negated = p.map_lits(&mut |lit| {
+        let c = -lit.coeff(k).clone();
+        let lit = lit.clone().map_expr(|mut e| {
+            e.set_coeff_unchecked(k, c);
+            e
+        });
+        Qf::Lit(lit.orient(k))
+    });
+    let a_set = bset(&negated, k);
+    if a_set.len() < b_set.len() {
+        p = negated;
+        b_set = a_set;
+    }
+
+    let mut period = Integer::ONE;
+    p.for_each_lit(&mut |lit| match lit {
+        Lit::Dvd(d, e) | Lit::NotDvd(d, e) if e.supported(k) => period = lcm(&period, d),
+        _ => (),
+    });
+    let minus_inf = p.map_lits(&mut |lit| match lit {
+        Lit::Pos(e) if e.supported(k) => Qf::Bool(e.coeff_unchecked(k).is_negative()),
+        Lit::Zero(e) if e.supported(k) => Qf::Bool(false),
+        Lit::NonZero(e) if e.supported(k) => Qf::Bool(true),
+        lit => Qf::Lit(lit.clone()),
+    });
+
+    let mut res = Qf::Bool(false);
+    let mut j = Integer::ONE;
+    while j <= period {
+        let jj = LinExpr::constant(Rational::from(j.clone()), nvars);
+        res = Qf::or(res, subs(&minus_inf, k, &jj));
+        for b in b_set.iter() {
+            res = Qf::or(res, subs(&p, k, &(b + &jj)));
+        }
+        if matches!(res, Qf::Bool(true)) {
+            break;
+        }
+        j += Integer::ONE;
+    }
+    res
+}
+
+/// Eliminate `exists v. p`, returning an equivalent quantifier-free formula
+pub fn eliminate_exists(v: &Var, p: &Formula) -> Formula {
+    let Ok(q) = qe::eliminate_exists::<Lit>(v, p);
+    q
+}
+
+/// Eliminate all quantifiers of `p`, innermost first, returning an equivalent quantifier-free
+/// formula over the integers. The result is in NNF where a quantifier was eliminated, and may
+/// contain divisibility atoms.
+///
+/// ```
+/// # use presburger::ast::{Atom, Formula, Term, Var};
+/// # use presburger::cooper::eliminate;
+/// # use presburger::types::Rational;
+/// # fn main () {
+/// // exists x. 2 x = y
+/// let p = Formula::exists(
+///     Var::new("x"),
+///     Formula::atom(Atom::equality(
+///         Term::scalar_var(Rational::from(2), "x"),
+///         Term::scalar_var(Rational::ONE, "y"),
+///     )),
+/// );
+/// let y = Term::scalar_var(Rational::ONE, "y");
+/// assert_eq!(eliminate(&p), Formula::atom(Atom::divides(2, y)));
+/// # }
+/// ```
+pub fn eliminate(p: &Formula) -> Formula {
+    let Ok(q) = qe::eliminate::<Lit>(p);
+    q
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qe_testing::{arb_qf_formula, env, eval, is_quantifier_free, var};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_cooper_divisibility() {
+        // exists x. 3 x + 1 <= y /\ y <= 3 x + 1, i.e. y = 1 mod 3
+        let p = Formula::exists(
+            Var::new("x"),
+            Formula::and(
+                Formula::atom(Atom::less_eq(
+                    Term::tadd(var(3, "x"), Term::num(1)),
+                    var(1, "y"),
+                )),
+                Formula::atom(Atom::less_eq(
+                    var(1, "y"),
+                    Term::tadd(var(3, "x"), Term::num(1)),
+                )),
+            ),
+        );
+        let q = eliminate(&p);
+        assert!(is_quantifier_free(&q));
+        assert!(q.to_string().contains('|'));
+        for y in -10i64..10 {
+            assert_eq!(eval(&q, &env(&[("y", y)])), y.rem_euclid(3) == 1, "y = {y}");
+        }
+
+        // exists x. 1/2 x = y /\ 3 | x, i.e. 3 | y
+        let p = Formula::exists(
+            Var::new("x"),
+            Formula::and(
+                Formula::atom(Atom::equality(
+                    Term::scalar_var(Rational::ONE / Rational::from(2), "x"),
+                    var(1, "y"),
+                )),
+                Formula::atom(Atom::divides(3, var(1, "x"))),
+            ),
+        );
+        let q = eliminate(&p);
+        for y in -10..10 {
+            assert_eq!(eval(&q, &env(&[("y", y)])), y % 3 == 0, "y = {y}");
+        }
+    }
+
+    #[test]
+    fn test_cooper_closed_formulas() {
+        // forall x. exists y. x = 2 y \/ x = 2 y + 1
+        let x = || var(1, "x");
+        let p = Formula::forall(
+            Var::new("x"),
+            Formula::exists(
+                Var::new("y"),
+                Formula::or(
+                    Formula::atom(Atom::equality(x(), var(2, "y"))),
+                    Formula::atom(Atom::equality(x(), Term::tadd(var(2, "y"), Term::num(1)))),
+                ),
+            ),
+        );
+        assert_eq!(eliminate(&p), Formula::atom(Atom::truth(true)));
+
+        // forall x. exists y. x = 2 y
+        let p = Formula::forall(
+            Var::new("x"),
+            Formula::exists(
+                Var::new("y"),
+                Formula::atom(Atom::equality(x(), var(2, "y"))),
+            ),
+        );
+        assert_eq!(eliminate(&p), Formula::atom(Atom::truth(false)));
+
+        // exists x. forall y. x <= y is false over the integers
+        let p = Formula::exists(
+            Var::new("x"),
+            Formula::forall(
+                Var::new("y"),
+                Formula::atom(Atom::less_eq(x(), var(1, "y"))),
+            ),
+        );
+        assert_eq!(eliminate(&p), Formula::atom(Atom::truth(false)));
+
+        // forall x. exists y. x < y < x + 1 is false over the integers
+        let p = Formula::forall(
+            Var::new("x"),
+            Formula::exists(
+                Var::new("y"),
+                Formula::and(
+                    Formula::fnot(Formula::atom(Atom::less_eq(var(1, "y"), x()))),
+                    Formula::fnot(Formula::atom(Atom::less_eq(
+                        Term::tadd(x(), Term::num(1)),
+                        var(1, "y"),
+                    ))),
+                ),
+            ),
+        );
+        assert_eq!(eliminate(&p), Formula::atom(Atom::truth(false)));
+    }
+
+    #[test]
+    fn test_cooper_logical_vars() {
+        // exists P. (P ==> x <= 0) /\ (~P ==> 2 | x), i.e. x <= 0 \/ 2 | x
+        let pvar = || Formula::atom(Atom::var("P"));
+        let p = Formula::exists(
+            Var::new("P"),
+            Formula::and(
+                Formula::implies(
+                    pvar(),
+                    Formula::atom(Atom::less_eq(var(1, "x"), Term::num(0))),
+                ),
+                Formula::implies(
+                    Formula::fnot(pvar()),
+                    Formula::atom(Atom::divides(2, var(1, "x"))),
+                ),
+            ),
+        );
+        let q = eliminate(&p);
+        assert!(!q.to_string().contains('P'));
+        for x in -5..5 {
+            assert_eq!(eval(&q, &env(&[("x", x)])), x <= 0 || x % 2 == 0);
+        }
+
+        // free logical variables are kept
+        let p = Formula::exists(
+            Var::new("x"),
+            Formula::and(
+                pvar(),
+                Formula::atom(Atom::equality(var(1, "x"), var(1, "y"))),
+            ),
+        );
+        assert_eq!(eliminate(&p), pvar());
+    }
+
+    fn arb_term() -> impl Strategy<Value = Term> {
+        (-3i64..=3, -3i64..=3, -6i64..=6)
+            .prop_map(|(a, b, c)| Term::tadd(var(a, "x"), Term::tadd(var(b, "y"), Term::num(c))))
+    }
+
+    fn arb_atom() -> impl Strategy<Value = Atom> {
+        prop_oneof![
+            (arb_term(), arb_term()).prop_map(|(t1, t2)| Atom::less_eq(t1, t2)),
+            (arb_term(), arb_term()).prop_map(|(t1, t2)| Atom::equality(t1, t2)),
+            (-4i64..=4, arb_term()).prop_map(|(d, t)| Atom::divides(d, t)),
+        ]
+    }
+
+    proptest! {
+        /// `exists x. p(x, y)` agrees with a search for `x` in a range that contains a witness if
+        /// there is one, for small coefficients and `y`
+        #[test]
+        fn cooper_agrees_with_search(p in arb_qf_formula(arb_atom())) {
+            let q = eliminate(&Formula::exists(Var::new("x"), p.clone()));
+            prop_assert!(is_quantifier_free(&q));
+            for y in -6..=6 {
+                let expected = (-100..=100).any(|x| eval(&p, &env(&[("x", x), ("y", y)])));
+                prop_assert_eq!(eval(&q, &env(&[("y", y)])), expected, "y = {}, q = {}", y, q);
+            }
+        }
+    }
+}
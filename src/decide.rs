@@ -0,0 +1,394 @@
+//! Decision procedure for closed formulas of Presburger arithmetic, and of linear arithmetic over
+//! the rationals.
+//!
+//! [`decide`] runs the whole pipeline: the formula is checked to be closed and well-sorted,
+//! converted to NNF (see [`crate::nnf`]), simplified, its quantifiers are eliminated by Cooper's
+//! method (see [`crate::cooper`]) over the integers, or by virtual substitution (see
+//! [`crate::virtual_subs`]) over the rationals, and the resulting ground formula is evaluated.
+
+use crate::ast::{Atom, Formula, Term, Var};
+use crate::lower::LowerError;
+use crate::nnf::to_nnf;
+use crate::types::Domain;
+use crate::{cooper, virtual_subs};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecideError {
+    /// The formula is not closed, the given variable occurs free
+    FreeVar(Var),
+    /// The given variable is used both as a logical and as a numeric variable
+    IllSorted(Var),
+    /// The formula contains an atom, rendered here, that is not supported over the domain
+    Unsupported(String),
+}
+
+impl fmt::Display for DecideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FreeVar(v) => write!(f, "Variable {v} is free in a formula that must be closed"),
+            Self::IllSorted(v) => {
+                write!(
+                    f,
+                    "Variable {v} is used both as a logical and a numeric variable"
+                )
+            }
+            Self::Unsupported(a) => write!(f, "Atom {a} is not supported over this domain"),
+        }
+    }
+}
+
+impl Error for DecideError {}
+
+impl From<LowerError> for DecideError {
+    fn from(e: LowerError) -> Self {
+        match e {
+            // virtual substitution only lowers the atoms of quantifier-free formulas, and handles
+            // the logical variables itself
+            LowerError::Quantified(v) => {
+                unreachable!("quantifier over {v} in an atom lowered by virtual substitution")
+            }
+            LowerError::LogicalVar(v) => {
+                unreachable!("logical variable {v} lowered by virtual substitution")
+            }
+            LowerError::NonConjunctive(p) | LowerError::Divides(p) => DecideError::Unsupported(p),
+        }
+    }
+}
+
+/// Decide whether the closed formula `p` is true when its variables range over `domain`.
+///
+/// ```
+/// # use presburger::ast::{Atom, Formula, Term, Var};
+/// # use presburger::decide;
+/// # use presburger::types::{Domain, Rational};
+/// # fn main () {
+/// // forall x. exists y. x = 2 y
+/// let p = Formula::forall(
+///     Var::new("x"),
+///     Formula::exists(
+///         Var::new("y"),
+///         Formula::atom(Atom::equality(
+///             Term::scalar_var(Rational::ONE, "x"),
+///             Term::scalar_var(Rational::from(2), "y"),
+///         )),
+///     ),
+/// );
+/// assert_eq!(decide(&p, Domain::Integer), Ok(false));
+/// assert_eq!(decide(&p, Domain::Rational), Ok(true));
+///
+/// // x is free in exists y. x = 2 y
+/// let Formula::Forall(x, q) = p else { unreachable!() };
+/// assert_eq!(decide(&q, Domain::Integer), Err(presburger::decide::DecideError::FreeVar(x)));
+/// # }
+/// ```
+pub fn decide(p: &Formula, domain: Domain) -> Result<bool, DecideError> {
+    check_well_formed(p, domain)?;
+    let p = simplify(to_nnf(p.clone()));
+    let ground = match domain {
+        Domain::Integer => cooper::eliminate(&p),
+        Domain::Rational => virtual_subs::eliminate(&p)?,
+    };
+    eval_ground(&ground)
+}
+
+/// The sort of a variable
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Sort {
+    Logical,
+    Numeric,
+}
+
+/// Check that `p` is closed, that no variable is used with both sorts, and that its atoms are
+/// supported over `domain`
+fn check_well_formed(p: &Formula, domain: Domain) -> Result<(), DecideError> {
+    check_vars(p, &mut Vec::new())?;
+    if domain == Domain::Rational
+        && let Some(a) = find_divides(p)
+    {
+        return Err(DecideError::Unsupported(a.to_string()));
+    }
+    Ok(())
+}
+
+/// Check the variables of `p` under the binders `bound`, innermost last, recording the sort of
+/// each bound variable once it is used
+fn check_vars<'a>(
+    p: &'a Formula,
+    bound: &mut Vec<(&'a Var, Option<Sort>)>,
+) -> Result<(), DecideError> {
+    match p {
+        Formula::Not(q) => check_vars(q, bound),
+        Formula::And(p, q) | Formula::Or(p, q) | Formula::Impl(p, q) | Formula::Iff(p, q) => {
+            check_vars(p, bound)?;
+            check_vars(q, bound)
+        }
+        Formula::Exists(v, q) | Formula::Forall(v, q) => {
+            bound.push((v, None));
+            let res = check_vars(q, bound);
+            bound.pop();
+            res
+        }
+        Formula::Atom(a) => match &**a {
+            Atom::TruthValue(_) => Ok(()),
+            Atom::LogicalVar(v) => occurs(v, Sort::Logical, bound),
+            Atom::Equality(t1, t2) | Atom::LessEq(t1, t2) => {
+                for v in term_vars(t1).into_iter().chain(term_vars(t2)) {
+                    occurs(v, Sort::Numeric, bound)?;
+                }
+                Ok(())
+            }
+            Atom::Divides(_, t) => {
+                for v in term_vars(t) {
+                    occurs(v, Sort::Numeric, bound)?;
+                }
+                Ok(())
+            }
+        },
+    }
+}
+
+/// Check an occurrence of `v` with the given sort under the binders `bound`
+fn occurs(v: &Var, sort: Sort, bound: &mut [(&Var, Option<Sort>)]) -> Result<(), DecideError> {
+    match bound.iter_mut().rev().find(|(w, _)| *w == v) {
+        None => Err(DecideError::FreeVar(v.clone())),
+        Some((_, Some(s))) if *s != sort => Err(DecideError::IllSorted(v.clone())),
+        Some((_, s)) => {
+            *s = Some(sort);
+            Ok(())
+        }
+    }
+}
+
+/// The variables of `t`, with repetitions
+fn term_vars(t: &Term) -> Vec<&Var> {
+    match t {
+        Term::Num(_) => vec![],
+        Term::ScalarVar(_, v) => vec![v],
+        Term::Add(t1, t2) => {
+            let mut vars = term_vars(t1);
+            vars.extend(term_vars(t2));
+            vars
+        }
+    }
+}
+
+/// The first divisibility atom of `p`, if any
+fn find_divides(p: &Formula) -> Option<&Atom> {
+    match p {
+        Formula::Not(q) | Formula::Exists(_, q) | Formula::Forall(_, q) => find_divides(q),
+        Formula::And(p, q) | Formula::Or(p, q) | Formula::Impl(p, q) | Formula::Iff(p, q) => {
+            find_divides(p).or_else(|| find_divides(q))
+        }
+        Formula::Atom(a) => match &**a {
+            Atom::Divides(_, _) => Some(a),
+            _ => None,
+        },
+    }
+}
+
+/// Propagate truth values through the connectives of a formula in NNF, and drop quantifiers whose
+/// body is a truth value
+fn simplify(p: Formula) -> Formula {
+    let truth = |p: &Formula| match p {
+        Formula::Atom(a) => match **a {
+            Atom::TruthValue(b) => Some(b),
+            _ => None,
+        },
+        Formula::Not(q) => match &**q {
+            Formula::Atom(a) => match **a {
+                Atom::TruthValue(b) => Some(!b),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    };
+    let constant = |b| Formula::atom(Atom::truth(b));
+    match p {
+        Formula::Not(_) => truth(&p).map(constant).unwrap_or(p),
+        Formula::And(p, q) => {
+            let (p, q) = (simplify(*p), simplify(*q));
+            match (truth(&p), truth(&q)) {
+                (Some(false), _) | (_, Some(false)) => constant(false),
+                (Some(true), _) => q,
+                (_, Some(true)) => p,
+                _ => Formula::and(p, q),
+            }
+        }
+        Formula::Or(p, q) => {
+            let (p, q) = (simplify(*p), simplify(*q));
+            match (truth(&p), truth(&q)) {
+                (Some(true), _) | (_, Some(true)) => constant(true),
+                (Some(false), _) => q,
+                (_, Some(false)) => p,
+                _ => Formula::or(p, q),
+            }
+        }
+        Formula::Exists(v, q) => {
+            let q = simplify(*q);
+            truth(&q).map(constant).unwrap_or(Formula::exists(v, q))
+        }
+        Formula::Forall(v, q) => {
+            let q = simplify(*q);
+            truth(&q).map(constant).unwrap_or(Formula::forall(v, q))
+        }
+        p => p,
+    }
+}
+
+/// Evaluate a formula without variables
+fn eval_ground(p: &Formula) -> Result<bool, DecideError> {
+    match p {
+        Formula::Not(q) => Ok(!eval_ground(q)?),
+        Formula::And(p, q) => Ok(eval_ground(p)? && eval_ground(q)?),
+        Formula::Or(p, q) => Ok(eval_ground(p)? || eval_ground(q)?),
+        Formula::Impl(p, q) => Ok(!eval_ground(p)? || eval_ground(q)?),
+        Formula::Iff(p, q) => Ok(eval_ground(p)? == eval_ground(q)?),
+        Formula::Exists(_, _) | Formula::Forall(_, _) => {
+            unreachable!("quantifiers are eliminated before evaluation")
+        }
+        Formula::Atom(a) => a.eval(&|_| None).map_err(DecideError::FreeVar),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qe_testing::{Env, arb_qf_formula, env, eval, test_points, var};
+    use proptest::prelude::*;
+
+    fn le(t1: Term, t2: Term) -> Formula {
+        Formula::atom(Atom::less_eq(t1, t2))
+    }
+
+    #[test]
+    fn test_well_formed() {
+        let x = Var::new("x");
+        let p = le(var(1, "x"), Term::num(0));
+        assert_eq!(
+            decide(&p, Domain::Integer),
+            Err(DecideError::FreeVar(x.clone()))
+        );
+        assert_eq!(
+            decide(&Formula::exists(x.clone(), p.clone()), Domain::Integer),
+            Ok(true)
+        );
+
+        // the binder of y does not bind x
+        let q = Formula::exists(Var::new("y"), p.clone());
+        assert_eq!(
+            decide(&q, Domain::Rational),
+            Err(DecideError::FreeVar(x.clone()))
+        );
+
+        // x is used as a logical and a numeric variable
+        let q = Formula::exists(
+            x.clone(),
+            Formula::and(p.clone(), Formula::atom(Atom::var("x"))),
+        );
+        assert_eq!(
+            decide(&q, Domain::Integer),
+            Err(DecideError::IllSorted(x.clone()))
+        );
+
+        // but different binders of x may give it different sorts
+        let q = Formula::and(
+            Formula::forall(x.clone(), le(var(1, "x"), var(1, "x"))),
+            Formula::exists(x.clone(), Formula::atom(Atom::var("x"))),
+        );
+        assert_eq!(decide(&q, Domain::Integer), Ok(true));
+        let q = Formula::exists(
+            x.clone(),
+            Formula::and(
+                Formula::atom(Atom::var("x")),
+                Formula::exists(x.clone(), p.clone()),
+            ),
+        );
+        assert_eq!(decide(&q, Domain::Rational), Ok(true));
+
+        // divisibility is only supported over the integers
+        let q = Formula::forall(x, Formula::atom(Atom::divides(1, var(1, "x"))));
+        assert_eq!(decide(&q, Domain::Integer), Ok(true));
+        assert!(matches!(
+            decide(&q, Domain::Rational),
+            Err(DecideError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_simplify() {
+        let t = || Formula::atom(Atom::truth(true));
+        let f = || Formula::atom(Atom::truth(false));
+        let p = le(var(1, "x"), Term::num(0));
+        assert_eq!(simplify(Formula::and(t(), p.clone())), p);
+        assert_eq!(simplify(Formula::or(p.clone(), Formula::fnot(f()))), t());
+        assert_eq!(
+            simplify(Formula::forall(Var::new("x"), Formula::and(p, f()))),
+            f()
+        );
+    }
+
+    #[test]
+    fn test_decide_logical_vars() {
+        // forall P. exists Q. P <==> ~Q
+        let p = Formula::forall(
+            Var::new("P"),
+            Formula::exists(
+                Var::new("Q"),
+                Formula::iff(
+                    Formula::atom(Atom::var("P")),
+                    Formula::fnot(Formula::atom(Atom::var("Q"))),
+                ),
+            ),
+        );
+        assert_eq!(decide(&p, Domain::Integer), Ok(true));
+        assert_eq!(decide(&p, Domain::Rational), Ok(true));
+    }
+
+    fn arb_term() -> impl Strategy<Value = Term> {
+        (-3i64..=3, -6i64..=6).prop_map(|(a, c)| Term::tadd(var(a, "x"), Term::num(c)))
+    }
+
+    fn arb_atom(divides: bool) -> impl Strategy<Value = Atom> {
+        prop_oneof![
+            (arb_term(), arb_term()).prop_map(|(t1, t2)| Atom::less_eq(t1, t2)),
+            (arb_term(), arb_term()).prop_map(|(t1, t2)| Atom::equality(t1, t2)),
+            (1i64..=4, arb_term()).prop_map(move |(d, t)| if divides {
+                Atom::divides(d, t)
+            } else {
+                Atom::less_eq(t, Term::num(d))
+            }),
+        ]
+    }
+
+    proptest! {
+        /// `exists x. p` and `forall x. p` over the integers agree with a search for `x` in a range
+        /// that contains a witness if there is one
+        #[test]
+        fn decide_integer_agrees_with_search(p in arb_qf_formula(arb_atom(true))) {
+            let values: Vec<bool> = (-60..=60).map(|c| eval(&p, &env(&[("x", c)]))).collect();
+            let x = Var::new("x");
+            let exists = decide(&Formula::exists(x.clone(), p.clone()), Domain::Integer);
+            prop_assert_eq!(exists, Ok(values.iter().any(|b| *b)));
+            let forall = decide(&Formula::forall(x, p), Domain::Integer);
+            prop_assert_eq!(forall, Ok(values.iter().all(|b| *b)));
+        }
+
+        /// `exists x. p` and `forall x. p` over the rationals agree with evaluating `p` at the
+        /// zeros of its atoms, between them, and beyond them
+        #[test]
+        fn decide_rational_agrees_with_roots(p in arb_qf_formula(arb_atom(false))) {
+            let values: Vec<bool> = test_points(&p, "x", &Env::new())
+                .into_iter()
+                .map(|c| eval(&p, &env(&[("x", c)])))
+                .collect();
+            let x = Var::new("x");
+            let exists = decide(&Formula::exists(x.clone(), p.clone()), Domain::Rational);
+            prop_assert_eq!(exists, Ok(values.iter().any(|b| *b)));
+            let forall = decide(&Formula::forall(x, p), Domain::Rational);
+            prop_assert_eq!(forall, Ok(values.iter().all(|b| *b)));
+        }
+    }
+}
@@ -0,0 +1,334 @@
+//! Gaussian elimination of linear equalities over the rationals.
+//!
+//! The equalities `e_j = 0` of a system are reduced to rows `x_p + f_p = 0`, one for each *pivot*
+//! variable `x_p`, where `f_p` only supports the *free* variables, i.e. those that are not pivots.
+//! This is the reduced row echelon form of the equalities, up to the order of the variables. The
+//! number of rows is the rank of the equalities, and the rows describe their solutions
+//! parametrically: `x_p = -f_p` for any values of the free variables.
+//!
+//! Each row is a combination of the equalities, which is kept along with it. When an equality
+//! reduces to a constant `c = 0` with `c != 0`, its combination is a certificate that the
+//! equalities are inconsistent, see [`crate::certificate`].
+
+use crate::certificate::Combination;
+use crate::lin_expr::LinExpr;
+use crate::lin_rel::LinRel;
+use crate::model::{Model, ModelError};
+use crate::types::Rational;
+use std::collections::HashMap;
+
+/// Equalities solved for their pivot variables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Echelon {
+    nvars: usize,
+    // rows[k] is `x_{pivots[k]} + f = 0`, where `f` supports no pivot; sorted by pivot
+    rows: Vec<LinExpr>,
+    pivots: Vec<usize>,
+    // origins[k] is the combination of the equalities that gives rows[k]
+    origins: Vec<Combination>,
+}
+
+impl Echelon {
+    /// Reduce the equalities among `relations`, ignoring the other relations. If the equalities
+    /// are inconsistent, return a combination of them that is a contradiction `c = 0`, with
+    /// indices into `relations`.
+    ///
+    /// The pivot is chosen to limit fill-in by the Markowitz criterion: `x_i` in the row `e_k`
+    /// minimizes `(r_k - 1) (c_i - 1)`, where `r_k` is the number of variables of `e_k` and `c_i`
+    /// the number of rows that support `x_i`.
+    ///
+    /// ```
+    /// # use presburger::echelon::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # fn main () {
+    /// // x_1 + x_2 = 1, x_2 + x_3 = 2, x_1 - x_3 = -1, x_2 <= 0
+    /// let relations = vec![
+    ///     LinRel::mk_eq(LinExpr::new(vec![-1, 1, 1, 0]).unwrap()),
+    ///     LinRel::mk_eq(LinExpr::new(vec![-2, 0, 1, 1]).unwrap()),
+    ///     LinRel::mk_eq(LinExpr::new(vec![1, 1, 0, -1]).unwrap()),
+    ///     LinRel::mk_le(LinExpr::new(vec![0, 0, 1, 0]).unwrap()),
+    /// ];
+    /// let echelon = Echelon::new(&relations).unwrap();
+    /// assert_eq!(echelon.rank(), 2);
+    /// assert_eq!(echelon.pivots(), &[1, 2]);
+    /// assert_eq!(echelon.free_vars(), vec![3]);
+    /// // x_1 = -1 + x_3, x_2 = 2 - x_3
+    /// assert_eq!(
+    ///     echelon.solution(),
+    ///     vec![
+    ///         (1, LinExpr::new(vec![-1, 0, 0, 1]).unwrap()),
+    ///         (2, LinExpr::new(vec![2, 0, 0, -1]).unwrap()),
+    ///     ]
+    /// );
+    ///
+    /// // x_1 - x_3 = 0 contradicts them
+    /// let mut relations = relations;
+    /// relations[2] = LinRel::mk_eq(LinExpr::new(vec![0, 1, 0, -1]).unwrap());
+    /// let combination = Echelon::new(&relations).unwrap_err();
+    /// assert!(combination.verify(&relations).is_ok());
+    /// # }
+    /// ```
+    pub fn new(relations: &[LinRel]) -> Result<Self, Combination> {
+        let nvars = relations
+            .iter()
+            .filter(|r| r.is_equality())
+            .map(|r| r.nvars())
+            .max()
+            .unwrap_or(0);
+        let mut pending: Vec<(LinExpr, Combination)> = relations
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.is_equality())
+            .map(|(j, r)| {
+                let mut e = r.lhs().clone();
+                e.extend_to(nvars);
+                (e, Combination::unit(j))
+            })
+            .collect();
+        let mut solved: Vec<(usize, LinExpr, Combination)> = Vec::new();
+        loop {
+            // rows without variables are either trivial or contradictory
+            if let Some((_, origin)) = pending
+                .iter()
+                .find(|(e, _)| e.is_const() && !e.const_().is_zero())
+            {
+                return Err(origin.clone());
+            }
+            pending.retain(|(e, _)| !e.is_const());
+            let Some((k, i)) = choose_pivot(&pending) else {
+                break;
+            };
+            let (mut row, mut origin) = pending.swap_remove(k);
+            let a = Rational::ONE / row.coeff_unchecked(i);
+            row *= &a;
+            origin.scale(&a);
+            let eliminate = |e: &mut LinExpr, o: &mut Combination| {
+                let c = -e.coeff_unchecked(i).clone();
+                if !c.is_zero() {
+                    *e += &row * &c;
+                    o.add_scaled(&origin, &c);
+                }
+            };
+            for (e, o) in pending.iter_mut() {
+                eliminate(e, o);
+            }
+            for (_, e, o) in solved.iter_mut() {
+                eliminate(e, o);
+            }
+            solved.push((i, row, origin));
+        }
+        solved.sort_by_key(|(i, _, _)| *i);
+        let mut echelon = Self {
+            nvars,
+            rows: Vec::with_capacity(solved.len()),
+            pivots: Vec::with_capacity(solved.len()),
+            origins: Vec::with_capacity(solved.len()),
+        };
+        for (i, row, origin) in solved {
+            echelon.pivots.push(i);
+            echelon.rows.push(row);
+            echelon.origins.push(origin);
+        }
+        Ok(echelon)
+    }
+
+    /// Number of variables of the equalities
+    pub fn nvars(&self) -> usize {
+        self.nvars
+    }
+
+    /// Rank of the equalities, i.e. the number of pivots
+    pub fn rank(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The pivot variables, in increasing order
+    pub fn pivots(&self) -> &[usize] {
+        &self.pivots
+    }
+
+    /// The variables that are not pivots, in increasing order
+    pub fn free_vars(&self) -> Vec<usize> {
+        (1..=self.nvars)
+            .filter(|i| self.pivots.binary_search(i).is_err())
+            .collect()
+    }
+
+    /// The solved equalities `x_p + f_p = 0`, one for each pivot, in the order of `pivots()`
+    pub fn equalities(&self) -> Vec<LinRel> {
+        self.rows.iter().cloned().map(LinRel::mk_eq).collect()
+    }
+
+    /// The combinations of the original equalities that give each solved equality
+    pub fn origins(&self) -> &[Combination] {
+        &self.origins
+    }
+
+    /// The parametric description of the solutions: each pivot `x_p` with the expression `-f_p`
+    /// over the free variables that it is equal to
+    pub fn solution(&self) -> Vec<(usize, LinExpr)> {
+        self.pivots
+            .iter()
+            .zip(self.rows.iter())
+            .map(|(p, row)| {
+                let mut e = -row;
+                e.set_coeff_unchecked(*p, Rational::ZERO);
+                (*p, e)
+            })
+            .collect()
+    }
+
+    /// Substitute the solutions for the pivots in `rel`, which results in a relation over the
+    /// free variables that is equivalent to `rel` under the equalities
+    pub fn reduce(&self, rel: LinRel) -> LinRel {
+        let mut rel = rel;
+        for (p, row) in self.pivots.iter().zip(self.rows.iter()) {
+            if rel.lhs().supported(*p) {
+                rel = rel
+                    .subs(*p, &LinRel::mk_eq(row.clone()))
+                    .expect("row is a substitution for its pivot");
+            }
+        }
+        rel
+    }
+
+    /// Set the pivots of `model` to their values given the free variables, so that the model
+    /// satisfies the equalities. The model must assign the free variables.
+    pub fn extend_model(&self, model: &mut Model) -> Result<(), ModelError> {
+        for (p, row) in self.pivots.iter().zip(self.rows.iter()) {
+            // row = x_p + f_p, so x_p = -f_p(model)
+            let mut f = row.clone();
+            f.set_coeff_unchecked(*p, Rational::ZERO);
+            model.set(*p, -f.eval(model)?);
+        }
+        Ok(())
+    }
+}
+
+/// The row and variable of the next pivot by the Markowitz criterion, the first variable of the
+/// first row on ties, or `None` if there are no rows
+fn choose_pivot(rows: &[(LinExpr, Combination)]) -> Option<(usize, usize)> {
+    let mut columns: HashMap<usize, usize> = HashMap::new();
+    for (e, _) in rows.iter() {
+        for (i, _) in e.terms() {
+            *columns.entry(i).or_default() += 1;
+        }
+    }
+    let mut best: Option<(usize, usize, usize)> = None;
+    for (k, (e, _)) in rows.iter().enumerate() {
+        for (i, _) in e.terms() {
+            let cost = (e.nnz() - 1) * (columns[&i] - 1);
+            if best.is_none_or(|(c, _, _)| cost < c) {
+                best = Some((cost, k, i));
+            }
+        }
+    }
+    best.map(|(_, k, i)| (k, i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lin_expr::LinExpr;
+    use crate::{eq, le};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_echelon() {
+        // x1 + x2 + x3 = 3, x3 = 1 (twice), 2 x1 + 2 x2 = 4, and an inequality
+        let relations = vec![
+            eq!(-3, 1, 1, 1),
+            eq!(-1, 0, 0, 1),
+            le!(0, 1, 0, 0),
+            eq!(-4, 2, 2, 0),
+            eq!(-1, 0, 0, 1),
+        ];
+        let echelon = Echelon::new(&relations).unwrap();
+        // x3 is chosen first, as its row has a single variable
+        assert_eq!(echelon.rank(), 2);
+        assert_eq!(echelon.nvars(), 3);
+        assert_eq!(echelon.pivots(), &[1, 3]);
+        assert_eq!(echelon.free_vars(), vec![2]);
+        assert_eq!(
+            echelon.equalities(),
+            vec![eq!(-2, 1, 1, 0), eq!(-1, 0, 0, 1)]
+        );
+        for (row, origin) in echelon.equalities().iter().zip(echelon.origins()) {
+            assert_eq!(origin.combine(&relations).unwrap(), *row);
+        }
+
+        // x1 <= 0 becomes 2 - x2 <= 0
+        assert_eq!(echelon.reduce(relations[2].clone()), le!(2, 0, -1, 0));
+        let mut model = Model::from_values(vec![0, 5]);
+        echelon.extend_model(&mut model).unwrap();
+        assert_eq!(model, Model::from_values(vec![-3, 5, 1]));
+        // the free variable x2 must be assigned
+        assert_eq!(
+            echelon.extend_model(&mut Model::new()),
+            Err(ModelError::Unassigned(2))
+        );
+
+        // no equalities
+        let echelon = Echelon::new(&[le!(0, 1)]).unwrap();
+        assert_eq!(echelon.rank(), 0);
+        assert_eq!(echelon.free_vars(), vec![]);
+
+        // 0 = 1 is inconsistent on its own
+        let relations = vec![eq!(0, 1), eq!(1, 0)];
+        assert_eq!(Echelon::new(&relations), Err(Combination::unit(1)));
+    }
+
+    #[test]
+    fn test_choose_pivot() {
+        let rows: Vec<(LinExpr, Combination)> =
+            [vec![0, 1, 1, 0], vec![0, 1, 1, 1], vec![0, 1, 0, 0]]
+                .into_iter()
+                .map(|c| (LinExpr::new(c).unwrap(), Combination::new()))
+                .collect();
+        // x1 has cost 1 * 2 in the first row and 2 * 2 in the second, x2 has cost 1 * 1 and
+        // 2 * 1, and x3 only occurs in the second row, with cost 2 * 0
+        assert_eq!(choose_pivot(&rows), Some((1, 3)));
+        // without the second row, x2 only occurs in the first row
+        let rows = [rows[0].clone(), rows[2].clone()];
+        assert_eq!(choose_pivot(&rows), Some((0, 2)));
+        assert_eq!(choose_pivot(&[]), None);
+    }
+
+    fn arb_equalities() -> impl Strategy<Value = Vec<LinRel>> {
+        (1usize..5).prop_flat_map(|n| {
+            prop::collection::vec(
+                prop::collection::vec(-3i32..=3, n + 1)
+                    .prop_map(|c| LinRel::mk_eq(LinExpr::new(c).unwrap())),
+                0..6,
+            )
+        })
+    }
+
+    proptest! {
+        /// Either the combination is a contradiction, or extending any assignment of the free
+        /// variables satisfies all equalities, each of which reduces to `0 = 0`
+        #[test]
+        fn echelon_solves_equalities(
+            relations in arb_equalities(),
+            values in prop::collection::vec(-5i32..=5, 5),
+        ) {
+            match Echelon::new(&relations) {
+                Err(combination) => prop_assert_eq!(combination.verify(&relations), Ok(())),
+                Ok(echelon) => {
+                    prop_assert!(echelon.rank() <= relations.len());
+                    prop_assert_eq!(echelon.rank() + echelon.free_vars().len(), echelon.nvars());
+                    let mut model = Model::from_values(values);
+                    echelon.extend_model(&mut model).unwrap();
+                    for r in relations.iter() {
+                        prop_assert_eq!(r.holds(&model), Ok(true));
+                        prop_assert!(echelon.reduce(r.clone()).lhs().is_zero());
+                    }
+                    for (row, origin) in echelon.equalities().iter().zip(echelon.origins()) {
+                        prop_assert_eq!(&origin.combine(&relations).unwrap(), row);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Strategies for choosing the variable to eliminate in each round of Fourier-Motzkin elimination.
+//!
+//! Eliminating `x_i` replaces the `L` relations that bound it from below and the `U` relations
+//! that bound it from above by `L * U` combinations, so the order in which variables are
+//! eliminated decides how fast the system grows.
+
+use crate::lin_rel::LinRel;
+
+/// Chooses the variable to eliminate from a system of inequalities
+pub trait EliminationOrder {
+    /// Choose a variable with a non-zero coefficient in some inequality (strict or not) of
+    /// `relations`, or return `None` if there is no such variable
+    fn choose(&self, relations: &[LinRel]) -> Option<usize>;
+}
+
+/// Eliminate the first variable with a non-zero coefficient in the first inequality that has one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FirstFound;
+
+impl EliminationOrder for FirstFound {
+    fn choose(&self, relations: &[LinRel]) -> Option<usize> {
+        relations.iter().find_map(|r| r.is_isolatable_le())
+    }
+}
+
+/// Eliminate the variable minimizing the growth of the number of relations,
+/// `L * U - L - U`, where `L` and `U` are its numbers of lower and upper bounds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MinProduct;
+
+impl EliminationOrder for MinProduct {
+    fn choose(&self, relations: &[LinRel]) -> Option<usize> {
+        let supports = inequality_supports(relations);
+        min_cost(relations, |i| {
+            let (lower, upper) = bound_counts(relations, &supports, i);
+            let (l, u) = (lower.len() as i64, upper.len() as i64);
+            l * u - l - u
+        })
+    }
+}
+
+/// Eliminate the variable minimizing the growth of the number of non-zero coefficients: the
+/// coefficients of the combinations of its bounds minus those of the bounds themselves. The
+/// combination of two bounds is assumed to be supported by the union of their supports, without
+/// `x_i`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MinFillIn;
+
+impl EliminationOrder for MinFillIn {
+    fn choose(&self, relations: &[LinRel]) -> Option<usize> {
+        let supports = inequality_supports(relations);
+        min_cost(relations, |i| {
+            let (lower, upper) = bound_counts(relations, &supports, i);
+            let removed: usize = lower.iter().chain(&upper).map(|k| supports[*k].len()).sum();
+            let mut generated = 0;
+            for l in lower.iter() {
+                for u in upper.iter() {
+                    generated += union_len(&supports[*l], &supports[*u]) - 1;
+                }
+            }
+            generated as i64 - removed as i64
+        })
+    }
+}
+
+/// The variables with a non-zero coefficient in each relation, or none if it is not an inequality
+fn inequality_supports(relations: &[LinRel]) -> Vec<Vec<usize>> {
+    relations
+        .iter()
+        .map(|r| {
+            if r.is_isolatable_le().is_none() {
+                return Vec::new();
+            }
+            r.lhs().terms().map(|(i, _)| i).collect()
+        })
+        .collect()
+}
+
+/// The indices of the inequalities that bound `x_i` from below and from above
+fn bound_counts(
+    relations: &[LinRel],
+    supports: &[Vec<usize>],
+    i: usize,
+) -> (Vec<usize>, Vec<usize>) {
+    let mut lower = Vec::new();
+    let mut upper = Vec::new();
+    for (k, r) in relations.iter().enumerate() {
+        if supports[k].binary_search(&i).is_ok() {
+            if r.lhs().coeff_unchecked(i).is_negative() {
+                lower.push(k);
+            } else {
+                upper.push(k);
+            }
+        }
+    }
+    (lower, upper)
+}
+
+/// Size of the union of two sorted sets
+fn union_len(a: &[usize], b: &[usize]) -> usize {
+    let (mut i, mut j, mut common) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                common += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    a.len() + b.len() - common
+}
+
+/// The variable occurring in an inequality with the smallest cost, the first one on ties
+fn min_cost(relations: &[LinRel], cost: impl Fn(usize) -> i64) -> Option<usize> {
+    relations
+        .iter()
+        .filter(|r| r.is_isolatable_le().is_some())
+        .flat_map(|r| r.lhs().terms().map(|(i, _)| i))
+        .min_by_key(|i| (cost(*i), *i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lin_expr::LinExpr;
+    use crate::{eq, le};
+
+    #[test]
+    fn test_elimination_orders() {
+        // x1 <= -x3, x4 <= x1, 0 <= x2 <= 1, 1 <= x2 <= 2
+        let relations = vec![
+            le!(0, 1, 0, 1, 0),
+            le!(0, -1, 0, 0, 1),
+            le!(-1, 0, 1, 0, 0),
+            le!(-2, 0, 1, 0, 0),
+            le!(0, 0, -1, 0, 0),
+            le!(1, 0, -1, 0, 0),
+            eq!(0, 0, 0, 0, 0, 1),
+        ];
+        assert_eq!(FirstFound.choose(&relations), Some(1));
+        // growth: x1 -> 1 - 2, x2 -> 4 - 4, x3 -> 0 - 1, x4 -> 0 - 1
+        assert_eq!(MinProduct.choose(&relations), Some(1));
+        // fill-in: x1 -> 2 - 4, x2 -> 0 - 4, x3 -> 0 - 2, x4 -> 0 - 2
+        assert_eq!(MinFillIn.choose(&relations), Some(2));
+
+        // equalities and constant relations have no variables to eliminate
+        let relations = vec![eq!(0, 1, 1), le!(1, 0, 0)];
+        assert_eq!(FirstFound.choose(&relations), None);
+        assert_eq!(MinProduct.choose(&relations), None);
+        assert_eq!(MinFillIn.choose(&relations), None);
+    }
+
+    #[test]
+    fn test_union_len() {
+        assert_eq!(union_len(&[], &[]), 0);
+        assert_eq!(union_len(&[1, 3, 4], &[2, 3]), 4);
+        assert_eq!(union_len(&[1, 2], &[1, 2]), 2);
+    }
+}
@@ -1,115 +1,1278 @@
 //! Implementation of Fourier-Motzkin Elimination
 //! <https://en.wikipedia.org/wiki/Fourier%E2%80%93Motzkin_elimination>
 
-use itertools::partition;
-
-use crate::lin_expr::{Bound, LinExprBound};
+use crate::certificate::{Combination, UnsatCertificate};
+use crate::echelon::Echelon;
+use crate::elim_order::{EliminationOrder, FirstFound};
+use crate::lin_expr::{Bound, LinExpr, LinExprBound};
+use crate::model::Model;
+use crate::solver::Solver;
+use crate::space::{Space, SpaceError};
+use crate::types::Rational;
+use crate::witness;
 use crate::{lin_rel::LinRel, lin_sys::LinSys};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// FME Solver State, the result of `FMESolver::check`
+pub use crate::solver::SatResult as FMEState;
 
-/// FME Solver State
+/// An event of `FMESolver::check`, reported to the solver's observer
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum FMEState {
-    /// `check` has not been called
-    UNKNOWN,
-    /// system of (in)equalities is satisfiable
-    SAT,
-    /// system of (in)equalities is unsatisfiable
-    UNSAT,
+pub enum FMEEvent {
+    /// A run of FME starts on a system without disequalities. `check` runs FME once on the
+    /// assertions, and up to twice more for each disequality.
+    Start { relations: usize },
+    /// `x_var` was eliminated by substituting its solution in the echelon form of the equalities
+    Substitute { var: usize },
+    /// A round of elimination starts with `relations` relations, after removing `trivial`
+    /// trivially true ones
+    Round { relations: usize, trivial: usize },
+    /// `x_var` was eliminated by combining its `lower` bounds with its `upper` bounds, generating
+    /// `lower * upper` new relations
+    Eliminate {
+        var: usize,
+        lower: usize,
+        upper: usize,
+    },
+    /// After an elimination, `redundant` generated relations were discarded by Imbert's
+    /// theorem, and `dominated` relations were removed because a parallel one implies them
+    Prune { redundant: usize, dominated: usize },
+    /// The run found a trivial contradiction
+    Contradiction,
+}
+
+/// Receives the events of `FMESolver::check`, e.g. for tracing. Any `FnMut(&FMEEvent) + Send` is
+/// an observer. Observers are `Send` so that a solver can be moved to another thread.
+pub trait FMEObserver: Send {
+    fn event(&mut self, event: &FMEEvent);
+}
+
+impl<F: FnMut(&FMEEvent) + Send> FMEObserver for F {
+    fn event(&mut self, event: &FMEEvent) {
+        self(event)
+    }
+}
+
+/// Statistics of the last call to `FMESolver::check`, accumulated over all of its FME runs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolverStats {
+    /// Number of FME runs
+    pub runs: usize,
+    /// Number of variables eliminated, by substitution or by combining bounds
+    pub eliminations: usize,
+    /// Largest number of relations held at once
+    pub peak_relations: usize,
+    /// Number of relations generated by each elimination that combined bounds, in order
+    pub generated: Vec<usize>,
+    /// Number of trivially true relations removed
+    pub trivial_removed: usize,
+    /// Number of generated relations discarded as redundant by Imbert's theorem
+    pub redundant_removed: usize,
+    /// Number of relations removed because a parallel relation implies them
+    pub dominated_removed: usize,
+    /// Time spent in `check`
+    pub wall_time: Duration,
+}
+
+/// A token to cancel `FMESolver::check`, e.g. from another thread. Clones share the same state.
+///
+/// ```
+/// # use presburger::fme::*;
+/// # use presburger::lin_expr::LinExpr;
+/// # use presburger::lin_rel::LinRel;
+/// # fn main () {
+/// let cancel = CancelToken::new();
+/// let mut solver = FMESolver::new();
+/// solver.set_limits(SolverLimits {
+///     cancel: Some(cancel.clone()),
+///     ..SolverLimits::default()
+/// });
+/// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 1, -1]).unwrap()));
+/// std::thread::spawn(move || cancel.cancel()).join().unwrap();
+/// assert_eq!(solver.check(), FMEState::UNKNOWN);
+/// assert_eq!(solver.reason_unknown(), Some(UnknownReason::Cancelled));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of every `check` using this token
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Limits on the resources used by `FMESolver::check`. When a limit is reached, `check` gives up
+/// and returns `UNKNOWN`. The default has no limits.
+#[derive(Debug, Clone, Default)]
+pub struct SolverLimits {
+    /// Maximum number of relations held at once by a run of FME
+    pub max_relations: Option<usize>,
+    /// Maximum number of variable eliminations, over all runs of FME
+    pub max_eliminations: Option<usize>,
+    /// Time after which `check` gives up
+    pub deadline: Option<Instant>,
+    /// Token that makes `check` give up when it is cancelled
+    pub cancel: Option<CancelToken>,
+}
+
+/// Why `FMESolver::check` returned `UNKNOWN`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownReason {
+    /// A run of FME would have exceeded `SolverLimits::max_relations`
+    MaxRelations,
+    /// `SolverLimits::max_eliminations` eliminations were done
+    MaxEliminations,
+    /// `SolverLimits::deadline` passed
+    Deadline,
+    /// `SolverLimits::cancel` was cancelled
+    Cancelled,
+}
+
+impl fmt::Display for UnknownReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxRelations => write!(f, "Relation limit reached"),
+            Self::MaxEliminations => write!(f, "Elimination limit reached"),
+            Self::Deadline => write!(f, "Deadline passed"),
+            Self::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+/// Why a run of FME stopped without a model: a proof of unsatisfiability, or a limit
+enum Stop<C> {
+    Unsat(C),
+    Unknown(UnknownReason),
+}
+
+impl<C> Stop<C> {
+    fn map<D>(self, f: impl FnOnce(C) -> D) -> Stop<D> {
+        match self {
+            Self::Unsat(c) => Stop::Unsat(f(c)),
+            Self::Unknown(reason) => Stop::Unknown(reason),
+        }
+    }
+}
+
+impl<C> From<UnknownReason> for Stop<C> {
+    fn from(reason: UnknownReason) -> Self {
+        Self::Unknown(reason)
+    }
+}
+
+/// Collects statistics, forwards events to the observer, and enforces the limits during `check`
+struct Monitor<'a> {
+    stats: SolverStats,
+    observer: Option<&'a mut dyn FMEObserver>,
+    limits: &'a SolverLimits,
+}
+
+impl Monitor<'_> {
+    /// Check for cancellation and the deadline
+    fn interrupted(&self) -> Result<(), UnknownReason> {
+        if self
+            .limits
+            .cancel
+            .as_ref()
+            .is_some_and(|c| c.is_cancelled())
+        {
+            Err(UnknownReason::Cancelled)
+        } else if self.limits.deadline.is_some_and(|d| Instant::now() >= d) {
+            Err(UnknownReason::Deadline)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check for interruption and the relation limit before a step that results in `relations`
+    /// relations
+    fn check_limits(&self, relations: usize) -> Result<(), UnknownReason> {
+        self.interrupted()?;
+        if self.limits.max_relations.is_some_and(|m| relations > m) {
+            Err(UnknownReason::MaxRelations)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check the elimination limit right before substituting or eliminating a variable, so that
+    /// systems needing no elimination are decided under any limit
+    fn check_eliminations(&self) -> Result<(), UnknownReason> {
+        if self
+            .limits
+            .max_eliminations
+            .is_some_and(|m| self.stats.eliminations >= m)
+        {
+            Err(UnknownReason::MaxEliminations)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn emit(&mut self, event: FMEEvent) {
+        let stats = &mut self.stats;
+        match &event {
+            FMEEvent::Start { relations } => {
+                stats.runs += 1;
+                stats.peak_relations = stats.peak_relations.max(*relations);
+            }
+            FMEEvent::Substitute { .. } => stats.eliminations += 1,
+            FMEEvent::Round { relations, trivial } => {
+                stats.peak_relations = stats.peak_relations.max(*relations);
+                stats.trivial_removed += trivial;
+            }
+            FMEEvent::Eliminate { lower, upper, .. } => {
+                stats.eliminations += 1;
+                stats.generated.push(lower * upper);
+            }
+            FMEEvent::Prune {
+                redundant,
+                dominated,
+            } => {
+                stats.redundant_removed += redundant;
+                stats.dominated_removed += dominated;
+            }
+            FMEEvent::Contradiction => {}
+        }
+        if let Some(observer) = self.observer.as_mut() {
+            observer.event(&event);
+        }
+    }
+}
+
+/// A step of the elimination, recorded so that models can be reconstructed by back-substitution
+#[derive(Debug, Clone)]
+enum Elimination {
+    /// The pivots of the equalities were eliminated by substituting their solutions
+    Equalities(Echelon),
+    /// `x_i` was eliminated by combining its lower bounds with its upper bounds
+    Bounds(usize, Vec<LinExprBound>, Vec<LinExprBound>),
+}
+
+/// The origin of a relation derived during elimination, in terms of the asserted relations.
+///
+/// A [`Combination`] is the combination the relation is equal to, from which certificates of
+/// unsatisfiability are built. A [`Support`] is only the set of assertions with a non-zero
+/// multiplier in it, which is all that Imbert's theorem needs, and is cheaper to maintain.
+trait Origin: Clone + Default {
+    /// The origin of the assertion with index `j`
+    fn unit(j: usize) -> Self;
+
+    /// Add `c` times `other`, where `c` is positive or `other` is the origin of an equality
+    fn add_scaled(&mut self, other: &Self, c: &Rational);
+
+    /// Indices of the assertions the relation depends on, in increasing order
+    fn support(&self) -> impl Iterator<Item = usize> + '_;
+
+    /// The combination of assertions, if it is tracked
+    fn into_combination(self) -> Option<Combination>;
+}
+
+impl Origin for Combination {
+    fn unit(j: usize) -> Self {
+        Combination::unit(j)
+    }
+
+    fn add_scaled(&mut self, other: &Self, c: &Rational) {
+        Combination::add_scaled(self, other, c);
+    }
+
+    fn support(&self) -> impl Iterator<Item = usize> + '_ {
+        Combination::support(self)
+    }
+
+    fn into_combination(self) -> Option<Combination> {
+        Some(self)
+    }
+}
+
+/// The indices of the assertions a derived relation depends on, sorted
+///
+/// The multipliers of inequalities in a combination are positive, so they never cancel, and the
+/// union of supports is the support of the sum restricted to inequalities. Multipliers of
+/// equalities may cancel, but Imbert's theorem does not count equalities.
+#[derive(Debug, Clone, Default)]
+struct Support(Vec<usize>);
+
+impl Origin for Support {
+    fn unit(j: usize) -> Self {
+        Self(vec![j])
+    }
+
+    fn add_scaled(&mut self, other: &Self, _c: &Rational) {
+        let mut union = Vec::with_capacity(self.0.len() + other.0.len());
+        let (mut lhs, mut rhs) = (self.0.iter().peekable(), other.0.iter().peekable());
+        while let (Some(j), Some(k)) = (lhs.peek(), rhs.peek()) {
+            match j.cmp(k) {
+                std::cmp::Ordering::Less => union.push(*lhs.next().unwrap()),
+                std::cmp::Ordering::Greater => union.push(*rhs.next().unwrap()),
+                std::cmp::Ordering::Equal => {
+                    union.push(*lhs.next().unwrap());
+                    rhs.next();
+                }
+            }
+        }
+        union.extend(lhs.chain(rhs));
+        self.0 = union;
+    }
+
+    fn support(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().copied()
+    }
+
+    fn into_combination(self) -> Option<Combination> {
+        None
+    }
+}
+
+/// Relations derived during elimination, each with its origin in terms of the asserted relations
+#[derive(Debug, Clone)]
+struct Derived<O> {
+    nvars: usize,
+    relations: Vec<LinRel>,
+    origins: Vec<O>,
+}
+
+impl<O: Origin> Derived<O> {
+    fn new(nvars: usize) -> Self {
+        Self {
+            nvars,
+            relations: Vec::new(),
+            origins: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, relation: LinRel, origin: O) {
+        self.relations.push(relation);
+        self.origins.push(origin);
+    }
+}
+
+/// The variables of each inequality at the start of Fourier-Motzkin elimination, by the index of
+/// the assertion it came from. The history of a derived inequality is the set of these
+/// inequalities in the support of its origin.
+///
+/// By Imbert's first acceleration theorem, which generalizes Chernikov's rule, an inequality is
+/// redundant if its history has more than `1 + |E|` elements, where `E` is the set of variables
+/// eliminated from it explicitly or by cancellation. These are the variables of its history that
+/// it no longer contains.
+struct Histories {
+    vars: HashMap<usize, Vec<usize>>,
+}
+
+impl Histories {
+    /// Collect the variables of the inequalities of `system`, whose origins are a single
+    /// inequality plus multiples of `equalities`
+    fn new<O: Origin>(system: &Derived<O>, equalities: &BTreeSet<usize>) -> Self {
+        let mut vars = HashMap::new();
+        for (r, o) in system.relations.iter().zip(system.origins.iter()) {
+            if r.is_equality() {
+                continue;
+            }
+            let support: Vec<usize> = r.lhs().terms().map(|(i, _)| i).collect();
+            for j in o.support().filter(|j| !equalities.contains(j)) {
+                vars.insert(j, support.clone());
+            }
+        }
+        Self { vars }
+    }
+
+    /// The history of a relation with the given origin, in increasing order
+    fn history(&self, origin: &impl Origin) -> Vec<usize> {
+        origin
+            .support()
+            .filter(|j| self.vars.contains_key(j))
+            .collect()
+    }
+
+    /// Is the derived inequality `rel` with the given origin redundant by Imbert's theorem?
+    fn is_redundant(&self, rel: &LinRel, origin: &impl Origin) -> bool {
+        let history: Vec<&Vec<usize>> =
+            origin.support().filter_map(|j| self.vars.get(&j)).collect();
+        if history.len() <= 2 {
+            // at least one variable was eliminated from a derived inequality
+            return false;
+        }
+        let eliminated: BTreeSet<usize> = history
+            .iter()
+            .flat_map(|vars| vars.iter().copied())
+            .filter(|i| !rel.lhs().supported(*i))
+            .collect();
+        history.len() > 1 + eliminated.len()
+    }
+
+    /// Remove the inequalities of `system` that are implied by a parallel one with a smaller or
+    /// equal history, returning how many were removed.
+    ///
+    /// Scaled to make the first non-zero coefficient `1` or `-1`, `e + c <= 0` implies
+    /// `e + c' <= 0` and `e + c' < 0` for `c' < c`, and `e + c < 0` implies `e + c <= 0`. The
+    /// condition on histories keeps Imbert's theorem valid: a relation implied by one with a larger
+    /// history may be needed to derive the relations that make others redundant.
+    fn remove_dominated<O: Origin>(&self, system: &mut Derived<O>) -> usize {
+        // relations grouped by normalized expression, with their constant, strictness and history
+        type Group = Vec<(usize, Rational, bool, Vec<usize>)>;
+        let mut parallel: HashMap<Vec<(usize, Rational)>, Group> = HashMap::new();
+        for (k, (r, o)) in system.relations.iter().zip(&system.origins).enumerate() {
+            let Some((terms, c)) = r.direction() else {
+                continue;
+            };
+            parallel
+                .entry(terms)
+                .or_default()
+                .push((k, c, r.is_strict(), self.history(o)));
+        }
+
+        let mut keep = vec![true; system.relations.len()];
+        for group in parallel.values().filter(|g| g.len() > 1) {
+            for (k, c, strict, history) in group.iter() {
+                // ties are broken by index, so that only one of two equal relations is removed
+                let dominated = group.iter().any(|(j, c_j, strict_j, history_j)| {
+                    (c_j, strict_j, k) > (c, strict, j)
+                        && history_j.iter().all(|i| history.binary_search(i).is_ok())
+                });
+                if dominated {
+                    keep[*k] = false;
+                }
+            }
+        }
+        let removed = keep.iter().filter(|k| !**k).count();
+        if removed > 0 {
+            let mut keep = keep.into_iter();
+            let (relations, origins) = std::mem::take(&mut system.relations)
+                .into_iter()
+                .zip(std::mem::take(&mut system.origins))
+                .filter(|_| keep.next().unwrap())
+                .unzip();
+            system.relations = relations;
+            system.origins = origins;
+        }
+        removed
+    }
 }
 
-#[derive(Debug)]
 pub struct FMESolver {
     state: FMEState,
     system: LinSys,
+    // labels[j] is the label of the j-th assertion, if any
+    labels: Vec<Option<String>>,
+    // scopes[k] is the number of assertions when the k-th open scope was pushed
+    scopes: Vec<usize>,
+    // extra relations of the last `check_assuming`, numbered after the assertions
+    assumptions: Vec<LinRel>,
+    model: Option<Model>,
+    certificate: Option<UnsatCertificate>,
+    stats: SolverStats,
+    observer: Option<Box<dyn FMEObserver>>,
+    order: Arc<dyn EliminationOrder + Send + Sync>,
+    limits: SolverLimits,
+    reason: Option<UnknownReason>,
+    // whether `check` tracks the combinations needed for certificates
+    certificates: bool,
+}
+
+impl fmt::Debug for FMESolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FMESolver")
+            .field("state", &self.state)
+            .field("system", &self.system)
+            .field("labels", &self.labels)
+            .field("scopes", &self.scopes)
+            .field("assumptions", &self.assumptions)
+            .field("model", &self.model)
+            .field("certificate", &self.certificate)
+            .field("stats", &self.stats)
+            .field("limits", &self.limits)
+            .field("reason", &self.reason)
+            .field("certificates", &self.certificates)
+            .finish_non_exhaustive()
+    }
 }
 
 impl FMESolver {
     /// Create a fresh solver
     pub fn new() -> Self {
+        Self::with_space(Space::new())
+    }
+
+    /// Create a fresh solver over the given space of variables
+    pub fn with_space(space: Space) -> Self {
         Self {
             state: FMEState::UNKNOWN,
-            system: LinSys::new(),
+            system: LinSys::with_space(space),
+            labels: Vec::new(),
+            scopes: Vec::new(),
+            assumptions: Vec::new(),
+            model: None,
+            certificate: None,
+            stats: SolverStats::default(),
+            observer: None,
+            order: Arc::new(FirstFound),
+            limits: SolverLimits::default(),
+            reason: None,
+            certificates: true,
         }
     }
 
+    /// Get the space of variables of the solver
+    pub fn space(&self) -> &Space {
+        self.system.space()
+    }
+
+    /// Get the asserted relations
+    pub fn assertions(&self) -> &LinSys {
+        &self.system
+    }
+
+    /// Choose the variable to eliminate in each round with `order`. The default is
+    /// [`FirstFound`].
+    ///
+    /// ```
+    /// # use presburger::elim_order::MinProduct;
+    /// # use presburger::fme::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # fn main () {
+    /// let mut solver = FMESolver::new();
+    /// solver.set_elimination_order(MinProduct);
+    /// // x_1 <= x_2, x_2 <= 0, 1 <= x_1
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 1, -1]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 0, 1]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![1, -1, 0]).unwrap()));
+    /// assert_eq!(solver.check(), FMEState::UNSAT);
+    /// # }
+    /// ```
+    pub fn set_elimination_order(&mut self, order: impl EliminationOrder + Send + Sync + 'static) {
+        self.order = Arc::new(order);
+    }
+
+    /// Choose whether `check` produces certificates of unsatisfiability, and thus unsat cores. The
+    /// default is `true`.
+    ///
+    /// To produce certificates, every relation derived by elimination carries the combination of
+    /// the assertions it is equal to. Without them, only the set of assertions each relation
+    /// depends on is tracked, as needed to discard redundant relations, and `unsat_certificate`,
+    /// `unsat_core` and `minimize_unsat_core` return `None`.
+    ///
+    /// ```
+    /// # use presburger::fme::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # fn main () {
+    /// let mut solver = FMESolver::new();
+    /// solver.set_produce_certificates(false);
+    /// // x_1 <= 0, 1 <= x_1
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 1]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![1, -1]).unwrap()));
+    /// assert_eq!(solver.check(), FMEState::UNSAT);
+    /// assert!(solver.unsat_certificate().is_none());
+    /// # }
+    /// ```
+    pub fn set_produce_certificates(&mut self, produce: bool) {
+        self.certificates = produce;
+    }
+
+    /// Does `check` produce certificates of unsatisfiability? See `set_produce_certificates`.
+    pub fn produces_certificates(&self) -> bool {
+        self.certificates
+    }
+
+    /// Limit the resources used by every following call to `check`
+    pub fn set_limits(&mut self, limits: SolverLimits) {
+        self.limits = limits;
+    }
+
+    /// Get the resource limits of the solver
+    pub fn limits(&self) -> &SolverLimits {
+        &self.limits
+    }
+
+    /// The limit that was reached, if the last call to `check` gave up and returned `UNKNOWN`
+    ///
+    /// ```
+    /// # use presburger::fme::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # fn main () {
+    /// let mut solver = FMESolver::new();
+    /// solver.set_limits(SolverLimits {
+    ///     max_eliminations: Some(1),
+    ///     ..SolverLimits::default()
+    /// });
+    /// // x_1 <= x_2, x_2 <= 0, 1 <= x_1 needs two eliminations
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 1, -1]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 0, 1]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![1, -1, 0]).unwrap()));
+    /// assert_eq!(solver.check(), FMEState::UNKNOWN);
+    /// assert_eq!(solver.reason_unknown(), Some(UnknownReason::MaxEliminations));
+    /// # }
+    /// ```
+    pub fn reason_unknown(&self) -> Option<UnknownReason> {
+        self.reason
+    }
+
+    /// Report the events of every following call to `check` to `observer`
+    ///
+    /// ```
+    /// # use presburger::fme::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # use std::sync::{Arc, Mutex};
+    /// # fn main () {
+    /// let events = Arc::new(Mutex::new(Vec::new()));
+    /// let log = events.clone();
+    /// let mut solver = FMESolver::new();
+    /// solver.set_observer(move |e: &FMEEvent| log.lock().unwrap().push(e.clone()));
+    /// // x_1 <= 0
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 1]).unwrap()));
+    /// assert_eq!(solver.check(), FMEState::SAT);
+    /// assert_eq!(
+    ///     events.lock().unwrap()[..2],
+    ///     [FMEEvent::Start { relations: 1 }, FMEEvent::Round { relations: 1, trivial: 0 }]
+    /// );
+    /// # }
+    /// ```
+    pub fn set_observer(&mut self, observer: impl FMEObserver + 'static) {
+        self.observer = Some(Box::new(observer));
+    }
+
+    /// Stop reporting events
+    pub fn clear_observer(&mut self) {
+        self.observer = None;
+    }
+
+    /// Statistics of the last call to `check`
+    pub fn stats(&self) -> &SolverStats {
+        &self.stats
+    }
+
+    /// Get the label of the assertion with index `j`, if it has one
+    pub fn label(&self, j: usize) -> Option<&str> {
+        self.labels.get(j)?.as_deref()
+    }
+
     /// Assert a new relation
     pub fn assert(&mut self, rel: LinRel) {
+        self.invalidate();
         self.system.add_relation(rel);
+        self.labels.push(None);
+    }
+
+    /// Assert a new relation with a label, which identifies it in unsat cores
+    pub fn assert_labeled(&mut self, rel: LinRel, label: impl Into<String>) {
+        self.assert(rel);
+        *self.labels.last_mut().unwrap() = Some(label.into());
+    }
+
+    /// Assert a new relation, checking that it belongs to the solver's space
+    pub fn try_assert(&mut self, rel: LinRel) -> Result<(), SpaceError> {
+        self.invalidate();
+        self.system.try_add_relation(rel)?;
+        self.labels.push(None);
+        Ok(())
+    }
+
+    /// Assert every relation of `sys`, which must be over the solver's space
+    pub fn assert_sys(&mut self, sys: &LinSys) -> Result<(), SpaceError> {
+        self.invalidate();
+        self.system.conjoin(sys)?;
+        self.labels.resize(self.system.len(), None);
+        Ok(())
     }
 
-    /// Reset the solver state and clear all assertions
+    /// Reset the solver state and clear all assertions and scopes, keeping the space
     pub fn reset(&mut self) {
-        self.state = FMEState::UNKNOWN;
+        self.invalidate();
         self.system.clear();
+        self.labels.clear();
+        self.scopes.clear();
+    }
+
+    /// Open a new scope. The assertions made from now on are retracted by the matching `pop`.
+    ///
+    /// ```
+    /// # use presburger::fme::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # fn main () {
+    /// let mut solver = FMESolver::new();
+    /// // x_1 <= 0
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 1]).unwrap()));
+    /// solver.push();
+    /// // 1 <= x_1
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![1, -1]).unwrap()));
+    /// assert_eq!(solver.check(), FMEState::UNSAT);
+    /// solver.pop(1);
+    /// assert_eq!(solver.assertions().len(), 1);
+    /// assert_eq!(solver.check(), FMEState::SAT);
+    /// # }
+    /// ```
+    pub fn push(&mut self) {
+        self.scopes.push(self.system.len());
+    }
+
+    /// Close the `n` innermost scopes, retracting the assertions made since they were opened.
+    /// Variables added to the space in these scopes are kept.
+    ///
+    /// Panics if fewer than `n` scopes are open.
+    pub fn pop(&mut self, n: usize) {
+        let depth = self.scopes.len();
+        assert!(n <= depth, "cannot pop {n} scopes, only {depth} are open");
+        if n == 0 {
+            return;
+        }
+        let len = self.scopes[depth - n];
+        self.scopes.truncate(depth - n);
+        self.invalidate();
+        self.system.truncate(len);
+        self.labels.truncate(len);
+    }
+
+    /// Get the number of open scopes
+    pub fn num_scopes(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Forget the result of the last `check`
+    fn invalidate(&mut self) {
+        self.state = FMEState::UNKNOWN;
+        self.model = None;
+        self.certificate = None;
+        self.reason = None;
+        self.assumptions.clear();
+    }
+
+    /// The relation with index `j`: an assertion, or an assumption of the last `check_assuming`
+    fn relation(&self, j: usize) -> &LinRel {
+        let len = self.system.len();
+        if j < len {
+            &self.system.relations()[j]
+        } else {
+            &self.assumptions[j - len]
+        }
+    }
+
+    /// A model of the assertions, if the last call to `check` returned `SAT`.
+    ///
+    /// The model assigns a value to every variable of the solver's space; variables that are not
+    /// constrained by the assertions are assigned zero.
+    ///
+    /// ```
+    /// # use presburger::fme::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # fn main () {
+    /// let mut solver = FMESolver::new();
+    /// // x_1 = 2 x_2, 1 <= x_2
+    /// solver.assert(LinRel::mk_eq(LinExpr::new(vec![0, 1, -2]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![1, 0, -1]).unwrap()));
+    /// assert_eq!(solver.check(), FMEState::SAT);
+    /// let model = solver.model().unwrap();
+    /// assert_eq!(solver.assertions().satisfied_by(model), Ok(true));
+    /// # }
+    /// ```
+    pub fn model(&self) -> Option<&Model> {
+        self.model.as_ref()
+    }
+
+    /// A certificate of unsatisfiability of the assertions, if the last call to `check` or
+    /// `check_assuming` returned `UNSAT` and the solver produces certificates. Relations are
+    /// referred to by their index in `checked_relations()`, which are the `assertions()` after a
+    /// `check`.
+    ///
+    /// ```
+    /// # use presburger::fme::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # fn main () {
+    /// let mut solver = FMESolver::new();
+    /// // x_1 <= x_2, x_2 <= 0, 1 <= x_1
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 1, -1]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 0, 1]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![1, -1, 0]).unwrap()));
+    /// assert_eq!(solver.check(), FMEState::UNSAT);
+    /// let certificate = solver.unsat_certificate().unwrap();
+    /// assert!(certificate.verify(solver.assertions()).is_ok());
+    /// # }
+    /// ```
+    pub fn unsat_certificate(&self) -> Option<&UnsatCertificate> {
+        self.certificate.as_ref()
+    }
+
+    /// The relations decided by the last check: the assertions, followed by the assumptions if it
+    /// was a `check_assuming`. The unsat certificate refers to relations by their index here.
+    ///
+    /// ```
+    /// # use presburger::fme::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # fn main () {
+    /// let mut solver = FMESolver::new();
+    /// // x_1 <= 0
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 1]).unwrap()));
+    /// // 1 <= x_1
+    /// let assumption = LinRel::mk_le(LinExpr::new(vec![1, -1]).unwrap());
+    /// assert_eq!(solver.check_assuming(&[assumption]), FMEState::UNSAT);
+    /// let certificate = solver.unsat_certificate().unwrap();
+    /// assert_eq!(solver.checked_relations().len(), 2);
+    /// assert!(certificate.verify(&solver.checked_relations()).is_ok());
+    /// # }
+    /// ```
+    pub fn checked_relations(&self) -> LinSys {
+        let mut sys = self.system.clone();
+        for r in self.assumptions.iter() {
+            sys.add_relation(r.clone());
+        }
+        sys
+    }
+
+    /// The labels of the labeled assertions used to prove unsatisfiability, if the last call to
+    /// `check` returned `UNSAT` and the solver produces certificates, in the order they were
+    /// asserted.
+    ///
+    /// Unlabeled assertions are treated as background facts: they may be used by the proof but
+    /// are never part of the core. The labeled assertions in the core together with all the
+    /// unlabeled ones are unsatisfiable, but the core may be reducible; see
+    /// [`FMESolver::minimize_unsat_core`].
+    ///
+    /// ```
+    /// # use presburger::fme::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # fn main () {
+    /// let mut solver = FMESolver::new();
+    /// solver.assert_labeled(LinRel::mk_le(LinExpr::new(vec![0, 1, -1]).unwrap()), "x <= y");
+    /// solver.assert_labeled(LinRel::mk_le(LinExpr::new(vec![1, 0, -1]).unwrap()), "1 <= y");
+    /// solver.assert_labeled(LinRel::mk_le(LinExpr::new(vec![0, 0, 1]).unwrap()), "y <= 0");
+    /// assert_eq!(solver.check(), FMEState::UNSAT);
+    /// assert_eq!(solver.unsat_core(), Some(vec!["1 <= y", "y <= 0"]));
+    /// # }
+    /// ```
+    pub fn unsat_core(&self) -> Option<Vec<&str>> {
+        let certificate = self.certificate.as_ref()?;
+        Some(
+            certificate
+                .support()
+                .into_iter()
+                .filter_map(|j| self.label(j))
+                .collect(),
+        )
+    }
+
+    /// Shrink the unsat core of the last `check` until it is irreducible, i.e. until removing any
+    /// of its assertions makes the rest, together with the unlabeled assertions, satisfiable.
+    /// Returns `None` unless the last call to `check` returned `UNSAT`.
+    ///
+    /// Each assertion of the core is dropped in turn, and the remaining ones are checked again. If
+    /// they are still unsatisfiable, the assertion is not needed, and the candidates shrink to the
+    /// support of the new proof. This takes at most one `check` per assertion of the initial core.
+    ///
+    /// The checks use the limits of the solver. If one of them gives up, the assertion is kept, so
+    /// the core is still unsatisfiable but may not be irreducible.
+    pub fn minimize_unsat_core(&self) -> Option<Vec<&str>> {
+        let certificate = self.certificate.as_ref()?;
+        let background: Vec<usize> = (0..self.system.len() + self.assumptions.len())
+            .filter(|j| self.label(*j).is_none())
+            .collect();
+        let mut candidates: Vec<usize> = certificate
+            .support()
+            .into_iter()
+            .filter(|j| self.label(*j).is_some())
+            .collect();
+        let mut necessary = Vec::new();
+        while let Some(j) = candidates.pop() {
+            let mut indices: Vec<usize> = background
+                .iter()
+                .chain(&necessary)
+                .chain(&candidates)
+                .copied()
+                .collect();
+            indices.sort_unstable();
+            match self.check_subset(&indices) {
+                Some(support) => candidates.retain(|k| support.contains(k)),
+                None => necessary.push(j),
+            }
+        }
+        necessary.sort_unstable();
+        Some(
+            necessary
+                .into_iter()
+                .filter_map(|j| self.label(j))
+                .collect(),
+        )
+    }
+
+    /// Check the assertions with the given indices, returning the indices of the assertions used
+    /// to prove unsatisfiability, or `None` if they are satisfiable or a limit is reached
+    fn check_subset(&self, indices: &[usize]) -> Option<Vec<usize>> {
+        let mut solver = Self::with_space(self.space().clone());
+        solver.order = self.order.clone();
+        solver.limits = self.limits.clone();
+        for j in indices {
+            solver.assert(self.relation(*j).clone());
+        }
+        if solver.check() != FMEState::UNSAT {
+            return None;
+        }
+        let certificate = solver
+            .certificate
+            .as_ref()
+            .expect("UNSAT has a certificate");
+        Some(
+            certificate
+                .support()
+                .into_iter()
+                .map(|k| indices[k])
+                .collect(),
+        )
     }
 
     /// Check satisfiablility in the current state
+    ///
+    /// Disequalities `e != 0` are not eliminated. Over the rationals, the convex set `P` described
+    /// by the other relations avoids finitely many hyperplanes `e = 0` iff `P` is non-empty and is
+    /// not contained in any one of them, i.e. iff `P /\ e < 0` or `P /\ -e < 0` is satisfiable for
+    /// every disequality. These are checked separately after `P`.
     pub fn check(&mut self) -> FMEState {
-        // reduce system to inequalities and constant equalities
-        self.system.eliminate_nontrivial_eqs();
+        self.invalidate();
+        self.run()
+    }
+
+    /// Check satisfiablility of the assertions together with `assumptions`, without asserting
+    /// them. The space grows as with `assert` if the assumptions range over more variables.
+    ///
+    /// The results stay available until the solver changes. The assumptions are numbered after
+    /// the assertions in the unsat certificate, see `checked_relations()`, and are unlabeled.
+    ///
+    /// ```
+    /// # use presburger::fme::*;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # fn main () {
+    /// let mut solver = FMESolver::new();
+    /// // x_1 <= 0
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 1]).unwrap()));
+    /// for c in 0..3 {
+    ///     // c <= x_1
+    ///     let assumption = LinRel::mk_le(LinExpr::new(vec![c, -1]).unwrap());
+    ///     let expected = if c <= 0 { FMEState::SAT } else { FMEState::UNSAT };
+    ///     assert_eq!(solver.check_assuming(&[assumption]), expected);
+    /// }
+    /// assert_eq!(solver.assertions().len(), 1);
+    /// # }
+    /// ```
+    pub fn check_assuming(&mut self, assumptions: &[LinRel]) -> FMEState {
+        self.invalidate();
+        // grow the space as `assert` would
+        let len = self.system.len();
+        for rel in assumptions {
+            self.system.add_relation(rel.clone());
+        }
+        self.system.truncate(len);
+        self.assumptions = assumptions.to_vec();
+        self.run()
+    }
+
+    /// Decide the assertions and assumptions, and record the result
+    fn run(&mut self) -> FMEState {
+        let start = Instant::now();
+        let mut observer = self.observer.take();
+        let mut monitor = Monitor {
+            stats: SolverStats::default(),
+            observer: observer.as_deref_mut().map(|o| o as &mut dyn FMEObserver),
+            limits: &self.limits,
+        };
+        let result = if self.certificates {
+            self.decide::<Combination>(&mut monitor)
+        } else {
+            self.decide::<Support>(&mut monitor)
+        };
+        self.stats = monitor.stats;
+        self.stats.wall_time = start.elapsed();
+        self.observer = observer;
+        match result {
+            Ok(model) => {
+                self.state = FMEState::SAT;
+                self.model = Some(model);
+            }
+            Err(Stop::Unsat(certificate)) => {
+                self.state = FMEState::UNSAT;
+                self.certificate = certificate;
+            }
+            Err(Stop::Unknown(reason)) => {
+                self.state = FMEState::UNKNOWN;
+                self.reason = Some(reason);
+            }
+        }
+        self.state.clone()
+    }
+
+    /// Decide the assertions, returning a model, a certificate of unsatisfiability if the origins
+    /// `O` are combinations, or the limit that was reached
+    fn decide<O: Origin>(
+        &self,
+        monitor: &mut Monitor,
+    ) -> Result<Model, Stop<Option<UnsatCertificate>>> {
+        let mut base = Derived::<O>::new(self.system.space().dim());
+        let mut disequalities = Vec::new();
+        let relations = self.system.relations().iter().chain(&self.assumptions);
+        for (j, r) in relations.enumerate() {
+            if r.is_disequality() {
+                disequalities.push((j, r.clone()));
+            } else {
+                base.push(r.clone(), O::unit(j));
+            }
+        }
+
+        let mut model = self.solve(base.clone(), monitor).map_err(|stop| {
+            stop.map(|origin| origin.into_combination().map(UnsatCertificate::Farkas))
+        })?;
+        let nes: Vec<LinRel> = disequalities.iter().map(|(_, r)| r.clone()).collect();
+        for (k, (index, ne)) in disequalities.iter().enumerate() {
+            let mut side = |e: LinExpr| {
+                let mut sys = base.clone();
+                sys.push(LinRel::mk_lt(e), O::unit(*index));
+                self.solve(sys, monitor)
+            };
+            let witness = match side(ne.lhs().clone()) {
+                Ok(w) => w,
+                Err(Stop::Unknown(reason)) => return Err(Stop::Unknown(reason)),
+                Err(Stop::Unsat(below)) => side(-ne.lhs()).map_err(|stop| {
+                    stop.map(|above| {
+                        Some(UnsatCertificate::Disequality {
+                            index: *index,
+                            below: below.into_combination()?,
+                            above: above.into_combination()?,
+                        })
+                    })
+                })?,
+            };
+            model = witness::avoid_hyperplanes(model, &witness, &nes[..=k]);
+        }
+        Ok(model)
+    }
+
+    /// Decide a system without disequalities, returning a model if it is satisfiable and the
+    /// origin of a contradiction otherwise, unless a limit is reached
+    fn solve<O: Origin>(
+        &self,
+        mut system: Derived<O>,
+        monitor: &mut Monitor,
+    ) -> Result<Model, Stop<O>> {
+        monitor.emit(FMEEvent::Start {
+            relations: system.relations.len(),
+        });
+        let mut trace = Vec::new();
+        self.eliminate(&mut system, &mut trace, monitor)?;
+        Ok(Self::back_substitute(system.nvars, &trace))
+    }
+
+    /// Decide satisfiability of a system without disequalities by Fourier-Motzkin elimination,
+    /// recording every elimination step in `trace`. If the system is unsatisfiable, return the
+    /// origin of a contradiction, i.e. the combination of the assertions that yields it if `O` is
+    /// `Combination`.
+    ///
+    /// The equalities are solved first, see [`Echelon`], and their solutions substituted in the
+    /// inequalities. If they are inconsistent, the combination of them that is a contradiction is
+    /// the origin.
+    ///
+    /// The combination of a lower bound `L <= x_i` from `a_L x_i + e_L <= 0` and an upper bound
+    /// `x_i <= U` from `a_U x_i + e_U <= 0` is `L - U <= 0`, which is `1/|a_L|` times the first
+    /// relation plus `1/a_U` times the second. It is strict iff one of the bounds is strict.
+    fn eliminate<O: Origin>(
+        &self,
+        system: &mut Derived<O>,
+        trace: &mut Vec<Elimination>,
+        monitor: &mut Monitor,
+    ) -> Result<(), Stop<O>> {
+        // reduce system to inequalities by solving the equalities
+        monitor.check_limits(system.relations.len())?;
+        let equalities: BTreeSet<usize> = system
+            .relations
+            .iter()
+            .zip(system.origins.iter())
+            .filter(|(r, _)| r.is_equality())
+            .flat_map(|(_, o)| o.support())
+            .collect();
+        // the origin of a combination of the relations of `system`
+        let origin_of = |combination: &Combination| {
+            let mut origin = O::default();
+            for (k, m) in combination.multipliers() {
+                origin.add_scaled(&system.origins[*k], m);
+            }
+            origin
+        };
+        let echelon = match Echelon::new(&system.relations) {
+            Ok(echelon) => echelon,
+            Err(combination) => {
+                monitor.emit(FMEEvent::Contradiction);
+                return Err(Stop::Unsat(origin_of(&combination)));
+            }
+        };
+        if !echelon.pivots().is_empty() {
+            monitor.check_eliminations()?;
+        }
+        let row_origins: Vec<O> = echelon.origins().iter().map(origin_of).collect();
+        let (relations, origins) = std::mem::take(&mut system.relations)
+            .into_iter()
+            .zip(std::mem::take(&mut system.origins))
+            .filter(|(r, _)| !r.is_equality())
+            .map(|(r, mut o)| {
+                // the rows have coefficient 1 on their pivot and support no other pivot, so
+                // substituting them adds `-a_p` times the row for each pivot `x_p`
+                for (p, row_origin) in echelon.pivots().iter().zip(row_origins.iter()) {
+                    if r.lhs().supported(*p) {
+                        o.add_scaled(row_origin, &-r.lhs().coeff_unchecked(*p));
+                    }
+                }
+                (echelon.reduce(r), o)
+            })
+            .unzip();
+        system.relations = relations;
+        system.origins = origins;
+        for p in echelon.pivots() {
+            monitor.emit(FMEEvent::Substitute { var: *p });
+        }
+        trace.push(Elimination::Equalities(echelon));
+        let histories = Histories::new(system, &equalities);
 
         loop {
-            println!(
-                "{} le relations at start of loop",
-                self.system.num_relations()
-            );
             // remove the trivial relations
-            self.system.eliminate_trivial_relations();
+            let before = system.relations.len();
+            let (relations, origins): (Vec<LinRel>, Vec<O>) = std::mem::take(&mut system.relations)
+                .into_iter()
+                .zip(std::mem::take(&mut system.origins))
+                .filter(|(r, _)| !r.is_trivial())
+                .unzip();
+            system.relations = relations;
+            system.origins = origins;
 
-            println!(
-                "{} le relations after removing trivial ones",
-                self.system.num_relations()
-            );
+            monitor.emit(FMEEvent::Round {
+                relations: system.relations.len(),
+                trivial: before - system.relations.len(),
+            });
             // If after equality removal there were only trivially SAT equalities then
             // the original system is SAT.
-            if self.system.relations().is_empty() {
-                return FMEState::SAT;
+            if system.relations.is_empty() {
+                return Ok(());
             }
             // check there are no contradictory (in)equalities
-            if self.system.has_trivial_contradiction() {
-                return FMEState::UNSAT;
+            if let Some(k) = system
+                .relations
+                .iter()
+                .position(|r| r.is_trivial_contradiction())
+            {
+                monitor.emit(FMEEvent::Contradiction);
+                return Err(Stop::Unsat(system.origins.swap_remove(k)));
             }
+            monitor.check_limits(system.relations.len())?;
 
             // At this point, there is guaranteed to be at least one non-constant inequality
-            let i = self.system.find_isolatable_variable_in_le().unwrap();
-            println!("isolating variable {i}");
-            let relations = self.system.relations();
-            let mut computed_bounds: Vec<LinExprBound> = relations
-                .iter()
-                .filter_map(|r| r.compute_bound_from(i))
-                .collect();
-            let split_index = partition(&mut computed_bounds, |b| b.bound == Bound::Lower);
-            // computed_bounds = [lower1, ... lowerN, upper1, ... upperM]
-            //                                      ^-- split_index
-            println!(
-                "{} lower bounds and {} upper bounds",
-                split_index,
-                computed_bounds.len() - split_index
-            );
-
-            // Remove all relations from the system that have non-zero a_i;
-            // these are replaced by the lower,upper bound pairs below
-            let mut to_remove = Vec::new();
-            for (j, r) in self.system.relations().iter().enumerate() {
-                if r.lhs().supported(i) {
-                    to_remove.push(j);
+            let i = self
+                .order
+                .choose(&system.relations)
+                .expect("there is a variable to eliminate");
+
+            // Replace all relations that have non-zero a_i by the lower,upper bound pairs below
+            let mut lower = Vec::new();
+            let mut upper = Vec::new();
+            let mut next = Derived::new(system.nvars);
+            for (r, o) in std::mem::take(&mut system.relations)
+                .into_iter()
+                .zip(std::mem::take(&mut system.origins))
+            {
+                match r.compute_bound_from(i) {
+                    None => next.push(r, o),
+                    Some(b) => {
+                        let scale = Rational::ONE / r.lhs().coeff_unchecked(i).clone().abs();
+                        if b.bound == Bound::Lower {
+                            lower.push((b, o, scale));
+                        } else {
+                            upper.push((b, o, scale));
+                        }
+                    }
                 }
             }
-            for j in to_remove {
-                self.system.remove_relation(j);
-            }
+            monitor.check_limits(next.relations.len() + lower.len() * upper.len())?;
+            monitor.check_eliminations()?;
+            monitor.emit(FMEEvent::Eliminate {
+                var: i,
+                lower: lower.len(),
+                upper: upper.len(),
+            });
 
-            // Form all pairs of <= relations: lower_bound_expr <= upper_bound_expr
-            for i in 0..split_index {
-                for j in split_index..computed_bounds.len() {
-                    self.system.add_relation(LinRel::le_from_lhs_rhs(
-                        &computed_bounds[i].expr,
-                        &computed_bounds[j].expr,
-                    ));
+            // Form all pairs of relations: lower_bound_expr <= upper_bound_expr (or <), except
+            // those that are redundant by Imbert's theorem
+            let mut redundant = 0;
+            for (l, l_origin, l_scale) in lower.iter() {
+                monitor.interrupted()?;
+                for (u, u_origin, u_scale) in upper.iter() {
+                    let rel = if l.strict || u.strict {
+                        LinRel::lt_from_lhs_rhs(&l.expr, &u.expr, self.system.space())
+                    } else {
+                        LinRel::le_from_lhs_rhs(&l.expr, &u.expr, self.system.space())
+                    }
+                    .expect("the space grows with the assertions and assumptions");
+                    let mut origin = O::default();
+                    origin.add_scaled(l_origin, l_scale);
+                    origin.add_scaled(u_origin, u_scale);
+                    if histories.is_redundant(&rel, &origin) {
+                        redundant += 1;
+                    } else {
+                        next.push(rel, origin);
+                    }
                 }
             }
+            let dominated = histories.remove_dominated(&mut next);
+            monitor.emit(FMEEvent::Prune {
+                redundant,
+                dominated,
+            });
+            *system = next;
+
+            trace.push(Elimination::Bounds(
+                i,
+                lower.into_iter().map(|(b, _, _)| b).collect(),
+                upper.into_iter().map(|(b, _, _)| b).collect(),
+            ));
         } // end of FME loop
     }
+
+    /// Build a model of the system whose elimination is recorded in `trace`.
+    ///
+    /// Every step only refers to variables eliminated after it, or never, so the eliminated
+    /// variables are given values in reverse order. Variables that were never eliminated are free
+    /// and are assigned zero.
+    fn back_substitute(nvars: usize, trace: &[Elimination]) -> Model {
+        let mut model = Model::zeros(nvars);
+        for step in trace.iter().rev() {
+            match step {
+                Elimination::Equalities(echelon) => {
+                    echelon
+                        .extend_model(&mut model)
+                        .expect("model assigns all variables");
+                }
+                Elimination::Bounds(i, lower, upper) => {
+                    let value = witness::value_between(&model, lower, upper);
+                    model.set(*i, value);
+                }
+            }
+        }
+        model
+    }
 }
 
 impl Default for FMESolver {
@@ -118,16 +1281,68 @@ impl Default for FMESolver {
     }
 }
 
+impl Solver for FMESolver {
+    fn assertions(&self) -> &LinSys {
+        FMESolver::assertions(self)
+    }
+
+    fn assert(&mut self, rel: LinRel) {
+        FMESolver::assert(self, rel);
+    }
+
+    fn try_assert(&mut self, rel: LinRel) -> Result<(), SpaceError> {
+        FMESolver::try_assert(self, rel)
+    }
+
+    fn check(&mut self) -> FMEState {
+        FMESolver::check(self)
+    }
+
+    fn model(&self) -> Option<&Model> {
+        FMESolver::model(self)
+    }
+
+    fn unsat_certificate(&self) -> Option<&UnsatCertificate> {
+        FMESolver::unsat_certificate(self)
+    }
+
+    fn reset(&mut self) {
+        FMESolver::reset(self);
+    }
+}
+
 #[cfg(test)]
 mod test_fme {
     use super::*;
 
+    use crate::elim_order::{MinFillIn, MinProduct};
     use crate::fme::FMESolver;
     use crate::lin_expr::{Bound, LinExpr, LinExprBound};
     use crate::lin_rel::LinRel;
     use crate::lin_sys::LinSys;
+    use crate::types::Rational;
     use crate::{eq, le};
     use dashu::rbig;
+    use proptest::prelude::*;
+
+    /// Check that `solver` is SAT and that its model satisfies all assertions
+    fn assert_sat_with_model(solver: &mut FMESolver) {
+        assert_eq!(solver.check(), FMEState::SAT);
+        let model = solver.model().expect("SAT result has a model");
+        assert_eq!(model.nvars(), solver.space().dim());
+        assert_eq!(solver.assertions().violations(model), Ok(vec![]));
+        assert!(solver.unsat_certificate().is_none());
+    }
+
+    /// Check that `solver` is UNSAT and that its certificate is valid
+    fn assert_unsat_with_certificate(solver: &mut FMESolver) {
+        assert_eq!(solver.check(), FMEState::UNSAT);
+        let certificate = solver
+            .unsat_certificate()
+            .expect("UNSAT result has a certificate");
+        assert_eq!(certificate.verify(solver.assertions()), Ok(()));
+        assert!(solver.model().is_none());
+    }
 
     #[test]
     fn test_manual_fme() {
@@ -162,6 +1377,7 @@ mod test_fme {
             LinExprBound {
                 i: 3,
                 bound: Bound::Upper,
+                strict: false,
                 expr: LinExpr::new(vec![rbig!(-5 / 10), rbig!(0), rbig!(0), rbig!(0)]).unwrap(),
             }
         )
@@ -172,7 +1388,7 @@ mod test_fme {
         let mut solver = FMESolver::new();
         solver.assert(eq!(0, 1, 1)); // x1 + x2 = 0
         solver.assert(eq!(0, 0, 1)); // x2 = 0
-        assert_eq!(solver.check(), FMEState::SAT);
+        assert_sat_with_model(&mut solver);
     }
 
     #[test]
@@ -183,7 +1399,7 @@ mod test_fme {
         solver.assert(eq!(0, 0, 0, 0, 15));
         solver.assert(eq!(0, 0, 0, 11, 13));
         solver.assert(eq!(0, 2, 0, 3, 5));
-        assert_eq!(solver.check(), FMEState::SAT);
+        assert_sat_with_model(&mut solver);
     }
 
     #[test]
@@ -192,7 +1408,7 @@ mod test_fme {
         solver.assert(eq!(0, 1, 1)); // x1 + x2 = 0
         solver.assert(eq!(0, 0, 1)); // x2 = 0
         solver.assert(le!(-1, 0, 0)); // -1 <= 0
-        assert_eq!(solver.check(), FMEState::SAT);
+        assert_sat_with_model(&mut solver);
     }
 
     #[test]
@@ -201,7 +1417,7 @@ mod test_fme {
         solver.assert(eq!(0, 1, 1)); // x1 + x2 = 0
         solver.assert(eq!(0, 0, 1)); // x2 = 0
         solver.assert(le!(1, 0, 0)); // 1 <= 0 -> UNSAT
-        assert_eq!(solver.check(), FMEState::UNSAT);
+        assert_unsat_with_certificate(&mut solver);
     }
 
     #[test]
@@ -209,7 +1425,7 @@ mod test_fme {
         let mut solver = FMESolver::new();
         solver.assert(eq!(1, 1)); // 1 + x1 = 0
         solver.assert(eq!(2, 1)); // 2 + x1 = 0
-        assert_eq!(solver.check(), FMEState::UNSAT);
+        assert_unsat_with_certificate(&mut solver);
     }
 
     #[test]
@@ -221,7 +1437,7 @@ mod test_fme {
         // ==> 1 + -1 - x2 <= 0 => 0 <= x2 unbounded
         // In this case after the first round there are only upper bounds, no lower bounds
         // thus the system has an empty set of relations => SAT
-        assert_eq!(solver.check(), FMEState::SAT);
+        assert_sat_with_model(&mut solver);
     }
 
     // Test from https://en.wikipedia.org/wiki/Fourier%E2%80%93Motzkin_elimination
@@ -232,7 +1448,7 @@ mod test_fme {
         solver.assert(le!(-9, 3, -6, 3));
         solver.assert(le!(7, -1, 5, -2));
         solver.assert(le!(-12, -3, 2, 6));
-        assert_eq!(solver.check(), FMEState::SAT);
+        assert_sat_with_model(&mut solver);
     }
 
     // Test from Decision Procedures, 2nd ed.
@@ -253,7 +1469,93 @@ mod test_fme {
         solver.assert(le!(-1, 0, 1, 0));
         solver.assert(le!(rbig!(3 / 4), rbig!(0), rbig!(0), rbig!(1)));
         solver.assert(le!(-1, 0, 0, 1));
-        assert_eq!(solver.check(), FMEState::SAT);
+        assert_sat_with_model(&mut solver);
+    }
+
+    // Regression: relations supporting the isolated variable must all be removed, not just
+    // those whose indices survive earlier removals.
+    // x1 >= 0, 1 + x1 - x2 <= 0, x2 <= 1/2 is UNSAT
+    #[test]
+    fn test_solver_check_removes_all_supported_relations() {
+        let mut solver = FMESolver::new();
+        solver.assert(le!(0, -1, 0));
+        solver.assert(le!(1, 1, -1));
+        solver.assert(le!(rbig!(-1 / 2), rbig!(0), rbig!(1)));
+        assert_unsat_with_certificate(&mut solver);
+    }
+
+    #[test]
+    fn test_solver_space() {
+        let space = Space::from_names(["x", "y"]).unwrap();
+        let mut solver = FMESolver::with_space(space.clone());
+        assert!(solver.try_assert(le!(0, 1, -1)).is_ok()); // x <= y
+        assert!(solver.try_assert(le!(0, 1, -1, 1)).is_err()); // x_3 is not in the space
+        let sys = LinSys::from_relations(vec![le!(1, -1, 1)]); // anonymous space
+        assert_eq!(solver.assert_sys(&sys), Err(SpaceError::SpaceMismatch));
+        let sys = LinSys::from_relations_in(space.clone(), vec![le!(1, -1, 1)]).unwrap();
+        assert!(solver.assert_sys(&sys).is_ok()); // 1 + y <= x
+        assert_eq!(solver.space(), &space);
+        assert_unsat_with_certificate(&mut solver);
+    }
+
+    #[test]
+    fn test_solver_check_strict() {
+        // x < 0 /\ 0 < x is UNSAT
+        let mut solver = FMESolver::new();
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![0, 1]).unwrap()));
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![0, -1]).unwrap()));
+        assert_unsat_with_certificate(&mut solver);
+
+        // x <= 0 /\ 0 <= x is SAT
+        let mut solver = FMESolver::new();
+        solver.assert(le!(0, 1));
+        solver.assert(le!(0, -1));
+        assert_sat_with_model(&mut solver);
+
+        // x < y /\ y <= z /\ z <= x is UNSAT, strictness propagates through eliminations
+        let mut solver = FMESolver::new();
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![0, 1, -1, 0]).unwrap()));
+        solver.assert(le!(0, 0, 1, -1));
+        solver.assert(le!(0, -1, 0, 1));
+        assert_unsat_with_certificate(&mut solver);
+
+        // 0 <= x < 1 is SAT
+        let mut solver = FMESolver::new();
+        solver.assert(le!(0, -1));
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![-1, 1]).unwrap()));
+        assert_sat_with_model(&mut solver);
+    }
+
+    #[test]
+    fn test_solver_check_disequalities() {
+        // 0 <= x <= 0 /\ x != 0 is UNSAT
+        let mut solver = FMESolver::new();
+        solver.assert(le!(0, -1));
+        solver.assert(le!(0, 1));
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![0, 1]).unwrap()));
+        assert_unsat_with_certificate(&mut solver);
+
+        // 0 <= x <= 1 /\ x != 0 /\ x != 1 is SAT
+        let mut solver = FMESolver::new();
+        solver.assert(le!(0, -1));
+        solver.assert(le!(-1, 1));
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![0, 1]).unwrap()));
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![-1, 1]).unwrap()));
+        assert_sat_with_model(&mut solver);
+
+        // x = y /\ x != y is UNSAT
+        let mut solver = FMESolver::new();
+        solver.assert(eq!(0, 1, -1));
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![0, 1, -1]).unwrap()));
+        assert_unsat_with_certificate(&mut solver);
+
+        // constant disequalities
+        let mut solver = FMESolver::new();
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![0, 0]).unwrap()));
+        assert_unsat_with_certificate(&mut solver);
+        let mut solver = FMESolver::new();
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![1, 0]).unwrap()));
+        assert_sat_with_model(&mut solver);
     }
 
     // Test from Decision Procedures, 2nd ed.
@@ -264,6 +1566,580 @@ mod test_fme {
         solver.assert(le!(0, 1, 0, -1));
         solver.assert(le!(0, -1, 1, 2));
         solver.assert(le!(1, 0, 0, -1));
-        assert_eq!(solver.check(), FMEState::UNSAT);
+        assert_unsat_with_certificate(&mut solver);
+    }
+
+    #[test]
+    fn test_solver_model() {
+        // x1 = 2 x2 + 1, x2 = x3, 1 <= x3 < 2, x4 unconstrained
+        let mut solver = FMESolver::new();
+        solver.assert(eq!(-1, 1, -2, 0, 0));
+        solver.assert(eq!(0, 0, 1, -1, 0));
+        solver.assert(le!(1, 0, 0, -1, 0));
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![-2, 0, 0, 1, 0]).unwrap()));
+        assert_sat_with_model(&mut solver);
+        let model = solver.model().unwrap();
+        assert_eq!(
+            model.value(1).unwrap(),
+            &(model.value(2).unwrap() * Rational::from(2) + Rational::ONE)
+        );
+        assert_eq!(model.value(4), Some(&Rational::ZERO));
+
+        // the assertions are kept, so checking again gives the same result
+        assert_sat_with_model(&mut solver);
+
+        // a new assertion invalidates the model
+        solver.assert(le!(3, 0, 0, -1, 0));
+        assert!(solver.model().is_none());
+        assert_unsat_with_certificate(&mut solver);
+        assert!(solver.model().is_none());
+    }
+
+    #[test]
+    fn test_solver_model_strict_and_disequalities() {
+        // 0 < x1 < y1 < 1, x1 != 1/2, x1 + x2 != 1/2
+        let mut solver = FMESolver::new();
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![0, -1, 0, 0]).unwrap()));
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![0, 1, 0, -1]).unwrap()));
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![-1, 0, 0, 1]).unwrap()));
+        let half = Rational::ONE / Rational::from(2);
+        solver.assert(LinRel::mk_ne(
+            LinExpr::new(vec![-half.clone(), Rational::ONE]).unwrap(),
+        ));
+        solver.assert(LinRel::mk_ne(
+            LinExpr::new(vec![-half, Rational::ONE, Rational::ONE]).unwrap(),
+        ));
+        assert_sat_with_model(&mut solver);
+    }
+
+    #[test]
+    fn test_solver_certificate() {
+        // x1 = x2 + 1, x2 = 2 x3, 0 <= x3, x1 < 1
+        let mut solver = FMESolver::new();
+        solver.assert(eq!(-1, 1, -1, 0));
+        solver.assert(eq!(0, 0, 1, -2));
+        solver.assert(le!(0, 0, 0, -1));
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![-1, 1, 0, 0]).unwrap()));
+        assert_unsat_with_certificate(&mut solver);
+        let Some(UnsatCertificate::Farkas(c)) = solver.unsat_certificate() else {
+            panic!("expected a Farkas certificate");
+        };
+        assert_eq!(c.support().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+
+        // 0 <= x1 <= 0 /\ x1 != 0 needs both sides of the disequality
+        let mut solver = FMESolver::new();
+        solver.assert(le!(0, -1));
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![0, 1]).unwrap()));
+        solver.assert(le!(0, 1));
+        assert_unsat_with_certificate(&mut solver);
+        assert!(matches!(
+            solver.unsat_certificate(),
+            Some(UnsatCertificate::Disequality { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_solver_unsat_core() {
+        // with y = x1 - x2: y < 0, -1/2 < y, y + x3 < 0, 1 < 2 y + x3. The first three are
+        // unsatisfiable, but the first proof found by FME uses all of them.
+        let lt = |coeffs: Vec<i32>| LinRel::mk_lt(LinExpr::new(coeffs).unwrap());
+        let mut solver = FMESolver::new();
+        solver.assert_labeled(lt(vec![0, 1, -1, 0]), "y < 0");
+        solver.assert_labeled(lt(vec![-1, -2, 2, 0]), "-1/2 < y");
+        solver.assert_labeled(lt(vec![0, 1, -1, 1]), "y + x3 < 0");
+        solver.assert_labeled(lt(vec![1, -2, 2, -1]), "1 < 2 y + x3");
+        assert!(solver.unsat_core().is_none());
+        assert_unsat_with_certificate(&mut solver);
+        assert_eq!(
+            solver.unsat_core(),
+            Some(vec!["y < 0", "-1/2 < y", "y + x3 < 0", "1 < 2 y + x3"])
+        );
+        assert_eq!(
+            solver.minimize_unsat_core(),
+            Some(vec!["y < 0", "y + x3 < 0", "1 < 2 y + x3"])
+        );
+
+        // unlabeled assertions are used, but not reported
+        let mut solver = FMESolver::new();
+        solver.assert(le!(0, -1)); // 0 <= x1
+        solver.assert_labeled(le!(0, 0, -1), "0 <= x2");
+        solver.assert_labeled(le!(1, 1, 0), "x1 <= -1");
+        solver.assert_labeled(LinRel::mk_ne(LinExpr::new(vec![0, 1]).unwrap()), "x1 != 0");
+        assert_unsat_with_certificate(&mut solver);
+        assert_eq!(solver.unsat_core(), Some(vec!["x1 <= -1"]));
+        assert_eq!(solver.minimize_unsat_core(), Some(vec!["x1 <= -1"]));
+        assert_eq!(solver.label(0), None);
+        assert_eq!(solver.label(3), Some("x1 != 0"));
+
+        // disequality cores include the disequality and both sides
+        solver.reset();
+        solver.assert_labeled(le!(0, -1), "0 <= x1");
+        solver.assert_labeled(le!(0, 1), "x1 <= 0");
+        solver.assert_labeled(le!(0, 0, 1), "x2 <= 0");
+        solver.assert_labeled(LinRel::mk_ne(LinExpr::new(vec![0, 1]).unwrap()), "x1 != 0");
+        assert_unsat_with_certificate(&mut solver);
+        assert_eq!(
+            solver.minimize_unsat_core(),
+            Some(vec!["0 <= x1", "x1 <= 0", "x1 != 0"])
+        );
+
+        // SAT results have no core
+        solver.reset();
+        solver.assert_labeled(le!(0, 1), "x1 <= 0");
+        assert_sat_with_model(&mut solver);
+        assert_eq!(solver.unsat_core(), None);
+        assert_eq!(solver.minimize_unsat_core(), None);
+    }
+
+    #[test]
+    fn test_solver_equality_phase() {
+        // x1 + x2 = 1, x2 + x3 = 2, x1 - x3 = -1 have rank 2, and x2 <= 0
+        let mut solver = FMESolver::new();
+        solver.assert(eq!(-1, 1, 1, 0));
+        solver.assert(eq!(-2, 0, 1, 1));
+        solver.assert(eq!(1, 1, 0, -1));
+        solver.assert(le!(0, 0, 1, 0));
+        assert_sat_with_model(&mut solver);
+        // two pivots are substituted, and x3 is eliminated by its bound
+        assert_eq!(solver.stats().eliminations, 3);
+
+        // x1 - x3 = 0 contradicts the first two equalities, without the inequality
+        solver.reset();
+        solver.assert(eq!(-1, 1, 1, 0));
+        solver.assert(eq!(-2, 0, 1, 1));
+        solver.assert(le!(0, 0, 1, 0));
+        solver.assert(eq!(0, 1, 0, -1));
+        assert_unsat_with_certificate(&mut solver);
+        assert_eq!(solver.unsat_certificate().unwrap().support(), vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn test_solver_stats_and_observer() {
+        use std::sync::{Arc, Mutex};
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = events.clone();
+        let mut solver = FMESolver::new();
+        solver.set_observer(move |e: &FMEEvent| log.lock().unwrap().push(e.clone()));
+        // x1 = x2, x2 <= 0, 0 <= x2
+        solver.assert(eq!(0, 1, -1));
+        solver.assert(le!(0, 0, 1));
+        solver.assert(le!(0, 0, -1));
+        assert_sat_with_model(&mut solver);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                FMEEvent::Start { relations: 3 },
+                FMEEvent::Substitute { var: 1 },
+                FMEEvent::Round {
+                    relations: 2,
+                    trivial: 0
+                },
+                FMEEvent::Eliminate {
+                    var: 2,
+                    lower: 1,
+                    upper: 1
+                },
+                FMEEvent::Prune {
+                    redundant: 0,
+                    dominated: 0
+                },
+                FMEEvent::Round {
+                    relations: 0,
+                    trivial: 1
+                },
+            ]
+        );
+        let stats = solver.stats();
+        assert_eq!(stats.runs, 1);
+        assert_eq!(stats.eliminations, 2);
+        assert_eq!(stats.peak_relations, 3);
+        assert_eq!(stats.generated, vec![1]);
+        assert_eq!(stats.trivial_removed, 1);
+
+        // each side of a disequality is another run
+        events.lock().unwrap().clear();
+        solver.clear_observer();
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![0, 1, 0]).unwrap()));
+        assert_unsat_with_certificate(&mut solver);
+        assert!(events.lock().unwrap().is_empty());
+        assert_eq!(solver.stats().runs, 3);
+    }
+
+    #[test]
+    fn test_solver_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Box<dyn FMEObserver>>();
+        assert_send::<FMESolver>();
+    }
+
+    #[test]
+    fn test_solver_redundancy() {
+        // 3 x1 < 2 x2, 2 + x2 < 2 x1, 2 < x1, x1 + x2 < 0: after eliminating x1, each relation
+        // has a history of size 2, and eliminating x2 combines them into two relations with
+        // history {0, 1, 2, 3}, which are redundant
+        let lt = |coeffs: Vec<i32>| LinRel::mk_lt(LinExpr::new(coeffs).unwrap());
+        let mut solver = FMESolver::new();
+        solver.assert(lt(vec![0, 3, -2]));
+        solver.assert(lt(vec![2, -2, 1]));
+        solver.assert(lt(vec![2, -1, 0]));
+        solver.assert(lt(vec![0, 1, 1]));
+        assert_unsat_with_certificate(&mut solver);
+        assert_eq!(solver.stats().generated, vec![4, 4]);
+        assert_eq!(solver.stats().redundant_removed, 2);
+    }
+
+    #[test]
+    fn test_solver_limits() {
+        // the system of `test_solver_redundancy` needs two eliminations and at most 4 relations
+        let lt = |coeffs: Vec<i32>| LinRel::mk_lt(LinExpr::new(coeffs).unwrap());
+        let mut solver = FMESolver::new();
+        solver.assert(lt(vec![0, 3, -2]));
+        solver.assert(lt(vec![2, -2, 1]));
+        solver.assert(lt(vec![2, -1, 0]));
+        solver.assert(lt(vec![0, 1, 1]));
+
+        let cases = [
+            (
+                SolverLimits {
+                    max_relations: Some(3),
+                    ..SolverLimits::default()
+                },
+                Some(UnknownReason::MaxRelations),
+            ),
+            (
+                SolverLimits {
+                    max_relations: Some(4),
+                    ..SolverLimits::default()
+                },
+                None,
+            ),
+            (
+                SolverLimits {
+                    max_eliminations: Some(1),
+                    ..SolverLimits::default()
+                },
+                Some(UnknownReason::MaxEliminations),
+            ),
+            (
+                SolverLimits {
+                    max_eliminations: Some(2),
+                    ..SolverLimits::default()
+                },
+                None,
+            ),
+            (
+                SolverLimits {
+                    deadline: Some(Instant::now()),
+                    ..SolverLimits::default()
+                },
+                Some(UnknownReason::Deadline),
+            ),
+        ];
+        for (limits, reason) in cases {
+            solver.set_limits(limits);
+            match reason {
+                Some(reason) => {
+                    assert_eq!(solver.check(), FMEState::UNKNOWN);
+                    assert_eq!(solver.reason_unknown(), Some(reason));
+                    assert!(solver.model().is_none());
+                    assert!(solver.unsat_certificate().is_none());
+                    assert!(solver.unsat_core().is_none());
+                }
+                None => {
+                    assert_unsat_with_certificate(&mut solver);
+                    assert_eq!(solver.reason_unknown(), None);
+                }
+            }
+        }
+
+        let cancel = CancelToken::new();
+        solver.set_limits(SolverLimits {
+            cancel: Some(cancel.clone()),
+            ..SolverLimits::default()
+        });
+        assert_unsat_with_certificate(&mut solver);
+        cancel.cancel();
+        assert!(solver.limits().cancel.as_ref().unwrap().is_cancelled());
+        assert_eq!(solver.check(), FMEState::UNKNOWN);
+        assert_eq!(solver.reason_unknown(), Some(UnknownReason::Cancelled));
+    }
+
+    #[test]
+    fn test_no_elimination_limit() {
+        let mut solver = FMESolver::new();
+        solver.set_limits(SolverLimits {
+            max_eliminations: Some(0),
+            ..SolverLimits::default()
+        });
+        // -1 <= 0 needs no elimination
+        solver.assert(le!(-1));
+        assert_eq!(solver.check(), FMEState::SAT);
+        // 1 <= 0 neither
+        solver.push();
+        solver.assert(le!(1));
+        assert_unsat_with_certificate(&mut solver);
+        solver.pop(1);
+        // x1 = 1 needs a substitution
+        solver.assert(eq!(-1, 1));
+        assert_eq!(solver.check(), FMEState::UNKNOWN);
+        assert_eq!(
+            solver.reason_unknown(),
+            Some(UnknownReason::MaxEliminations)
+        );
+    }
+
+    #[test]
+    fn test_solver_scopes() {
+        let mut solver = FMESolver::new();
+        // x1 <= x2
+        solver.assert_labeled(le!(0, 1, -1), "x1 <= x2");
+        solver.push();
+        // x2 <= 0
+        solver.assert_labeled(le!(0, 0, 1), "x2 <= 0");
+        assert_sat_with_model(&mut solver);
+        solver.push();
+        solver.push();
+        // 1 <= x1, and x3 = 1 adds a variable
+        solver.assert_labeled(le!(1, -1, 0), "1 <= x1");
+        solver.assert(eq!(-1, 0, 0, 1));
+        assert_eq!(solver.num_scopes(), 3);
+        assert_unsat_with_certificate(&mut solver);
+        assert_eq!(
+            solver.unsat_core(),
+            Some(vec!["x1 <= x2", "x2 <= 0", "1 <= x1"])
+        );
+
+        solver.pop(0);
+        assert_eq!(solver.state, FMEState::UNSAT);
+        solver.pop(2);
+        assert_eq!(solver.num_scopes(), 1);
+        assert_eq!(solver.assertions().len(), 2);
+        assert_eq!(solver.space().dim(), 3);
+        assert!(solver.unsat_certificate().is_none());
+        assert_sat_with_model(&mut solver);
+
+        // labels of retracted assertions are forgotten
+        solver.assert(le!(1, -1, 0));
+        assert_eq!(solver.label(2), None);
+        assert_unsat_with_certificate(&mut solver);
+        solver.pop(1);
+        assert_eq!(solver.num_scopes(), 0);
+        assert_eq!(solver.assertions().len(), 1);
+        assert_sat_with_model(&mut solver);
+
+        solver.push();
+        solver.reset();
+        assert_eq!(solver.num_scopes(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot pop 2 scopes, only 1 are open")]
+    fn test_solver_pop_too_many() {
+        let mut solver = FMESolver::new();
+        solver.push();
+        solver.pop(2);
+    }
+
+    #[test]
+    fn test_solver_check_assuming() {
+        let mut solver = FMESolver::new();
+        // x1 <= x2, x2 <= 0
+        solver.assert_labeled(le!(0, 1, -1), "x1 <= x2");
+        solver.assert_labeled(le!(0, 0, 1), "x2 <= 0");
+        // 1 <= x1, x1 != x2
+        let assumptions = [
+            le!(1, -1, 0),
+            LinRel::mk_ne(LinExpr::new(vec![0, 1, -1]).unwrap()),
+        ];
+
+        assert_eq!(solver.check_assuming(&assumptions[1..]), FMEState::SAT);
+        let model = solver.model().unwrap();
+        assert_eq!(solver.assertions().violations(model), Ok(vec![]));
+        assert_eq!(assumptions[1].holds(model), Ok(true));
+
+        assert_eq!(solver.check_assuming(&assumptions), FMEState::UNSAT);
+        assert_eq!(solver.assertions().len(), 2);
+        // the certificate refers to the assumptions after the assertions
+        let sys = solver.checked_relations();
+        assert_eq!(sys.len(), 4);
+        assert_eq!(sys.relations()[2..], assumptions);
+        let certificate = solver.unsat_certificate().unwrap();
+        assert_eq!(certificate.verify(&sys), Ok(()));
+        assert!(certificate.verify(solver.assertions()).is_err());
+        assert_eq!(certificate.support(), vec![0, 1, 2]);
+        assert_eq!(solver.unsat_core(), Some(vec!["x1 <= x2", "x2 <= 0"]));
+        assert_eq!(
+            solver.minimize_unsat_core(),
+            Some(vec!["x1 <= x2", "x2 <= 0"])
+        );
+
+        // assumptions are forgotten by the next check
+        assert_sat_with_model(&mut solver);
+        // and may range over new variables: x1 + x3 = 5
+        assert_eq!(solver.check_assuming(&[eq!(-5, 1, 0, 1)]), FMEState::SAT);
+        assert_eq!(solver.space().dim(), 3);
+        assert_eq!(solver.model().unwrap().nvars(), 3);
+    }
+
+    #[test]
+    fn test_remove_dominated() {
+        let mut base = Derived::new(2);
+        base.push(le!(0, 1, 0), Combination::unit(0));
+        base.push(le!(0, 0, 1), Combination::unit(1));
+        let histories = Histories::new(&base, &BTreeSet::new());
+
+        let both = Combination::from_multipliers([(0, Rational::ONE), (1, Rational::ONE)]);
+        let mut system = Derived::new(2);
+        // x1 <= 0 with history {0}
+        system.push(le!(0, 1, 0), Combination::unit(0));
+        // x1 <= -1/2 is tighter, but its history {0, 1} is larger
+        system.push(le!(1, 2, 0), both.clone());
+        // x1 <= 1 is implied by both
+        system.push(le!(-3, 3, 0), both.clone());
+        // x1 < -1/2 implies x1 <= -1/2
+        system.push(
+            LinRel::mk_lt(LinExpr::new(vec![rbig!(1 / 2), rbig!(1), rbig!(0)]).unwrap()),
+            both,
+        );
+        assert_eq!(histories.remove_dominated(&mut system), 2);
+        assert_eq!(system.relations[0], le!(0, 1, 0));
+        assert!(system.relations[1].is_strict());
+        assert_eq!(system.relations.len(), system.origins.len());
+    }
+
+    fn arb_relation(nvars: usize) -> impl Strategy<Value = LinRel> {
+        (prop::collection::vec(-3i32..=3, nvars + 1), 0..4).prop_map(|(coeffs, c)| {
+            let lhs = LinExpr::new(coeffs).unwrap();
+            match c {
+                0 => LinRel::mk_eq(lhs),
+                1 => LinRel::mk_lt(lhs),
+                2 => LinRel::mk_ne(lhs),
+                _ => LinRel::mk_le(lhs),
+            }
+        })
+    }
+
+    fn arb_system() -> impl Strategy<Value = Vec<LinRel>> {
+        (1usize..4).prop_flat_map(|n| prop::collection::vec(arb_relation(n), 1..6))
+    }
+
+    proptest! {
+        /// Models of satisfiable systems satisfy every assertion, and certificates of
+        /// unsatisfiable systems are valid
+        #[test]
+        fn fme_model_satisfies_assertions(relations in arb_system()) {
+            let mut solver = FMESolver::new();
+            for r in relations {
+                solver.assert(r);
+            }
+            if solver.check() == FMEState::SAT {
+                let model = solver.model().unwrap();
+                prop_assert_eq!(solver.assertions().violations(model), Ok(vec![]));
+            } else {
+                let certificate = solver.unsat_certificate().unwrap();
+                prop_assert_eq!(certificate.verify(solver.assertions()), Ok(()));
+            }
+        }
+
+        /// All elimination orders agree, and give valid models and certificates
+        #[test]
+        fn fme_elimination_orders_agree(relations in arb_system()) {
+            let check = |order: &dyn Fn(&mut FMESolver)| {
+                let mut solver = FMESolver::new();
+                order(&mut solver);
+                for r in relations.iter() {
+                    solver.assert(r.clone());
+                }
+                let state = solver.check();
+                if state == FMEState::SAT {
+                    assert_eq!(solver.assertions().violations(solver.model().unwrap()), Ok(vec![]));
+                } else {
+                    let certificate = solver.unsat_certificate().unwrap();
+                    assert_eq!(certificate.verify(solver.assertions()), Ok(()));
+                }
+                state
+            };
+            let first = check(&|s| s.set_elimination_order(FirstFound));
+            prop_assert_eq!(check(&|s| s.set_elimination_order(MinProduct)), first.clone());
+            prop_assert_eq!(check(&|s| s.set_elimination_order(MinFillIn)), first);
+        }
+
+        /// Without certificates, the solver takes the same steps and finds the same models
+        #[test]
+        fn fme_without_certificates_agrees(relations in arb_system()) {
+            let mut with = FMESolver::new();
+            let mut without = FMESolver::new();
+            without.set_produce_certificates(false);
+            for r in relations {
+                with.assert(r.clone());
+                without.assert(r);
+            }
+            prop_assert_eq!(without.check(), with.check());
+            prop_assert_eq!(without.model(), with.model());
+            prop_assert!(without.unsat_certificate().is_none());
+            let (mut s1, mut s2) = (without.stats().clone(), with.stats().clone());
+            s1.wall_time = Duration::ZERO;
+            s2.wall_time = Duration::ZERO;
+            prop_assert_eq!(s1, s2);
+        }
+
+        /// Checking the assertions of a scope, or a base under assumptions, agrees with checking
+        /// from scratch
+        #[test]
+        fn fme_incremental_agrees(base in arb_system(), extra in arb_system()) {
+            let fresh = |relations: &[LinRel]| {
+                let mut solver = FMESolver::new();
+                for r in relations {
+                    solver.assert(r.clone());
+                }
+                solver.check()
+            };
+            let all: Vec<LinRel> = base.iter().chain(&extra).cloned().collect();
+            let mut solver = FMESolver::new();
+            for r in base.iter() {
+                solver.assert(r.clone());
+            }
+            prop_assert_eq!(solver.check_assuming(&extra), fresh(&all));
+            if let Some(certificate) = solver.unsat_certificate() {
+                prop_assert_eq!(certificate.verify(&solver.checked_relations()), Ok(()));
+            }
+            solver.push();
+            for r in extra.iter() {
+                solver.assert(r.clone());
+            }
+            prop_assert_eq!(solver.check(), fresh(&all));
+            solver.pop(1);
+            prop_assert_eq!(solver.check(), fresh(&base));
+        }
+
+        /// Minimized cores are unsatisfiable, and removing any assertion from them makes them
+        /// satisfiable
+        #[test]
+        fn fme_minimal_core_is_irreducible(relations in arb_system()) {
+            let mut solver = FMESolver::new();
+            for (j, r) in relations.iter().enumerate() {
+                solver.assert_labeled(r.clone(), j.to_string());
+            }
+            if solver.check() == FMEState::UNSAT {
+                let core: Vec<usize> = solver
+                    .minimize_unsat_core()
+                    .unwrap()
+                    .iter()
+                    .map(|l| l.parse().unwrap())
+                    .collect();
+                let check = |skip: Option<usize>| {
+                    let mut s = FMESolver::new();
+                    for j in core.iter().filter(|j| Some(**j) != skip) {
+                        s.assert(relations[*j].clone());
+                    }
+                    s.check()
+                };
+                prop_assert_eq!(check(None), FMEState::UNSAT);
+                for j in core.iter() {
+                    prop_assert_eq!(check(Some(*j)), FMEState::SAT);
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,48 @@
+//! Test fixtures shared by the decision procedures over the integers, [`crate::omega`] and
+//! [`crate::branch_bound`]
+
+use crate::lin_expr::LinExpr;
+use crate::lin_rel::LinRel;
+use crate::solver::{SatResult, Solver};
+use proptest::prelude::*;
+
+/// Check `solver`, and that its model is an integer solution
+pub(crate) fn check_valid(solver: &mut impl Solver) -> SatResult {
+    let state = solver.check();
+    if state == SatResult::SAT {
+        let model = solver.model().expect("SAT result has a model");
+        assert_eq!(model.nvars(), solver.assertions().space().dim());
+        assert!(model.values().iter().all(|v| v.is_int()));
+        assert_eq!(solver.assertions().violations(model), Ok(vec![]));
+    }
+    state
+}
+
+/// The relations `-bound <= x_i <= bound` for all `i`
+pub(crate) fn bounding_box(nvars: usize, bound: i32) -> Vec<LinRel> {
+    (1..=nvars)
+        .flat_map(|i| {
+            let x = LinExpr::var(i, nvars);
+            [
+                LinRel::mk_le(&x - LinExpr::constant(bound, nvars)),
+                LinRel::mk_le(-x - LinExpr::constant(bound, nvars)),
+            ]
+        })
+        .collect()
+}
+
+pub(crate) fn arb_relation(nvars: usize) -> impl Strategy<Value = LinRel> {
+    (prop::collection::vec(-5i32..=5, nvars + 1), 0..6).prop_map(|(coeffs, c)| {
+        let lhs = LinExpr::new(coeffs).unwrap();
+        match c {
+            0 => LinRel::mk_eq(lhs),
+            1 => LinRel::mk_lt(lhs),
+            2 => LinRel::mk_ne(lhs),
+            _ => LinRel::mk_le(lhs),
+        }
+    })
+}
+
+pub(crate) fn arb_system() -> impl Strategy<Value = (usize, Vec<LinRel>)> {
+    (1usize..4).prop_flat_map(|n| (Just(n), prop::collection::vec(arb_relation(n), 1..6)))
+}
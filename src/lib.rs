@@ -1,10 +1,32 @@
 pub mod ast;
 pub mod ast_strategy;
+pub mod branch_bound;
+pub mod certificate;
+pub mod cooper;
+pub mod decide;
+pub mod echelon;
+pub mod elim_order;
 pub mod fme;
+#[cfg(test)]
+mod int_testing;
 pub mod lin_expr;
 pub mod lin_rel;
 pub mod lin_sys;
+pub mod lower;
 pub mod macros;
+pub mod model;
 pub mod nnf;
+pub mod omega;
+mod qe;
+#[cfg(test)]
+mod qe_testing;
+pub mod rational;
+pub mod simplex;
+pub mod solver;
+pub mod space;
 pub mod sym_mod;
 pub mod types;
+pub mod virtual_subs;
+mod witness;
+
+pub use decide::decide;
@@ -1,9 +1,14 @@
 //! Implementation of affine linear expressions: b + \sum_i^n a_i x_i
 
+use crate::model::{Model, ModelError};
+use crate::space::Space;
 use crate::types::Rational;
 use std::cmp::Ordering;
 use std::error::Error;
 use std::fmt;
+use std::ops::{
+    Add, AddAssign, Deref, DerefMut, Div, DivAssign, Index, Mul, MulAssign, Neg, Sub, SubAssign,
+};
 
 #[derive(Debug)]
 pub enum LinExprError {
@@ -45,15 +50,23 @@ impl Error for LinExprError {}
 /// or...
 ///
 /// b + \sum_{i=1}^{n} a_i x_i \le 0
+///
+/// Only the non-zero coefficients are stored, so that arithmetic on expressions over many
+/// variables with few non-zero coefficients costs time in the number of non-zero coefficients.
 #[derive(Debug, Clone)]
 pub struct LinExpr {
-    // Coeffs vector. The 0th element corresponds to the value of the
-    // constant term; this is always present, but its value may be 0.
+    // Total number of variables, including those w/ coeff 0
+    nvars: usize,
+    // The constant term b; this is always present, but its value may be 0.
+    constant: Rational,
+    // Non-zero coefficients (i, a_i), sorted by variable index
     //
-    // Invariant: len(self.coeff) > 0
-    coeff: Vec<Rational>,
+    // Invariant: 1 <= i <= nvars and a_i != 0
+    terms: Vec<(usize, Rational)>,
 }
 
+static ZERO: Rational = Rational::ZERO;
+
 impl PartialEq for LinExpr {
     /// Custom Eq allows correct comparison of
     /// linear expressions even if the underlying arrays of
@@ -86,20 +99,8 @@ impl PartialEq for LinExpr {
     /// ```
     ///
     fn eq(&self, other: &Self) -> bool {
-        if self.const_() != other.const_() {
-            return false;
-        }
-        // Compare lengths of self and other after monomials with coeff
-        // zero are truncated from the end.
-        let sc = self.coeffs();
-        let oc = other.coeffs();
-        match sc.len().cmp(&oc.len()) {
-            Ordering::Less => oc[sc.len()..].iter().all(|a| a.is_zero()) && sc == &oc[..sc.len()],
-            Ordering::Equal => sc == oc,
-            Ordering::Greater => {
-                sc[oc.len()..].iter().all(|a| a.is_zero()) && &sc[..oc.len()] == oc
-            }
-        }
+        // Only non-zero coefficients are stored, so the number of variables does not matter
+        self.constant == other.constant && self.terms == other.terms
     }
 }
 
@@ -122,19 +123,7 @@ impl Eq for LinExpr {}
 /// ```
 impl fmt::Display for LinExpr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut term_vec = Vec::new();
-        if !self.const_().is_zero() {
-            term_vec.push(format!("{}", self.const_()));
-        }
-        let coeffs = self.coeffs();
-        for (i, a) in coeffs.iter().enumerate() {
-            if *a > Rational::ZERO {
-                term_vec.push(format!("{} x_{}", a, i + 1));
-            } else if *a < Rational::ZERO {
-                term_vec.push(format!("({}) x_{}", a, i + 1));
-            }
-        }
-        write!(f, "{}", term_vec.join(" + "))
+        write!(f, "{}", self.format_terms(Space::default_name))
     }
 }
 
@@ -142,12 +131,16 @@ impl LinExpr {
     /// Create a new `LinExpr` from a slice of `Rational`
     pub fn new(coeffs: Vec<impl Into<Rational>>) -> Result<Self, LinExprError> {
         if coeffs.is_empty() {
-            Err(LinExprError::RationalInvalid)
-        } else {
-            Ok(Self {
-                coeff: coeffs.into_iter().map(|c| c.into()).collect(),
-            })
+            return Err(LinExprError::RationalInvalid);
+        }
+        let mut coeffs = coeffs.into_iter().map(|c| c.into());
+        let constant = coeffs.next().expect("coeffs is non-empty");
+        let mut expr = Self::new_zeros(0);
+        expr.constant = constant;
+        for a in coeffs {
+            expr.add_var(a);
         }
+        Ok(expr)
     }
 }
 
@@ -155,94 +148,546 @@ impl LinExpr {
     /// Create a new zero `LinExpr` with given number of variables
     pub fn new_zeros(nvars: usize) -> Self {
         Self {
-            coeff: vec![Rational::ZERO; nvars + 1],
+            nvars,
+            constant: Rational::ZERO,
+            terms: Vec::new(),
         }
     }
 
+    /// Create the expression `x_i` over `nvars` variables, where `1 <= i <= nvars`
+    ///
+    /// ```
+    /// # use presburger::lin_expr::*;
+    /// # fn main () {
+    /// assert_eq!(LinExpr::var(2, 3), LinExpr::new(vec![0, 0, 1, 0]).unwrap());
+    /// # }
+    /// ```
+    pub fn var(i: usize, nvars: usize) -> Self {
+        debug_assert!(1 <= i && i <= nvars);
+        let mut expr = Self::new_zeros(nvars);
+        expr.terms.push((i, Rational::ONE));
+        expr
+    }
+
+    /// Create the constant expression `c` over `nvars` variables
+    pub fn constant(c: impl Into<Rational>, nvars: usize) -> Self {
+        let mut expr = Self::new_zeros(nvars);
+        expr.constant = c.into();
+        expr
+    }
+
     /// If `self` is an expression over x_1 ... x_n, then add new variable x_{n+1}
     /// with coefficient `value`.
     pub fn add_var(&mut self, value: impl Into<Rational>) {
-        self.coeff.push(value.into());
+        self.nvars += 1;
+        let value = value.into();
+        if !value.is_zero() {
+            self.terms.push((self.nvars, value));
+        }
+    }
+
+    /// Drop the variables `vars` (each > 0) from the expression, renumbering the remaining
+    /// variables in order. Their coefficients are discarded.
+    ///
+    /// ```
+    /// # use presburger::lin_expr::*;
+    /// # fn main () {
+    /// let e = LinExpr::new(vec![1, 2, 3, 4]).unwrap();
+    /// assert_eq!(e.remove_vars(&[2]), LinExpr::new(vec![1, 2, 4]).unwrap());
+    /// # }
+    /// ```
+    pub fn remove_vars(&self, vars: &[usize]) -> Self {
+        let mut vars = vars.to_vec();
+        vars.sort_unstable();
+        vars.dedup();
+        vars.retain(|i| 1 <= *i && *i <= self.nvars);
+        let removed_below = |i: usize| vars.partition_point(|j| *j < i);
+        Self {
+            nvars: self.nvars - vars.len(),
+            constant: self.constant.clone(),
+            terms: self
+                .terms
+                .iter()
+                .filter(|(i, _)| !vars.contains(i))
+                .map(|(i, a)| (i - removed_below(*i), a.clone()))
+                .collect(),
+        }
+    }
+
+    /// Pad the expression with zero coefficients so that it ranges over at least `nvars` variables
+    pub fn extend_to(&mut self, nvars: usize) {
+        self.nvars = self.nvars.max(nvars);
     }
 
     /// Total number of variables in the expression, including those w/ coeff 0
     pub fn nvars(&self) -> usize {
-        self.coeff.len() - 1
+        self.nvars
+    }
+
+    /// Number of variables with non-zero coefficient
+    pub fn nnz(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Position of x_i in `self.terms`, or the position where it would be inserted
+    fn find(&self, i: usize) -> Result<usize, usize> {
+        self.terms.binary_search_by_key(&i, |(j, _)| *j)
     }
 
     /// Get the coefficient a_i
     pub fn coeff(&self, i: usize) -> Result<&Rational, LinExprError> {
         if 1 <= i && i <= self.nvars() {
-            Ok(&self.coeff[i])
+            Ok(self.coeff_unchecked(i))
         } else {
             Err(LinExprError::IndexOutOfBounds)
         }
     }
 
+    /// Get the coefficient a_i, where i = 0 is the constant term. Variables beyond `nvars()` have
+    /// coefficient 0.
     pub fn coeff_unchecked(&self, i: usize) -> &Rational {
-        &self.coeff[i]
+        if i == 0 {
+            return &self.constant;
+        }
+        match self.find(i) {
+            Ok(k) => &self.terms[k].1,
+            Err(_) => &ZERO,
+        }
     }
 
     /// Set the coefficient a_i
     pub fn set_coeff(&mut self, i: usize, value: Rational) -> Result<(), LinExprError> {
         if 1 <= i && i <= self.nvars() {
-            self.coeff[i] = value;
+            self.set_coeff_unchecked(i, value);
             Ok(())
         } else {
             Err(LinExprError::IndexOutOfBounds)
         }
     }
 
-    /// Set the coefficient a_i, i > 0 is not checked
+    /// Set the coefficient a_i, where i = 0 is the constant term. The expression is extended to
+    /// range over x_i if `i > nvars()`.
     pub fn set_coeff_unchecked(&mut self, i: usize, value: Rational) {
-        self.coeff[i] = value;
+        if i == 0 {
+            self.constant = value;
+            return;
+        }
+        self.extend_to(i);
+        match (self.find(i), value.is_zero()) {
+            (Ok(k), true) => {
+                self.terms.remove(k);
+            }
+            (Ok(k), false) => self.terms[k].1 = value,
+            (Err(_), true) => (),
+            (Err(k), false) => self.terms.insert(k, (i, value)),
+        }
     }
 
-    /// Get a slice of the variable coefficients a_1 ... a_n
-    pub fn coeffs(&self) -> &[Rational] {
-        &self.coeff[1..]
+    /// Get the variable coefficients a_1 ... a_n, including those that are zero
+    pub fn coeffs(&self) -> Coeffs<'_> {
+        Coeffs { expr: self }
     }
 
-    /// Get a mutable slice of the variable coefficients a_1 ... a_n
-    pub fn coeffs_mut(&mut self) -> &mut [Rational] {
-        &mut self.coeff[1..]
+    /// Get a mutable view of the variable coefficients a_1 ... a_n, including those that are
+    /// zero. The coefficients are written back to the expression when the view is dropped.
+    pub fn coeffs_mut(&mut self) -> CoeffsMut<'_> {
+        let dense = self.coeffs().iter().cloned().collect();
+        CoeffsMut { expr: self, dense }
+    }
+
+    /// Get the non-zero coefficients `(i, a_i)`, sorted by variable index
+    pub fn terms(&self) -> impl ExactSizeIterator<Item = (usize, &Rational)> + Clone {
+        self.terms.iter().map(|(i, a)| (*i, a))
     }
 
     /// Get the constant term
     pub fn const_(&self) -> &Rational {
-        &self.coeff[0]
+        &self.constant
     }
 
     /// Set the constant term
     pub fn set_const(&mut self, value: Rational) {
-        self.coeff[0] = value;
+        self.constant = value;
     }
 
     /// Is the variable x_i in the support, i.e. a_i != 0?
     pub fn supported(&self, i: usize) -> bool {
-        if 1 <= i && i <= self.nvars() {
-            !self.coeff[i].is_zero()
-        } else {
-            false
-        }
+        i >= 1 && self.find(i).is_ok()
     }
 
     /// Check if the expression is equivalent to zero (all coefficients and constant are zero)
     pub fn is_zero(&self) -> bool {
-        self.const_().is_zero() && self.coeffs().iter().all(|c| c.is_zero())
+        self.constant.is_zero() && self.terms.is_empty()
     }
 
     /// Check if the expression is a constant (all coefficients are zero)
     pub fn is_const(&self) -> bool {
-        self.coeffs().iter().all(|c| c.is_zero())
+        self.terms.is_empty()
+    }
+
+    /// Evaluate the expression under `model`, which must assign every variable with a non-zero
+    /// coefficient
+    ///
+    /// ```
+    /// # use presburger::lin_expr::*;
+    /// # use presburger::model::{Model, ModelError};
+    /// # use presburger::types::Rational;
+    /// # fn main () {
+    /// // 1 + 2 x_1 - x_2 at x_1 = 3, x_2 = 4
+    /// let e = LinExpr::new(vec![1, 2, -1]).unwrap();
+    /// assert_eq!(e.eval(&Model::from_values(vec![3, 4])), Ok(Rational::from(3)));
+    /// assert_eq!(e.eval(&Model::from_values(vec![3])), Err(ModelError::Unassigned(2)));
+    /// # }
+    /// ```
+    pub fn eval(&self, model: &Model) -> Result<Rational, ModelError> {
+        let mut value = self.constant.clone();
+        for (i, a) in self.terms.iter() {
+            value += a * model.value(*i).ok_or(ModelError::Unassigned(*i))?;
+        }
+        Ok(value)
+    }
+
+    /// Display the expression using the variable names of `space`.
+    ///
+    /// Variables beyond the dimension of `space` are given their default names.
+    ///
+    /// ```
+    /// # use presburger::lin_expr::*;
+    /// # use presburger::space::Space;
+    /// # fn main () {
+    /// let space = Space::from_names(["x", "y"]).unwrap();
+    /// let e0 = LinExpr::new(vec![1i64, 2, -1, 3]).unwrap();
+    /// assert_eq!(e0.to_string_in(&space), "1 + 2 x + (-1) y + 3 x_3");
+    /// # }
+    /// ```
+    pub fn to_string_in(&self, space: &Space) -> String {
+        self.format_terms(|i| {
+            space
+                .name(i)
+                .map_or_else(|| Space::default_name(i), |n| n.to_string())
+        })
+    }
+
+    /// Join the non-zero monomials of the expression, naming variable `i` by `name(i)`
+    fn format_terms(&self, name: impl Fn(usize) -> String) -> String {
+        let mut term_vec = Vec::new();
+        if !self.const_().is_zero() {
+            term_vec.push(format!("{}", self.const_()));
+        }
+        for (i, a) in self.terms.iter() {
+            if *a > Rational::ZERO {
+                term_vec.push(format!("{} {}", a, name(*i)));
+            } else {
+                term_vec.push(format!("({}) {}", a, name(*i)));
+            }
+        }
+        term_vec.join(" + ")
+    }
+
+    /// Replace the terms by the merge of the terms of `self` and `rhs`, negating those of `rhs`
+    /// if `negate`
+    fn merge(&mut self, rhs: &LinExpr, negate: bool) {
+        let signed = |a: &Rational| if negate { -a.clone() } else { a.clone() };
+        let lhs_terms = std::mem::take(&mut self.terms);
+        let mut terms = Vec::with_capacity(lhs_terms.len() + rhs.terms.len());
+        let mut lhs_iter = lhs_terms.into_iter().peekable();
+        let mut rhs_iter = rhs.terms.iter().peekable();
+        loop {
+            let ord = match (lhs_iter.peek(), rhs_iter.peek()) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((i, _)), Some((j, _))) => i.cmp(j),
+            };
+            match ord {
+                Ordering::Less => terms.push(lhs_iter.next().unwrap()),
+                Ordering::Greater => {
+                    let (j, b) = rhs_iter.next().unwrap();
+                    terms.push((*j, signed(b)));
+                }
+                Ordering::Equal => {
+                    let (i, a) = lhs_iter.next().unwrap();
+                    let (_, b) = rhs_iter.next().unwrap();
+                    let c = a + signed(b);
+                    if !c.is_zero() {
+                        terms.push((i, c));
+                    }
+                }
+            }
+        }
+        self.terms = terms;
+    }
+}
+
+/// The variable coefficients a_1 ... a_n of a [`LinExpr`], including those that are zero, indexed
+/// from 0 like a slice
+///
+/// ```
+/// # use presburger::lin_expr::*;
+/// # use presburger::types::Rational;
+/// # fn main () {
+/// let e = LinExpr::new(vec![1, 0, 2]).unwrap();
+/// let coeffs = e.coeffs();
+/// assert_eq!(coeffs.len(), 2);
+/// assert_eq!(coeffs[1], Rational::from(2));
+/// assert_eq!(coeffs, &[Rational::ZERO, Rational::from(2)]);
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct Coeffs<'a> {
+    expr: &'a LinExpr,
+}
+
+impl<'a> Coeffs<'a> {
+    /// The number of variables
+    pub fn len(&self) -> usize {
+        self.expr.nvars
+    }
+
+    /// Is the expression over no variables?
+    pub fn is_empty(&self) -> bool {
+        self.expr.nvars == 0
+    }
+
+    /// Iterate over the coefficients in order
+    pub fn iter(&self) -> CoeffsIter<'a> {
+        CoeffsIter {
+            terms: self.expr.terms.iter().peekable(),
+            next: 1,
+            nvars: self.expr.nvars,
+        }
+    }
+}
+
+impl Index<usize> for Coeffs<'_> {
+    type Output = Rational;
+
+    /// The coefficient a_{j+1}. Panics if `j >= len()`, like a slice.
+    fn index(&self, j: usize) -> &Rational {
+        assert!(j < self.len(), "coefficient index {j} out of bounds");
+        self.expr.coeff_unchecked(j + 1)
+    }
+}
+
+impl<'a> IntoIterator for Coeffs<'a> {
+    type Item = &'a Rational;
+    type IntoIter = CoeffsIter<'a>;
+
+    fn into_iter(self) -> CoeffsIter<'a> {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for Coeffs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq<[Rational]> for Coeffs<'_> {
+    fn eq(&self, other: &[Rational]) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<const N: usize> PartialEq<&[Rational; N]> for Coeffs<'_> {
+    fn eq(&self, other: &&[Rational; N]) -> bool {
+        *self == other[..]
+    }
+}
+
+/// Iterator over the coefficients of a [`LinExpr`], see [`LinExpr::coeffs`]
+#[derive(Clone)]
+pub struct CoeffsIter<'a> {
+    terms: std::iter::Peekable<std::slice::Iter<'a, (usize, Rational)>>,
+    // index of the next variable
+    next: usize,
+    nvars: usize,
+}
+
+impl<'a> Iterator for CoeffsIter<'a> {
+    type Item = &'a Rational;
+
+    fn next(&mut self) -> Option<&'a Rational> {
+        if self.next > self.nvars {
+            return None;
+        }
+        let i = self.next;
+        self.next += 1;
+        match self.terms.next_if(|(j, _)| *j == i) {
+            Some((_, a)) => Some(a),
+            None => Some(&ZERO),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.nvars + 1 - self.next;
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for CoeffsIter<'_> {}
+
+/// Mutable view of the coefficients of a [`LinExpr`], see [`LinExpr::coeffs_mut`]
+pub struct CoeffsMut<'a> {
+    expr: &'a mut LinExpr,
+    dense: Vec<Rational>,
+}
+
+impl Deref for CoeffsMut<'_> {
+    type Target = [Rational];
+
+    fn deref(&self) -> &[Rational] {
+        &self.dense
+    }
+}
+
+impl DerefMut for CoeffsMut<'_> {
+    fn deref_mut(&mut self) -> &mut [Rational] {
+        &mut self.dense
+    }
+}
+
+impl Drop for CoeffsMut<'_> {
+    fn drop(&mut self) {
+        self.expr.terms = std::mem::take(&mut self.dense)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, a)| !a.is_zero())
+            .map(|(i, a)| (i + 1, a))
+            .collect();
+    }
+}
+
+/// Arithmetic on linear expressions.
+///
+/// Operands over different numbers of variables are padded with zero coefficients, consistent with
+/// the length insensitive `PartialEq`; the result ranges over the larger number of variables.
+///
+/// ```
+/// # use presburger::lin_expr::*;
+/// # use presburger::types::Rational;
+/// # fn main () {
+/// // (1 + x_1) + 2 (x_2 - 1) = -1 + x_1 + 2 x_2
+/// let e = LinExpr::constant(1, 2)
+///     + LinExpr::var(1, 1)
+///     + (LinExpr::var(2, 2) - LinExpr::constant(1, 0)) * Rational::from(2);
+/// assert_eq!(e, LinExpr::new(vec![-1, 1, 2]).unwrap());
+/// assert_eq!(e.nvars(), 2);
+/// # }
+/// ```
+impl AddAssign<&LinExpr> for LinExpr {
+    fn add_assign(&mut self, rhs: &LinExpr) {
+        self.extend_to(rhs.nvars());
+        self.constant += &rhs.constant;
+        self.merge(rhs, false);
+    }
+}
+
+impl SubAssign<&LinExpr> for LinExpr {
+    fn sub_assign(&mut self, rhs: &LinExpr) {
+        self.extend_to(rhs.nvars());
+        self.constant -= &rhs.constant;
+        self.merge(rhs, true);
+    }
+}
+
+impl MulAssign<&Rational> for LinExpr {
+    fn mul_assign(&mut self, rhs: &Rational) {
+        if rhs.is_zero() {
+            self.terms.clear();
+        }
+        for (_, a) in self.terms.iter_mut() {
+            *a *= rhs;
+        }
+        self.constant *= rhs;
+    }
+}
+
+/// Division by zero panics, like division of `Rational`
+impl DivAssign<&Rational> for LinExpr {
+    fn div_assign(&mut self, rhs: &Rational) {
+        for (_, a) in self.terms.iter_mut() {
+            *a /= rhs;
+        }
+        self.constant /= rhs;
+    }
+}
+
+/// Implement the by-value and by-reference variants of a binary operator in terms of the
+/// corresponding `OpAssign<&Rhs> for LinExpr`
+macro_rules! impl_binop_via_assign {
+    ($Op:ident, $op:ident, $OpAssign:ident, $op_assign:ident, $Rhs:ty) => {
+        impl $OpAssign<$Rhs> for LinExpr {
+            fn $op_assign(&mut self, rhs: $Rhs) {
+                $OpAssign::$op_assign(self, &rhs);
+            }
+        }
+
+        impl $Op<$Rhs> for LinExpr {
+            type Output = LinExpr;
+            fn $op(mut self, rhs: $Rhs) -> LinExpr {
+                $OpAssign::$op_assign(&mut self, &rhs);
+                self
+            }
+        }
+
+        impl $Op<&$Rhs> for LinExpr {
+            type Output = LinExpr;
+            fn $op(mut self, rhs: &$Rhs) -> LinExpr {
+                $OpAssign::$op_assign(&mut self, rhs);
+                self
+            }
+        }
+
+        impl $Op<$Rhs> for &LinExpr {
+            type Output = LinExpr;
+            fn $op(self, rhs: $Rhs) -> LinExpr {
+                let mut res = self.clone();
+                $OpAssign::$op_assign(&mut res, &rhs);
+                res
+            }
+        }
+
+        impl $Op<&$Rhs> for &LinExpr {
+            type Output = LinExpr;
+            fn $op(self, rhs: &$Rhs) -> LinExpr {
+                let mut res = self.clone();
+                $OpAssign::$op_assign(&mut res, rhs);
+                res
+            }
+        }
+    };
+}
+
+impl_binop_via_assign!(Add, add, AddAssign, add_assign, LinExpr);
+impl_binop_via_assign!(Sub, sub, SubAssign, sub_assign, LinExpr);
+impl_binop_via_assign!(Mul, mul, MulAssign, mul_assign, Rational);
+impl_binop_via_assign!(Div, div, DivAssign, div_assign, Rational);
+
+impl Neg for LinExpr {
+    type Output = LinExpr;
+    fn neg(mut self) -> LinExpr {
+        for (_, a) in self.terms.iter_mut() {
+            *a = -std::mem::take(a);
+        }
+        self.constant = -std::mem::take(&mut self.constant);
+        self
+    }
+}
+
+impl Neg for &LinExpr {
+    type Output = LinExpr;
+    fn neg(self) -> LinExpr {
+        -self.clone()
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Bound {
-    /// An upper bound: x_i <= b + sum_j b_j x_j
+    /// An upper bound: x_i <= b + sum_j b_j x_j (or <)
     Upper,
-    /// A lower bound: b + sum_j b_j x_j <= x_i
+    /// A lower bound: b + sum_j b_j x_j <= x_i (or <)
     Lower,
 }
 
@@ -253,6 +698,8 @@ pub struct LinExprBound {
     pub i: usize,
     /// Type of bound
     pub bound: Bound,
+    /// Is the bound strict?
+    pub strict: bool,
     /// bounding expression
     pub expr: LinExpr,
 }
@@ -260,6 +707,7 @@ pub struct LinExprBound {
 #[cfg(test)]
 mod test_expr_support {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn lin_expr_basic_api() {
@@ -298,6 +746,109 @@ mod test_expr_support {
         assert!(e2.supported(1));
     }
 
+    #[test]
+    fn lin_expr_extend_to() {
+        let mut e1 = LinExpr::new(vec![1, 2]).expect("failed to create linear expression");
+        e1.extend_to(3);
+        assert_eq!(e1.nvars(), 3);
+        assert_eq!(e1.coeff(3).unwrap(), &0.into());
+        assert_eq!(e1, LinExpr::new(vec![1, 2]).unwrap());
+
+        // never truncates
+        e1.extend_to(1);
+        assert_eq!(e1.nvars(), 3);
+    }
+
+    #[test]
+    fn lin_expr_sparse_storage() {
+        let mut e1 = LinExpr::new(vec![1, 0, 2, 0]).unwrap();
+        assert_eq!(e1.nvars(), 3);
+        assert_eq!(e1.nnz(), 1);
+        assert_eq!(e1.coeffs(), &[0.into(), 2.into(), 0.into()]);
+
+        // setting a coefficient to zero removes it, and unchecked indices grow the expression
+        e1.set_coeff(2, Rational::ZERO).unwrap();
+        assert!(e1.is_const());
+        e1.set_coeff_unchecked(5, Rational::from(3));
+        assert_eq!(e1.nvars(), 5);
+        assert_eq!(e1.coeff(5).unwrap(), &3.into());
+        assert_eq!(e1.coeff_unchecked(6), &0.into());
+
+        // the mutable view writes back on drop
+        for a in e1.coeffs_mut().iter_mut() {
+            *a += Rational::ONE;
+        }
+        assert_eq!(e1, LinExpr::new(vec![1, 1, 1, 1, 1, 4]).unwrap());
+        e1.coeffs_mut()[4] = Rational::ZERO;
+        assert_eq!(e1.nnz(), 4);
+        assert_eq!(
+            e1.terms().map(|(i, _)| i).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+
+        // removing variables renumbers the remaining ones
+        let e2 = LinExpr::new(vec![1, 2, 0, 4, 5]).unwrap();
+        assert_eq!(
+            e2.remove_vars(&[3, 1, 3]),
+            LinExpr::new(vec![1, 0, 5]).unwrap()
+        );
+        assert_eq!(e2.remove_vars(&[3, 1, 3]).nvars(), 2);
+    }
+
+    #[test]
+    fn lin_expr_constructors() {
+        let x2 = LinExpr::var(2, 3);
+        assert_eq!(x2.nvars(), 3);
+        assert!(x2.supported(2));
+        assert!(!x2.supported(1));
+        assert_eq!(x2.const_(), &0.into());
+
+        let c = LinExpr::constant(-4, 2);
+        assert_eq!(c.nvars(), 2);
+        assert!(c.is_const());
+        assert_eq!(c.const_(), &Rational::from(-4));
+    }
+
+    #[test]
+    fn lin_expr_arith() {
+        let e1 = LinExpr::new(vec![1, 2, 3]).unwrap();
+        let e2 = LinExpr::new(vec![-1, 1, 0, 5]).unwrap();
+
+        // padding to the larger number of variables
+        let sum = &e1 + &e2;
+        assert_eq!(sum.nvars(), 3);
+        assert_eq!(sum, LinExpr::new(vec![0, 3, 3, 5]).unwrap());
+        assert_eq!(&e2 + &e1, sum);
+        assert_eq!(&e1 - &e2, LinExpr::new(vec![2, 1, 3, -5]).unwrap());
+        assert_eq!(&e2 - &e1, -(&e1 - &e2));
+
+        // owned and borrowed operands agree
+        assert_eq!(e1.clone() + e2.clone(), sum);
+        assert_eq!(e1.clone() + &e2, sum);
+        assert_eq!(&e1 + e2.clone(), sum);
+
+        // scaling
+        let half = Rational::ONE / Rational::from(2);
+        assert_eq!(
+            &e1 * Rational::from(2),
+            LinExpr::new(vec![2, 4, 6]).unwrap()
+        );
+        assert_eq!(
+            &e1 * &half,
+            LinExpr::new(vec![half.clone(), Rational::ONE, Rational::from(3) * &half]).unwrap()
+        );
+        assert_eq!(&e1 / Rational::from(2), &e1 * half);
+        assert_eq!(-LinExpr::new_zeros(2), LinExpr::new_zeros(2));
+
+        // assignment variants
+        let mut e3 = e1.clone();
+        e3 += &e2;
+        e3 -= e2;
+        e3 *= Rational::from(3);
+        e3 /= &Rational::from(3);
+        assert_eq!(e3, e1);
+    }
+
     #[test]
     fn test_is_zero() {
         // Zero expression: 0 = 0
@@ -353,4 +904,32 @@ mod test_expr_support {
         assert!(!mixed_expr.is_const());
         assert!(!mixed_expr.is_zero());
     }
+
+    fn arb_coeffs() -> impl Strategy<Value = Vec<i64>> {
+        prop::collection::vec(prop_oneof![Just(0i64), -5i64..5], 1..12)
+    }
+
+    /// The coefficients `[b, a_1, ..., a_n]` of `e`
+    fn dense(e: &LinExpr) -> Vec<Rational> {
+        std::iter::once(e.const_().clone())
+            .chain(e.coeffs().iter().cloned())
+            .collect()
+    }
+
+    proptest! {
+        /// Arithmetic agrees with arithmetic on the dense coefficient vectors
+        #[test]
+        fn lin_expr_arith_agrees_with_dense(c1 in arb_coeffs(), c2 in arb_coeffs(), k in -3i64..3) {
+            let (e1, e2) = (LinExpr::new(c1.clone()).unwrap(), LinExpr::new(c2.clone()).unwrap());
+            let k = Rational::from(k);
+            let n = c1.len().max(c2.len());
+            let coeff = |c: &[i64], i: usize| Rational::from(c.get(i).copied().unwrap_or(0));
+            let expected: Vec<Rational> = (0..n).map(|i| coeff(&c1, i) + coeff(&c2, i) * &k).collect();
+            prop_assert_eq!(dense(&(&e1 + &e2 * &k)), expected);
+            let expected: Vec<Rational> = (0..n).map(|i| coeff(&c1, i) - coeff(&c2, i)).collect();
+            prop_assert_eq!(dense(&(&e1 - &e2)), expected);
+            prop_assert_eq!(dense(&-&e1), c1.iter().map(|c| Rational::from(-c)).collect::<Vec<_>>());
+            prop_assert!(e1.terms().all(|(i, a)| !a.is_zero() && a == &coeff(&c1, i)));
+        }
+    }
 }
@@ -1,7 +1,10 @@
-//! Implemenetation of linear relations: b + \sum_{i=0}^n a_i x_i = 0 (or <= 0)
+//! Implemenetation of linear relations: b + \sum_{i=0}^n a_i x_i = 0 (or <= 0, < 0, != 0)
 
-use crate::lin_expr::{Bound, LinExpr, LinExprBound, LinExprError};
-use crate::types::Rational;
+use crate::lin_expr::{Bound, Coeffs, LinExpr, LinExprBound, LinExprError};
+use crate::model::{Model, ModelError};
+use crate::space::{Space, SpaceError};
+use crate::sym_mod::{div_floor, gcd, lcm};
+use crate::types::{Domain, Integer, Rational};
 use std::fmt;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -10,6 +13,10 @@ pub enum Constraint {
     Eq,
     /// Less than or equal to
     Le,
+    /// Strictly less than
+    Lt,
+    /// Disequality
+    Ne,
 }
 
 impl fmt::Display for Constraint {
@@ -17,12 +24,14 @@ impl fmt::Display for Constraint {
         let symbol = match self {
             Constraint::Eq => "=",
             Constraint::Le => "<=",
+            Constraint::Lt => "<",
+            Constraint::Ne => "!=",
         };
         write!(f, "{symbol}")
     }
 }
 
-/// Represents `LinExpr rel 0` where `rel` can be = or <= (in)equality
+/// Represents `LinExpr rel 0` where `rel` can be =, <=, <, or !=
 ///
 /// Note that the derived equality is only structural, not mathematical equality.
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -52,24 +61,77 @@ impl LinRel {
         }
     }
 
-    /// Create a normalized expr <= 0 relation from a non-normalized one: lhs <= rhs
-    pub fn le_from_lhs_rhs(lhs: &LinExpr, rhs: &LinExpr) -> Self {
-        let n = lhs.nvars();
-        debug_assert!(n == rhs.nvars());
-        let mut expr = LinExpr::new_zeros(n);
-        expr.set_const(lhs.const_() - rhs.const_());
-        for i in 1..=n {
-            expr.set_coeff(i, lhs.coeff_unchecked(i) - rhs.coeff_unchecked(i))
-                .unwrap();
+    pub fn mk_lt(lhs: LinExpr) -> Self {
+        Self {
+            lhs,
+            constraint: Constraint::Lt,
+        }
+    }
+
+    pub fn mk_ne(lhs: LinExpr) -> Self {
+        Self {
+            lhs,
+            constraint: Constraint::Ne,
         }
-        Self::mk_le(expr)
+    }
+
+    /// Create a normalized expr <= 0 relation from a non-normalized one: lhs <= rhs
+    ///
+    /// Both sides must range over variables of `space`, so that their indices refer to the same
+    /// variables.
+    ///
+    /// ```
+    /// # use presburger::lin_expr::*;
+    /// # use presburger::lin_rel::*;
+    /// # use presburger::space::*;
+    /// # fn main () {
+    /// let space = Space::from_names(["x", "y"]).unwrap();
+    /// // x <= y
+    /// let (x, y) = (LinExpr::var(1, 2), LinExpr::var(2, 2));
+    /// let le = LinRel::le_from_lhs_rhs(&x, &y, &space).unwrap();
+    /// assert_eq!(le, LinRel::mk_le(LinExpr::new(vec![0, 1, -1]).unwrap()));
+    /// // x_3 is not a variable of the space
+    /// assert!(LinRel::le_from_lhs_rhs(&x, &LinExpr::var(3, 3), &space).is_err());
+    /// # }
+    /// ```
+    pub fn le_from_lhs_rhs(
+        lhs: &LinExpr,
+        rhs: &LinExpr,
+        space: &Space,
+    ) -> Result<Self, SpaceError> {
+        space.check_expr(lhs)?;
+        space.check_expr(rhs)?;
+        Ok(Self::mk_le(lhs - rhs))
+    }
+
+    /// Create a normalized expr < 0 relation from a non-normalized one: lhs < rhs. Both sides
+    /// must range over variables of `space`.
+    pub fn lt_from_lhs_rhs(
+        lhs: &LinExpr,
+        rhs: &LinExpr,
+        space: &Space,
+    ) -> Result<Self, SpaceError> {
+        space.check_expr(lhs)?;
+        space.check_expr(rhs)?;
+        Ok(Self::mk_lt(lhs - rhs))
+    }
+
+    /// Pad the relation with zero coefficients so that it ranges over at least `nvars` variables
+    pub fn extend_to(mut self, nvars: usize) -> Self {
+        self.lhs.extend_to(nvars);
+        self
     }
 
     pub fn nvars(&self) -> usize {
         self.lhs.nvars()
     }
 
-    pub fn coeffs(&self) -> &[Rational] {
+    /// Display the relation using the variable names of `space`
+    pub fn to_string_in(&self, space: &Space) -> String {
+        format!("{} {} 0", self.lhs.to_string_in(space), self.constraint)
+    }
+
+    pub fn coeffs(&self) -> Coeffs<'_> {
         self.lhs.coeffs()
     }
 
@@ -81,10 +143,23 @@ impl LinRel {
         &self.lhs
     }
 
+    pub fn constraint(&self) -> &Constraint {
+        &self.constraint
+    }
+
     pub fn is_equality(&self) -> bool {
         matches!(self.constraint, Constraint::Eq)
     }
 
+    pub fn is_disequality(&self) -> bool {
+        matches!(self.constraint, Constraint::Ne)
+    }
+
+    /// Is the relation a strict inequality?
+    pub fn is_strict(&self) -> bool {
+        matches!(self.constraint, Constraint::Lt)
+    }
+
     /// An equality is a possible substitution iff. some coeff is non-zero.
     /// Return the position of the first substitution coefficient, or None.
     ///
@@ -115,11 +190,12 @@ impl LinRel {
         if self.constraint != Constraint::Eq {
             return None;
         }
-        self.lhs
-            .coeffs()
-            .iter()
-            .position(|c| !c.is_zero())
-            .map(|i| i + 1)
+        self.first_var()
+    }
+
+    /// The index of the first variable with a non-zero coefficient, if any
+    fn first_var(&self) -> Option<usize> {
+        self.lhs.terms().next().map(|(i, _)| i)
     }
 
     /// An equality is a possible substitution for x_i iff. coeff(x_i) != 0
@@ -189,86 +265,234 @@ impl LinRel {
             // only equalities can be substituted
             return Err(LinExprError::AssertionError);
         }
-        let n = self.nvars();
-        debug_assert!(n == self.lhs.nvars());
-        debug_assert!(n == other.lhs.nvars());
-        let m = -Rational::ONE / other.lhs.coeff(i)?.clone();
-        // Safe b/c nvars other == nvars self and we know other variable i is valid
-        let se_coeff = self.lhs.coeff_unchecked(i);
-
-        let mut new_lhs = LinExpr::new_zeros(n);
-        for j in 1..=n {
-            new_lhs.set_coeff_unchecked(
-                j,
-                self.lhs.coeff_unchecked(j) + m.clone() * other.lhs.coeff_unchecked(j) * se_coeff,
-            );
-        }
-        new_lhs.set_const(self.lhs.const_() + m * other.lhs.const_() * se_coeff);
+        let m = -Rational::ONE / other.lhs.coeff(i)?;
+        // x_i may be beyond the variables of `self`, in which case its coefficient is zero
+        let se_coeff = match self.lhs.coeff(i) {
+            Ok(c) => c.clone(),
+            Err(_) => Rational::ZERO,
+        };
         Ok(Self {
-            lhs: new_lhs,
+            lhs: self.lhs + &other.lhs * (m * se_coeff),
             constraint: self.constraint,
         })
     }
 
-    /// Determine if an inequality has a variable that can be isolated; return the index of that
-    /// variable or None if none exists.
-    ///
-    /// TODO: lin_rel::is_isolatable_le: refactor to share code with `is_subs`
+    /// Determine if an inequality (strict or not) has a variable that can be isolated; return the
+    /// index of that variable or None if none exists.
     pub fn is_isolatable_le(&self) -> Option<usize> {
-        if self.constraint != Constraint::Le {
+        if !matches!(self.constraint, Constraint::Le | Constraint::Lt) {
             return None;
         }
-        self.lhs
-            .coeffs()
-            .iter()
-            .position(|c| !c.is_zero())
-            .map(|i| i + 1)
+        self.first_var()
+    }
+
+    /// Does the relation hold under `model`? See `LinExpr::eval()`.
+    pub fn holds(&self, model: &Model) -> Result<bool, ModelError> {
+        Ok(self.holds_at(&self.lhs.eval(model)?))
+    }
+
+    /// Does `value rel 0` hold?
+    fn holds_at(&self, value: &Rational) -> bool {
+        match self.constraint {
+            Constraint::Eq => value.is_zero(),
+            Constraint::Le => value <= &Rational::ZERO,
+            Constraint::Lt => value < &Rational::ZERO,
+            Constraint::Ne => !value.is_zero(),
+        }
+    }
+
+    /// If the relation does not hold under `model`, return the value of its left hand side, which
+    /// measures how far the relation is from holding.
+    ///
+    /// ```
+    /// # use presburger::lin_expr::*;
+    /// # use presburger::lin_rel::*;
+    /// # use presburger::model::Model;
+    /// # use presburger::types::Rational;
+    /// # fn main () {
+    /// // x_1 - 2 <= 0 is violated by 3 at x_1 = 5
+    /// let le = LinRel::mk_le(LinExpr::new(vec![-2, 1]).unwrap());
+    /// assert_eq!(le.violation(&Model::from_values(vec![5])), Ok(Some(Rational::from(3))));
+    /// assert_eq!(le.violation(&Model::from_values(vec![1])), Ok(None));
+    /// # }
+    /// ```
+    pub fn violation(&self, model: &Model) -> Result<Option<Rational>, ModelError> {
+        let value = self.lhs.eval(model)?;
+        if self.holds_at(&value) {
+            Ok(None)
+        } else {
+            Ok(Some(value))
+        }
+    }
+
+    /// Drop the variables `vars` from the relation, renumbering the remaining variables in order.
+    /// See `LinExpr::remove_vars()`.
+    pub fn remove_vars(&self, vars: &[usize]) -> Self {
+        Self {
+            lhs: self.lhs.remove_vars(vars),
+            constraint: self.constraint.clone(),
+        }
+    }
+
+    /// Scale the relation by a positive factor so that its first non-zero coefficient is `1` or
+    /// `-1`, and return the scaled non-zero coefficients `(i, a_i)` and constant. Parallel
+    /// relations with the same orientation have the same terms, whatever their number of
+    /// variables. Returns `None` for constant relations.
+    pub fn direction(&self) -> Option<(Vec<(usize, Rational)>, Rational)> {
+        let (_, lead) = self.lhs.terms().next()?;
+        let scale = Rational::ONE / lead.clone().abs();
+        let terms = self.lhs.terms().map(|(i, a)| (i, a * &scale)).collect();
+        Some((terms, self.lhs.const_() * &scale))
     }
 
     /// Determine if `self` is a trivially true (in)equality between constants,
-    /// e.g. 0 = 0, or -1 <= 0
+    /// e.g. 0 = 0, -1 <= 0, -1 < 0, or 1 != 0
     pub fn is_trivial(&self) -> bool {
-        let coeffs_zero = self.lhs.coeffs().iter().all(|c| c.is_zero());
-        coeffs_zero
-            && match self.constraint {
-                Constraint::Eq => self.const_().is_zero(),
-                Constraint::Le => self.const_() <= &Rational::ZERO,
-            }
+        self.lhs.is_const() && self.const_holds()
     }
 
     /// Determine if `self` is a trivially false (in)equality between constants,
-    /// e.g. 1 = 0, or 2 <= 0
+    /// e.g. 1 = 0, 2 <= 0, 0 < 0, or 0 != 0
     pub fn is_trivial_contradiction(&self) -> bool {
-        let coeffs_zero = self.lhs.coeffs().iter().all(|c| c.is_zero());
-        coeffs_zero
-            && match self.constraint {
-                Constraint::Eq => !self.const_().is_zero(),
-                Constraint::Le => self.const_() > &Rational::ZERO,
+        self.lhs.is_const() && !self.const_holds()
+    }
+
+    /// Does the relation hold when all variable coefficients are ignored?
+    fn const_holds(&self) -> bool {
+        self.holds_at(self.const_())
+    }
+
+    /// Normalize the relation for integer-valued variables.
+    ///
+    /// The relation is scaled to integer coefficients and divided by the gcd `g` of its variable
+    /// coefficients. The constant of an inequality is then rounded up, which tightens the relation
+    /// without losing integer solutions. A strict inequality `e < 0` is first turned into
+    /// `e + 1 <= 0`. An (dis)equality is oriented so that its first non-zero coefficient is
+    /// positive; if `g` does not divide its constant, an equality has no integer solutions and is
+    /// replaced by the contradiction `1 = 0`, and a disequality always holds and is replaced by
+    /// `1 != 0`.
+    ///
+    /// Constant relations are replaced by `0 rel 0` or `1 rel 0`, whichever has the same truth
+    /// value.
+    ///
+    /// ```
+    /// # use presburger::lin_expr::*;
+    /// # use presburger::lin_rel::*;
+    /// # fn main() -> Result<(), LinExprError> {
+    /// // 2 x_1 + 3 <= 0 ==> x_1 + 2 <= 0
+    /// let le = LinRel::mk_le(LinExpr::new(vec![3, 2])?);
+    /// assert_eq!(le.normalize_integer(), LinRel::mk_le(LinExpr::new(vec![2, 1])?));
+    ///
+    /// // 2 x_1 + 4 x_2 + 1 = 0 has no integer solutions
+    /// let eq = LinRel::mk_eq(LinExpr::new(vec![1, 2, 4])?);
+    /// assert!(eq.normalize_integer().is_trivial_contradiction());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn normalize_integer(self) -> Self {
+        let nvars = self.nvars();
+        let constraint = match self.constraint {
+            Constraint::Lt => Constraint::Le,
+            ref c => c.clone(),
+        };
+        let constant = |holds: bool| Self {
+            lhs: LinExpr::constant(
+                if holds == (constraint == Constraint::Ne) {
+                    1
+                } else {
+                    0
+                },
+                nvars,
+            ),
+            constraint: constraint.clone(),
+        };
+        if self.lhs.is_const() {
+            return constant(self.is_trivial());
+        }
+
+        let all_coeffs = || std::iter::once(self.const_()).chain(self.coeffs());
+        let denom = all_coeffs().fold(Integer::ONE, |l, c| lcm(&l, &c.denominator()));
+        let scale = Rational::from(denom);
+        let mut scaled: Vec<Integer> = all_coeffs().map(|c| (c * &scale).numerator()).collect();
+        let g = scaled[1..].iter().fold(Integer::ZERO, |g, a| gcd(&g, a));
+        if self.constraint == Constraint::Lt {
+            // over the integers, e < 0 iff e + 1 <= 0
+            scaled[0] += Integer::ONE;
+        }
+
+        let (c, sign) = match constraint {
+            Constraint::Le => (-div_floor(-scaled[0].clone(), g.clone()), Integer::ONE),
+            _ => {
+                if &scaled[0] % &g != Integer::ZERO {
+                    return constant(constraint == Constraint::Ne);
+                }
+                let first = scaled[1..].iter().find(|a| *a != &Integer::ZERO);
+                let sign = match first {
+                    Some(a) if a < &Integer::ZERO => Integer::NEG_ONE,
+                    _ => Integer::ONE,
+                };
+                (&scaled[0] / &g * &sign, sign)
             }
+        };
+        let mut coeffs = vec![Rational::from(c)];
+        coeffs.extend(scaled[1..].iter().map(|a| Rational::from(a / &g * &sign)));
+        Self {
+            lhs: LinExpr::new(coeffs).expect("the constant makes coeffs non-empty"),
+            constraint,
+        }
+    }
+
+    /// Negate the relation: return a list of relations whose disjunction holds exactly where
+    /// `self` does not. The relations in the list are pairwise disjoint.
+    ///
+    /// Over the rationals, `e <= 0` becomes `-e < 0`, `e < 0` becomes `-e <= 0`, `e = 0` becomes
+    /// `e < 0 \/ -e < 0`, and `e != 0` becomes `e = 0`. Over the integers, every resulting relation
+    /// is then normalized with `normalize_integer`, so strict inequalities are replaced, e.g.
+    /// `e <= 0` becomes `-e + 1 <= 0`.
+    ///
+    /// ```
+    /// # use presburger::lin_expr::*;
+    /// # use presburger::lin_rel::*;
+    /// # use presburger::types::Domain;
+    /// # fn main() -> Result<(), LinExprError> {
+    /// // x_1 - 2 x_2 <= 0
+    /// let le = LinRel::mk_le(LinExpr::new(vec![0, 1, -2])?);
+    /// assert_eq!(le.negate(Domain::Rational), vec![LinRel::mk_lt(LinExpr::new(vec![0, -1, 2])?)]);
+    /// assert_eq!(le.negate(Domain::Integer), vec![LinRel::mk_le(LinExpr::new(vec![1, -1, 2])?)]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn negate(&self, domain: Domain) -> Vec<LinRel> {
+        let negated = match self.constraint {
+            Constraint::Le => vec![Self::mk_lt(-&self.lhs)],
+            Constraint::Lt => vec![Self::mk_le(-&self.lhs)],
+            Constraint::Eq => vec![Self::mk_lt(self.lhs.clone()), Self::mk_lt(-&self.lhs)],
+            Constraint::Ne => vec![Self::mk_eq(self.lhs.clone())],
+        };
+        match domain {
+            Domain::Rational => negated,
+            Domain::Integer => negated.into_iter().map(|r| r.normalize_integer()).collect(),
+        }
     }
 
     /// Find the first variable that is possible to eliminate by finding the first non-zero
-    /// coefficient of `self.lhs`.
+    /// coefficient of `self.lhs`. Returns its 0-based position, i.e. `i - 1` for `x_i`.
     pub fn find_variable_to_eliminate(&self) -> Option<usize> {
-        self.lhs
-            .coeffs()
-            .iter()
-            .enumerate()
-            .find(|(_i, c)| !c.is_zero())
-            .map(|(i, _c)| i)
+        self.first_var().map(|i| i - 1)
     }
 
     /// Isolate variable `i` (> 0) and return an upper or lower bound depending on the sign of its
-    /// coefficient `a_i`.
+    /// coefficient `a_i`. The bound is strict iff `self` is a strict inequality.
     ///
-    /// If `a_i` = 0, or `i` is out of bounds, return None
+    /// If `a_i` = 0, `i` is out of bounds, or `self` is not an inequality, return None
     ///
     /// Example: `1 + x1 + 3x2 <= 0` with `i = 1` results in `x1 <= -1 + (-3)x2`, an Upper bound
     pub fn compute_bound_from(&self, i: usize) -> Option<LinExprBound> {
         debug_assert!(i > 0);
-        let mut coeffs = vec![self.lhs.const_()];
-        coeffs.extend(self.lhs.coeffs());
-        let ai = *coeffs.get(i)?;
+        if !matches!(self.constraint, Constraint::Le | Constraint::Lt) {
+            return None;
+        }
+        let ai = self.lhs.coeff(i).ok()?;
         if ai.is_zero() {
             return None;
         }
@@ -277,11 +501,14 @@ impl LinRel {
         } else {
             Bound::Lower
         };
-        let mut new_coeffs: Vec<Rational> = coeffs.iter().map(|&c| -c.clone() / ai).collect();
-        new_coeffs[i] = Rational::ZERO;
-        let expr = LinExpr::new(new_coeffs)
-            .expect("unreachable because the constant makes coeffs/new_coeffs non-empty");
-        Some(LinExprBound { i, bound, expr })
+        let mut expr = &self.lhs * (-Rational::ONE / ai);
+        expr.set_coeff_unchecked(i, Rational::ZERO);
+        Some(LinExprBound {
+            i,
+            bound,
+            strict: self.is_strict(),
+            expr,
+        })
     }
 }
 
@@ -290,6 +517,20 @@ mod tests {
     use super::*;
     use crate::{eq, le};
 
+    #[test]
+    fn lin_rel_from_lhs_rhs() {
+        let space = Space::anonymous(2);
+        let x1 = LinExpr::var(1, 1);
+        let x2 = LinExpr::var(2, 2) + LinExpr::constant(1, 2);
+        // x_1 < x_2 + 1, where x_1 ranges over fewer variables than the space has
+        let lt = LinRel::lt_from_lhs_rhs(&x1, &x2, &space).unwrap();
+        assert_eq!(lt, LinRel::mk_lt(LinExpr::new(vec![-1, 1, -1]).unwrap()));
+        let x3 = LinExpr::var(3, 3);
+        let mismatch = Err(SpaceError::DimensionMismatch { nvars: 3, dim: 2 });
+        assert_eq!(LinRel::lt_from_lhs_rhs(&x1, &x3, &space), mismatch);
+        assert_eq!(LinRel::le_from_lhs_rhs(&x3, &x1, &space), mismatch);
+    }
+
     #[test]
     fn lin_eq_basic_api() {
         let eq1 = eq!(0, 1, 2, 0);
@@ -416,6 +657,125 @@ mod tests {
         assert!(!trivial_le_zero.is_trivial_contradiction());
     }
 
+    #[test]
+    fn test_normalize_integer() {
+        // 2x1 + 3 <= 0 ==> x1 + 2 <= 0
+        assert_eq!(le!(3, 2).normalize_integer(), le!(2, 1));
+        // -4x1 + 6x2 - 3 <= 0 ==> -2x1 + 3x2 - 1 <= 0
+        assert_eq!(le!(-3, -4, 6).normalize_integer(), le!(-1, -2, 3));
+        // 1/2 x1 + 1/3 x2 + 1/6 <= 0 ==> 3x1 + 2x2 + 1 <= 0
+        let r = LinRel::mk_le(
+            LinExpr::new(vec![
+                Rational::ONE / Rational::from(6),
+                Rational::ONE / Rational::from(2),
+                Rational::ONE / Rational::from(3),
+            ])
+            .unwrap(),
+        );
+        assert_eq!(r.normalize_integer(), le!(1, 3, 2));
+        // -6x1 - 4x2 + 2 = 0 ==> 3x1 + 2x2 - 1 = 0
+        assert_eq!(eq!(2, -6, -4).normalize_integer(), eq!(-1, 3, 2));
+        // 2x1 + 4x2 + 1 = 0 has no integer solutions
+        assert_eq!(eq!(1, 2, 4).normalize_integer(), eq!(1, 0, 0));
+        // constant relations
+        assert_eq!(le!(-5, 0).normalize_integer(), le!(0, 0));
+        assert_eq!(le!(5, 0).normalize_integer(), le!(1, 0));
+        assert_eq!(eq!(0, 0).normalize_integer(), eq!(0, 0));
+        assert_eq!(eq!(-3, 0).normalize_integer(), eq!(1, 0));
+        // 2x1 + 1 < 0 ==> 2x1 + 2 <= 0 ==> x1 + 1 <= 0
+        let lt = LinRel::mk_lt(LinExpr::new(vec![1, 2]).unwrap());
+        assert_eq!(lt.normalize_integer(), le!(1, 1));
+        // -2x1 + 4x2 + 1 != 0 always holds over the integers
+        let ne = LinRel::mk_ne(LinExpr::new(vec![1, -2, 4]).unwrap());
+        assert!(ne.normalize_integer().is_trivial());
+        // -2x1 + 4x2 + 2 != 0 ==> x1 - 2x2 - 1 != 0
+        let ne = LinRel::mk_ne(LinExpr::new(vec![2, -2, 4]).unwrap());
+        assert_eq!(
+            ne.normalize_integer(),
+            LinRel::mk_ne(LinExpr::new(vec![-1, 1, -2]).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_strict_and_disequality() {
+        let lt = LinRel::mk_lt(LinExpr::new(vec![0, 1, 2]).unwrap());
+        assert!(lt.is_strict());
+        assert_eq!(lt.is_isolatable_le(), Some(1));
+        assert_eq!(lt.to_string(), "1 x_1 + 2 x_2 < 0");
+        let bound = lt.compute_bound_from(2).unwrap();
+        assert!(bound.strict);
+        assert!(matches!(bound.bound, Bound::Upper));
+
+        let ne = LinRel::mk_ne(LinExpr::new(vec![0, 1, 2]).unwrap());
+        assert!(ne.is_disequality());
+        assert_eq!(ne.is_isolatable_le(), None);
+        assert!(ne.compute_bound_from(1).is_none());
+        assert_eq!(ne.to_string(), "1 x_1 + 2 x_2 != 0");
+
+        // constant relations
+        let lt = |c: i32| LinRel::mk_lt(LinExpr::new(vec![c, 0]).unwrap());
+        let ne = |c: i32| LinRel::mk_ne(LinExpr::new(vec![c, 0]).unwrap());
+        assert!(lt(-1).is_trivial());
+        assert!(lt(0).is_trivial_contradiction());
+        assert!(ne(1).is_trivial());
+        assert!(ne(0).is_trivial_contradiction());
+    }
+
+    #[test]
+    fn test_direction() {
+        // 2 - 2 x_2 + 4 x_3 <= 0 and 1 - x_2 + 2 x_3 <= 0 over four variables are parallel
+        let r1 = le!(2, 0, -2, 4);
+        let r2 = le!(1, 0, -1, 2, 0);
+        let expected = vec![(2, Rational::from(-1)), (3, Rational::from(2))];
+        assert_eq!(r1.direction(), Some((expected.clone(), Rational::ONE)));
+        assert_eq!(r2.direction(), Some((expected, Rational::ONE)));
+        // the opposite orientation is a different direction
+        let opposite = LinRel::mk_le(-r1.lhs()).direction().unwrap().0;
+        assert_ne!(opposite, r1.direction().unwrap().0);
+        assert_eq!(le!(1, 0, 0).direction(), None);
+    }
+
+    #[test]
+    fn test_negate() {
+        let lt = |v: Vec<i32>| LinRel::mk_lt(LinExpr::new(v).unwrap());
+        let ne = |v: Vec<i32>| LinRel::mk_ne(LinExpr::new(v).unwrap());
+
+        // rationals
+        assert_eq!(
+            le!(1, 2, -1).negate(Domain::Rational),
+            vec![lt(vec![-1, -2, 1])]
+        );
+        assert_eq!(
+            lt(vec![1, 2, -1]).negate(Domain::Rational),
+            vec![le!(-1, -2, 1)]
+        );
+        assert_eq!(
+            eq!(1, 2, -1).negate(Domain::Rational),
+            vec![lt(vec![1, 2, -1]), lt(vec![-1, -2, 1])]
+        );
+        assert_eq!(
+            ne(vec![1, 2, -1]).negate(Domain::Rational),
+            vec![eq!(1, 2, -1)]
+        );
+
+        // integers
+        assert_eq!(le!(1, 2, -1).negate(Domain::Integer), vec![le!(0, -2, 1)]);
+        assert_eq!(
+            lt(vec![1, 2, -1]).negate(Domain::Integer),
+            vec![le!(-1, -2, 1)]
+        );
+        assert_eq!(
+            eq!(1, 2, -1).negate(Domain::Integer),
+            vec![le!(2, 2, -1), le!(0, -2, 1)]
+        );
+        // 2x1 != 1 always holds over the integers, so its negation never does
+        assert!(ne(vec![-1, 2]).negate(Domain::Integer)[0].is_trivial_contradiction());
+
+        // constant relations
+        assert!(le!(-1, 0).negate(Domain::Rational)[0].is_trivial_contradiction());
+        assert!(le!(0, 0).negate(Domain::Integer)[0].is_trivial_contradiction());
+    }
+
     #[test]
     fn test_compute_bound_from() {
         // Test case 1: Upper bound from positive coefficient
@@ -1,13 +1,65 @@
 //! Implementation of linear systems: a collection of linear relations
 
+use crate::certificate::Combination;
+use crate::echelon::Echelon;
+use crate::lin_expr::Bound;
 use crate::lin_rel::LinRel;
-
-/// A system of linear relations
-#[derive(Debug, PartialEq, Eq)]
+use crate::model::{Model, ModelError, Violation};
+use crate::space::{Space, SpaceError};
+use crate::types::{Domain, Rational};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+
+/// A system of linear relations over a space of variables
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LinSys {
+    space: Space,
     relations: Vec<LinRel>,
 }
 
+/// Display one relation per line using the variable names of the system's space
+impl fmt::Display for LinSys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self
+            .relations
+            .iter()
+            .map(|r| r.to_string_in(&self.space))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Errors of `LinSys::project_out()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectError {
+    /// A variable index is 0 or greater than the dimension of the space
+    InvalidVar(usize),
+    /// A variable name is not part of the space
+    UnknownName(String),
+    /// A disequality supports a variable to project out that no equality supports. The projection
+    /// of such a system is not a conjunction of linear relations in general.
+    Disequality(usize),
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidVar(i) => {
+                write!(f, "Variable index {i} is not in the space")
+            }
+            Self::UnknownName(name) => {
+                write!(f, "Unknown variable name {name}")
+            }
+            Self::Disequality(i) => {
+                write!(f, "Cannot project out x_{i} from a disequality")
+            }
+        }
+    }
+}
+
+impl Error for ProjectError {}
+
 impl Default for LinSys {
     fn default() -> Self {
         Self::new()
@@ -15,16 +67,43 @@ impl Default for LinSys {
 }
 
 impl LinSys {
-    /// Create a new empty linear system
+    /// Create a new empty linear system over an empty space
     pub fn new() -> Self {
+        Self::with_space(Space::new())
+    }
+
+    /// Create a new empty linear system over the given space
+    pub fn with_space(space: Space) -> Self {
         Self {
+            space,
             relations: Vec::new(),
         }
     }
 
-    /// Create a linear system from a vector of linear relations
+    /// Create a linear system from a vector of linear relations, over an anonymous space large
+    /// enough for all of them
     pub fn from_relations(relations: Vec<LinRel>) -> Self {
-        Self { relations }
+        let nvars = relations.iter().map(|r| r.nvars()).max().unwrap_or(0);
+        Self {
+            space: Space::anonymous(nvars),
+            relations,
+        }
+    }
+
+    /// Create a linear system from a vector of linear relations over the given space.
+    ///
+    /// Returns an error if some relation does not belong to the space.
+    pub fn from_relations_in(space: Space, relations: Vec<LinRel>) -> Result<Self, SpaceError> {
+        let mut sys = Self::with_space(space);
+        for r in relations {
+            sys.try_add_relation(r)?;
+        }
+        Ok(sys)
+    }
+
+    /// Get the space of variables of the system
+    pub fn space(&self) -> &Space {
+        &self.space
     }
 
     /// Get the number of relations in the system
@@ -47,11 +126,44 @@ impl LinSys {
         &self.relations
     }
 
-    /// Add a linear relation to the system
+    /// Add a linear relation to the system.
+    ///
+    /// If the relation ranges over more variables than the space has, the space is grown with
+    /// default-named variables. Use `try_add_relation` to reject such relations instead.
     pub fn add_relation(&mut self, relation: LinRel) {
+        self.space.extend_to(relation.nvars());
         self.relations.push(relation);
     }
 
+    /// Add a linear relation to the system, checking that it belongs to the system's space
+    ///
+    /// ```
+    /// # use presburger::lin_expr::*;
+    /// # use presburger::lin_rel::*;
+    /// # use presburger::lin_sys::*;
+    /// # use presburger::space::*;
+    /// # fn main () {
+    /// let mut sys = LinSys::with_space(Space::from_names(["x", "y"]).unwrap());
+    /// // x + y <= 0
+    /// assert!(sys.try_add_relation(LinRel::mk_le(LinExpr::new(vec![0, 1, 1]).unwrap())).is_ok());
+    /// // x + y + x_3 <= 0 refers to a variable outside the space
+    /// assert!(sys.try_add_relation(LinRel::mk_le(LinExpr::new(vec![0, 1, 1, 1]).unwrap())).is_err());
+    /// assert_eq!(sys.to_string(), "1 x + 1 y <= 0");
+    /// # }
+    /// ```
+    pub fn try_add_relation(&mut self, relation: LinRel) -> Result<(), SpaceError> {
+        self.space.check_expr(relation.lhs())?;
+        self.relations.push(relation);
+        Ok(())
+    }
+
+    /// Add all relations of `other` to `self`. Both systems must be over the same space.
+    pub fn conjoin(&mut self, other: &LinSys) -> Result<(), SpaceError> {
+        self.space.check_same(&other.space)?;
+        self.relations.extend(other.relations.iter().cloned());
+        Ok(())
+    }
+
     /// Remove a linear relation from the system by index
     pub fn remove_relation(&mut self, index: usize) -> Option<LinRel> {
         if index < self.relations.len() {
@@ -61,45 +173,279 @@ impl LinSys {
         }
     }
 
-    /// Clear all relations from the system
+    /// Keep only the relations for which `f` returns `true`, preserving their order
+    pub fn retain(&mut self, f: impl FnMut(&LinRel) -> bool) {
+        self.relations.retain(f);
+    }
+
+    /// Keep only the first `len` relations of the system, keeping its space
+    pub fn truncate(&mut self, len: usize) {
+        self.relations.truncate(len);
+    }
+
+    /// Clear all relations from the system, keeping its space
     pub fn clear(&mut self) {
         self.relations.clear();
     }
 
+    /// Do all relations of the system hold under `model`? See `LinRel::holds()`.
+    pub fn satisfied_by(&self, model: &Model) -> Result<bool, ModelError> {
+        for r in self.relations.iter() {
+            if !r.holds(model)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// List the relations of the system that do not hold under `model`, see
+    /// `LinRel::violation()`
+    pub fn violations(&self, model: &Model) -> Result<Vec<Violation>, ModelError> {
+        let mut violations = Vec::new();
+        for (index, r) in self.relations.iter().enumerate() {
+            if let Some(value) = r.violation(model)? {
+                violations.push(Violation { index, value });
+            }
+        }
+        Ok(violations)
+    }
+
+    /// Complement of the system: a list of pairwise disjoint systems over the same space whose
+    /// union holds exactly where `self` does not.
+    ///
+    /// For relations `r_1, ..., r_n`, the complement is the union over `i` of
+    /// `r_1 /\ ... /\ r_{i-1} /\ p` for every `p` in the negation of `r_i` (see `LinRel::negate`).
+    /// The complement of an empty system is empty.
+    pub fn complement(&self, domain: Domain) -> Vec<LinSys> {
+        let mut systems = Vec::new();
+        for (i, r) in self.relations.iter().enumerate() {
+            for p in r.negate(domain) {
+                let mut sys = Self::with_space(self.space.clone());
+                sys.relations.extend(self.relations[..i].iter().cloned());
+                sys.relations.push(p);
+                systems.push(sys);
+            }
+        }
+        systems
+    }
+
+    /// Project the system onto the variables not in `vars`: return a system over the remaining
+    /// variables, renumbered in order, that is equivalent over the rationals to `exists vars. self`.
+    ///
+    /// Each variable is eliminated by substituting an equality that supports it, if there is one,
+    /// and by Fourier-Motzkin elimination otherwise, in the order given. Trivially true relations
+    /// and inequalities implied by a parallel one are removed after each step. If `self` is
+    /// unsatisfiable, the result may be a trivial contradiction.
+    ///
+    /// Returns an error if an index of `vars` is not in the space, or if a disequality supports a
+    /// variable of `vars` that no equality supports when the variable is eliminated.
+    ///
+    /// ```
+    /// # use presburger::lin_expr::*;
+    /// # use presburger::lin_rel::*;
+    /// # use presburger::lin_sys::*;
+    /// # use presburger::space::*;
+    /// # fn main () {
+    /// // exists y. x <= y /\ y <= z  <==>  x <= z
+    /// let space = Space::from_names(["x", "y", "z"]).unwrap();
+    /// let sys = LinSys::from_relations_in(space, vec![
+    ///     LinRel::mk_le(LinExpr::new(vec![0, 1, -1, 0]).unwrap()),
+    ///     LinRel::mk_le(LinExpr::new(vec![0, 0, 1, -1]).unwrap()),
+    /// ]).unwrap();
+    /// let projected = sys.project_out(&[2]).unwrap();
+    /// assert_eq!(projected.space().names(), &["x", "z"]);
+    /// assert_eq!(projected.to_string(), "1 x + (-1) z <= 0");
+    /// assert_eq!(sys.project_out(&[4]), Err(ProjectError::InvalidVar(4)));
+    /// # }
+    /// ```
+    pub fn project_out(&self, vars: &[usize]) -> Result<LinSys, ProjectError> {
+        if let Some(&i) = vars.iter().find(|&&i| i == 0 || i > self.space.dim()) {
+            return Err(ProjectError::InvalidVar(i));
+        }
+        let mut sys = self.clone();
+        for &i in vars {
+            sys.eliminate_var(i)?;
+        }
+        let mut vars = vars.to_vec();
+        vars.sort_unstable();
+        vars.dedup();
+        Ok(Self {
+            space: self.space.remove_vars(&vars),
+            relations: sys.relations.iter().map(|r| r.remove_vars(&vars)).collect(),
+        })
+    }
+
+    /// Like `project_out()`, with the variables to remove given by name
+    pub fn project_out_names<S: AsRef<str>>(&self, names: &[S]) -> Result<LinSys, ProjectError> {
+        let vars = names
+            .iter()
+            .map(|name| {
+                let name = name.as_ref();
+                self.space
+                    .index_of(name)
+                    .ok_or_else(|| ProjectError::UnknownName(name.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.project_out(&vars)
+    }
+
+    /// Replace the relations supporting `x_i` by relations without it, see `project_out()`
+    fn eliminate_var(&mut self, i: usize) -> Result<(), ProjectError> {
+        if let Some(k) = self.relations.iter().position(|r| r.is_subs_for(i)) {
+            let eq = self.relations.remove(k);
+            for r in self.relations.iter_mut() {
+                if r.lhs().supported(i) {
+                    *r = r
+                        .clone()
+                        .subs(i, &eq)
+                        .expect("eq is a substitution for x_i");
+                }
+            }
+        } else {
+            if self
+                .relations
+                .iter()
+                .any(|r| r.is_disequality() && r.lhs().supported(i))
+            {
+                return Err(ProjectError::Disequality(i));
+            }
+            let mut lower = Vec::new();
+            let mut upper = Vec::new();
+            let mut rest = Vec::new();
+            for r in std::mem::take(&mut self.relations) {
+                match r.compute_bound_from(i) {
+                    None => rest.push(r),
+                    Some(b) if b.bound == Bound::Lower => lower.push(b),
+                    Some(b) => upper.push(b),
+                }
+            }
+            for l in lower.iter() {
+                for u in upper.iter() {
+                    let rel = if l.strict || u.strict {
+                        LinRel::lt_from_lhs_rhs(&l.expr, &u.expr, &self.space)
+                    } else {
+                        LinRel::le_from_lhs_rhs(&l.expr, &u.expr, &self.space)
+                    };
+                    rest.push(rel.expect("the relations of a system belong to its space"));
+                }
+            }
+            self.relations = rest;
+        }
+        self.eliminate_trivial_relations();
+        self.remove_parallel_inequalities();
+        Ok(())
+    }
+
+    /// Remove the inequalities implied by a parallel one, see `LinRel::direction()`. Scaled to the
+    /// same direction, `e + c <= 0` implies `e + c' <= 0` and `e + c' < 0` for `c' < c`, and
+    /// `e + c < 0` implies `e + c <= 0`.
+    fn remove_parallel_inequalities(&mut self) {
+        // the index of the tightest inequality in each direction, with its constant and strictness
+        let mut tightest: HashMap<Vec<(usize, Rational)>, (usize, Rational, bool)> = HashMap::new();
+        for (k, r) in self.relations.iter().enumerate() {
+            if r.is_equality() || r.is_disequality() {
+                continue;
+            }
+            let Some((terms, c)) = r.direction() else {
+                continue;
+            };
+            let strict = r.is_strict();
+            let entry = tightest.entry(terms).or_insert((k, c.clone(), strict));
+            if (&c, strict) > (&entry.1, entry.2) {
+                *entry = (k, c, strict);
+            }
+        }
+        let keep: HashSet<usize> = tightest.values().map(|(k, _, _)| *k).collect();
+        let mut k = 0;
+        self.relations.retain(|r| {
+            let kept =
+                r.is_equality() || r.is_disequality() || r.lhs().is_const() || keep.contains(&k);
+            k += 1;
+            kept
+        });
+    }
+
+    /// Solve the equalities of the system by Gaussian elimination, see [`Echelon`]. The
+    /// equalities are removed, and their solutions are substituted in the other relations, which
+    /// then only support the free variables. The returned `Echelon` keeps the solved equalities,
+    /// e.g. to extend a model of the remaining relations with `Echelon::extend_model()`.
+    ///
+    /// If the equalities are inconsistent, the system is unchanged and a combination of its
+    /// relations that is a contradiction is returned.
+    ///
+    /// ```
+    /// # use presburger::lin_expr::*;
+    /// # use presburger::lin_rel::*;
+    /// # use presburger::lin_sys::*;
+    /// # use presburger::model::Model;
+    /// # fn main () {
+    /// // x_1 = 2 x_2, x_1 + x_2 <= 3
+    /// let mut sys = LinSys::from_relations(vec![
+    ///     LinRel::mk_eq(LinExpr::new(vec![0, 1, -2]).unwrap()),
+    ///     LinRel::mk_le(LinExpr::new(vec![-3, 1, 1]).unwrap()),
+    /// ]);
+    /// let echelon = sys.solve_eqs().unwrap();
+    /// assert_eq!(echelon.rank(), 1);
+    /// // 3 x_2 <= 3
+    /// assert_eq!(sys.relations(), &[LinRel::mk_le(LinExpr::new(vec![-3, 0, 3]).unwrap())]);
+    /// let mut model = Model::from_values(vec![0, 1]);
+    /// echelon.extend_model(&mut model).unwrap();
+    /// assert_eq!(model, Model::from_values(vec![2, 1]));
+    /// # }
+    /// ```
+    pub fn solve_eqs(&mut self) -> Result<Echelon, Combination> {
+        let echelon = Echelon::new(&self.relations)?;
+        self.relations = std::mem::take(&mut self.relations)
+            .into_iter()
+            .filter(|r| !r.is_equality())
+            .map(|r| echelon.reduce(r))
+            .collect();
+        Ok(echelon)
+    }
+
     /// Reduce, if possible, the linear system by substituting some eligible equality in the system
-    /// into every relation.
+    /// into every relation. To eliminate all equalities at once, use `solve_eqs()`.
     ///
     /// Returns `true` if an equality was eliminated and `false` if there are no further reductions
     /// possible.
     pub fn reduce_eqs(&mut self) -> bool {
+        self.reduce_eq().is_some()
+    }
+
+    /// Like `reduce_eqs()`, but return the index of the eliminated variable and the equality that
+    /// was substituted for it, which is removed from the system.
+    pub fn reduce_eq(&mut self) -> Option<(usize, LinRel)> {
         // Find the first equality that can be used for substitution
-        let sub_index = self
+        let index = self
             .relations
             .iter()
-            .position(|rel| rel.is_subs().is_some());
+            .position(|rel| rel.is_subs().is_some())?;
 
-        if let Some(index) = sub_index {
-            // Get the substitution equality
-            let subs_eq = self.relations.remove(index);
-            let sub_var = subs_eq.is_subs().unwrap();
+        // Get the substitution equality
+        let subs_eq = self.relations.remove(index);
+        let sub_var = subs_eq.is_subs().unwrap();
 
-            // Substitute this equality into all remaining relations
-            for i in 0..self.relations.len() {
-                if let Ok(substituted) = self.relations[i].clone().subs(sub_var, &subs_eq) {
-                    self.relations[i] = substituted;
-                }
+        // Substitute this equality into all remaining relations
+        for i in 0..self.relations.len() {
+            if let Ok(substituted) = self.relations[i].clone().subs(sub_var, &subs_eq) {
+                self.relations[i] = substituted;
             }
-
-            true
-        } else {
-            false
         }
+
+        Some((sub_var, subs_eq))
     }
 
-    /// Call `reduce_eqs()` repeatedly until no more equality reductions are possible.
-    /// If equalities still remain they are guaranteed to be equalities between constants.
+    /// Eliminate the equalities with `solve_eqs()`, discarding their solutions. If the equalities
+    /// are inconsistent, they are replaced by a single equality between constants that
+    /// contradicts them.
     pub fn eliminate_nontrivial_eqs(&mut self) {
-        while self.reduce_eqs() {}
+        if let Err(combination) = self.solve_eqs() {
+            let contradiction = combination
+                .combine(&self.relations)
+                .expect("combination of equalities of the system");
+            self.relations.retain(|r| !r.is_equality());
+            self.relations.push(contradiction);
+        }
     }
 
     /// Filter out trivial constant (in)equalities from the system. See `LinRel::is_trivial().`
@@ -117,6 +463,17 @@ impl LinSys {
         }
     }
 
+    /// Normalize every relation for integer-valued variables. See `LinRel::normalize_integer()`.
+    ///
+    /// Equalities without integer solutions become trivial contradictions, which are then
+    /// detected by `has_trivial_contradiction()`.
+    pub fn normalize_integer(&mut self) {
+        self.relations = std::mem::take(&mut self.relations)
+            .into_iter()
+            .map(|r| r.normalize_integer())
+            .collect();
+    }
+
     /// Determine if `self` contains a trivial contradiction between constants, e.g. 1 = 0, or
     /// 2 <= 0.
     pub fn has_trivial_contradiction(&self) -> bool {
@@ -139,8 +496,12 @@ impl LinSys {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fme::{FMESolver, FMEState};
     use crate::lin_expr::LinExpr;
     use crate::lin_rel::LinRel;
+    use crate::space::{Space, SpaceError};
+    use crate::types::Rational;
+    use proptest::prelude::*;
 
     #[test]
     fn test_new_empty_system() {
@@ -176,6 +537,33 @@ mod tests {
         assert_eq!(system.relations(), &[eq2]);
     }
 
+    #[test]
+    fn test_space_of_system() {
+        let eq1 = LinRel::mk_eq(LinExpr::new(vec![0, 1, 2]).unwrap());
+        let eq2 = LinRel::mk_le(LinExpr::new(vec![0, 3, 4, 5]).unwrap());
+
+        // anonymous spaces grow with the relations
+        let mut system = LinSys::from_relations(vec![eq1.clone()]);
+        assert_eq!(system.space(), &Space::anonymous(2));
+        system.add_relation(eq2.clone());
+        assert_eq!(system.space(), &Space::anonymous(3));
+
+        // named spaces reject relations over too many variables
+        let mut named = LinSys::with_space(Space::from_names(["x", "y"]).unwrap());
+        assert!(named.try_add_relation(eq1.clone()).is_ok());
+        assert_eq!(
+            named.try_add_relation(eq2.clone()),
+            Err(SpaceError::DimensionMismatch { nvars: 3, dim: 2 })
+        );
+        assert!(LinSys::from_relations_in(named.space().clone(), vec![eq1, eq2]).is_err());
+
+        // systems can only be conjoined within the same space
+        assert_eq!(named.conjoin(&system), Err(SpaceError::SpaceMismatch));
+        let other = LinSys::from_relations_in(named.space().clone(), vec![]).unwrap();
+        assert!(named.conjoin(&other).is_ok());
+        assert_eq!(named.to_string(), "1 x + 2 y = 0");
+    }
+
     #[test]
     fn test_clear() {
         let mut system = LinSys::new();
@@ -277,6 +665,254 @@ mod tests {
             remaining,
             LinRel::mk_le(LinExpr::new(vec![-2, 0, 0, -9]).unwrap())
         );
+
+        // x1 + x2 = 1 and x1 + x2 = 2 are inconsistent
+        let mut system = LinSys::from_relations(vec![
+            LinRel::mk_eq(LinExpr::new(vec![-1, 1, 1]).unwrap()),
+            LinRel::mk_le(LinExpr::new(vec![0, 1, 0]).unwrap()),
+            LinRel::mk_eq(LinExpr::new(vec![-2, 1, 1]).unwrap()),
+        ]);
+        system.eliminate_nontrivial_eqs();
+        assert_eq!(system.len(), 2);
+        assert_eq!(
+            system.relations()[0],
+            LinRel::mk_le(LinExpr::new(vec![0, 1, 0]).unwrap())
+        );
+        assert!(system.relations()[1].is_trivial_contradiction());
+    }
+
+    #[test]
+    fn test_satisfied_by() {
+        // x1 + x2 <= 2, x1 = 1, x2 != 0
+        let system = LinSys::from_relations(vec![
+            LinRel::mk_le(LinExpr::new(vec![-2, 1, 1]).unwrap()),
+            LinRel::mk_eq(LinExpr::new(vec![-1, 1, 0]).unwrap()),
+            LinRel::mk_ne(LinExpr::new(vec![0, 0, 1]).unwrap()),
+        ]);
+        assert_eq!(
+            system.satisfied_by(&Model::from_values(vec![1, 1])),
+            Ok(true)
+        );
+        assert_eq!(
+            system.violations(&Model::from_values(vec![1, 1])),
+            Ok(vec![])
+        );
+
+        let model = Model::from_values(vec![3, 0]);
+        assert_eq!(system.satisfied_by(&model), Ok(false));
+        assert_eq!(
+            system.violations(&model),
+            Ok(vec![
+                Violation {
+                    index: 0,
+                    value: Rational::ONE
+                },
+                Violation {
+                    index: 1,
+                    value: Rational::from(2)
+                },
+                Violation {
+                    index: 2,
+                    value: Rational::ZERO
+                },
+            ])
+        );
+        // x2 has a non-zero coefficient but is not assigned
+        assert_eq!(
+            system.satisfied_by(&Model::from_values(vec![1])),
+            Err(ModelError::Unassigned(2))
+        );
+        assert_eq!(
+            system.violations(&Model::from_values(vec![1])),
+            Err(ModelError::Unassigned(2))
+        );
+    }
+
+    #[test]
+    fn test_complement() {
+        // x1 <= 0 /\ x2 = 0
+        let system = LinSys::from_relations(vec![
+            LinRel::mk_le(LinExpr::new(vec![0, 1, 0]).unwrap()),
+            LinRel::mk_eq(LinExpr::new(vec![0, 0, 1]).unwrap()),
+        ]);
+        // 0 < x1 \/ (x1 <= 0 /\ x2 < 0) \/ (x1 <= 0 /\ 0 < x2)
+        let complement = system.complement(Domain::Rational);
+        assert_eq!(complement.len(), 3);
+        assert!(complement.iter().all(|s| s.space() == system.space()));
+        assert_eq!(
+            complement[0].relations(),
+            &[LinRel::mk_lt(LinExpr::new(vec![0, -1, 0]).unwrap())]
+        );
+        assert_eq!(
+            complement[2].relations(),
+            &[
+                LinRel::mk_le(LinExpr::new(vec![0, 1, 0]).unwrap()),
+                LinRel::mk_lt(LinExpr::new(vec![0, 0, -1]).unwrap()),
+            ]
+        );
+        // 1 <= x1 \/ (x1 <= 0 /\ x2 <= -1) \/ (x1 <= 0 /\ 1 <= x2)
+        let complement = system.complement(Domain::Integer);
+        assert_eq!(
+            complement[1].relations(),
+            &[
+                LinRel::mk_le(LinExpr::new(vec![0, 1, 0]).unwrap()),
+                LinRel::mk_le(LinExpr::new(vec![1, 0, 1]).unwrap()),
+            ]
+        );
+
+        assert!(LinSys::new().complement(Domain::Rational).is_empty());
+    }
+
+    #[test]
+    fn test_solve_eqs() {
+        // x1 + x2 = 1, x1 - x2 = 3, x1 + x3 <= 0, x2 != x3
+        let relations = vec![
+            LinRel::mk_eq(LinExpr::new(vec![-1, 1, 1, 0]).unwrap()),
+            LinRel::mk_eq(LinExpr::new(vec![-3, 1, -1, 0]).unwrap()),
+            LinRel::mk_le(LinExpr::new(vec![0, 1, 0, 1]).unwrap()),
+            LinRel::mk_ne(LinExpr::new(vec![0, 0, 1, -1]).unwrap()),
+        ];
+        let mut sys = LinSys::from_relations(relations.clone());
+        let echelon = sys.solve_eqs().unwrap();
+        assert_eq!(echelon.rank(), 2);
+        assert_eq!(echelon.free_vars(), vec![3]);
+        // x1 = 2, x2 = -1
+        assert_eq!(
+            sys.relations(),
+            &[
+                LinRel::mk_le(LinExpr::new(vec![2, 0, 0, 1]).unwrap()),
+                LinRel::mk_ne(LinExpr::new(vec![-1, 0, 0, -1]).unwrap()),
+            ]
+        );
+        assert_eq!(sys.space(), &Space::anonymous(3));
+
+        // adding x1 + 2 x2 = 1 is inconsistent, and leaves the system unchanged
+        let mut relations = relations;
+        relations.push(LinRel::mk_eq(LinExpr::new(vec![-1, 1, 2, 0]).unwrap()));
+        let mut sys = LinSys::from_relations(relations.clone());
+        let combination = sys.solve_eqs().unwrap_err();
+        assert_eq!(combination.verify(&relations), Ok(()));
+        assert_eq!(sys.relations(), &relations[..]);
+    }
+
+    #[test]
+    fn test_project_out() {
+        let le = |coeffs: Vec<i32>| LinRel::mk_le(LinExpr::new(coeffs).unwrap());
+        let space = Space::from_names(["x", "y", "z"]).unwrap();
+
+        // exists y. x = 2 y /\ y <= 3 /\ z != y  <==>  x <= 6 /\ z != x / 2
+        let sys = LinSys::from_relations_in(
+            space.clone(),
+            vec![
+                LinRel::mk_eq(LinExpr::new(vec![0, 1, -2, 0]).unwrap()),
+                le(vec![-3, 0, 1, 0]),
+                LinRel::mk_ne(LinExpr::new(vec![0, 0, -1, 1]).unwrap()),
+            ],
+        )
+        .unwrap();
+        let projected = sys.project_out_names(&["y"]).unwrap();
+        assert_eq!(projected.space().names(), &["x", "z"]);
+        assert_eq!(
+            projected.to_string(),
+            "-3 + 1/2 x <= 0\n(-1/2) x + 1 z != 0"
+        );
+
+        // exists x, y. x <= y /\ 1 <= x /\ y <= z /\ y < 2 z  <==>  1 <= z /\ 1 < 2 z,
+        // the second of which is implied by the first
+        let sys = LinSys::from_relations_in(
+            space.clone(),
+            vec![
+                le(vec![0, 1, -1, 0]),
+                le(vec![1, -1, 0, 0]),
+                le(vec![0, 0, 1, -1]),
+                LinRel::mk_lt(LinExpr::new(vec![0, 0, 1, -2]).unwrap()),
+            ],
+        )
+        .unwrap();
+        let projected = sys.project_out(&[1, 2]).unwrap();
+        assert_eq!(projected.space().names(), &["z"]);
+        assert_eq!(projected.to_string(), "1 + (-1) z <= 0");
+
+        // projecting out every variable decides the system
+        let projected = sys.project_out(&[1, 2, 3]).unwrap();
+        assert!(projected.is_empty());
+        let mut unsat = sys.clone();
+        unsat.add_relation(le(vec![0, 0, 0, 1]));
+        assert!(
+            unsat
+                .project_out(&[3, 2, 1])
+                .unwrap()
+                .has_trivial_contradiction()
+        );
+
+        assert_eq!(
+            sys.project_out_names(&["w"]),
+            Err(ProjectError::UnknownName("w".to_string()))
+        );
+        assert_eq!(sys.project_out(&[0]), Err(ProjectError::InvalidVar(0)));
+        assert_eq!(sys.project_out(&[1, 4]), Err(ProjectError::InvalidVar(4)));
+
+        // exists y. x <= y /\ y != 0 is true, but not a conjunction over x
+        let sys = LinSys::from_relations_in(
+            space,
+            vec![
+                le(vec![0, 1, -1, 0]),
+                LinRel::mk_ne(LinExpr::new(vec![0, 0, 1, 0]).unwrap()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            sys.project_out_names(&["y"]),
+            Err(ProjectError::Disequality(2))
+        );
+    }
+
+    fn arb_system() -> impl Strategy<Value = Vec<LinRel>> {
+        let relation = (prop::collection::vec(-3i32..=3, 4), 0..3).prop_map(|(coeffs, c)| {
+            let lhs = LinExpr::new(coeffs).unwrap();
+            match c {
+                0 => LinRel::mk_eq(lhs),
+                1 => LinRel::mk_lt(lhs),
+                _ => LinRel::mk_le(lhs),
+            }
+        });
+        prop::collection::vec(relation, 1..6)
+    }
+
+    proptest! {
+        /// A point satisfies the projection of a system iff it extends to a model of the system
+        #[test]
+        fn project_out_is_exists(relations in arb_system(), x in -3i32..=3) {
+            let sys = LinSys::from_relations(relations);
+            let projected = sys.project_out(&[2, 3]).unwrap();
+            prop_assert_eq!(projected.space().dim(), 1);
+
+            // x_1 = x has an extension iff the system is satisfiable with x_1 = x
+            let mut fixed = FMESolver::with_space(sys.space().clone());
+            fixed.assert_sys(&sys).unwrap();
+            fixed.assert(LinRel::mk_eq(LinExpr::new(vec![-x, 1]).unwrap()));
+            let extends = fixed.check() == FMEState::SAT;
+            prop_assert_eq!(projected.satisfied_by(&Model::from_values(vec![x])), Ok(extends));
+        }
+    }
+
+    #[test]
+    fn test_normalize_integer() {
+        // 2x1 + 2x2 = 1 has rational but no integer solutions
+        let mut system = LinSys::from_relations(vec![
+            LinRel::mk_le(LinExpr::new(vec![3, 2, 0]).unwrap()),
+            LinRel::mk_eq(LinExpr::new(vec![-1, 2, 2]).unwrap()),
+        ]);
+        assert!(!system.has_trivial_contradiction());
+        system.normalize_integer();
+        assert_eq!(
+            system.relations(),
+            &[
+                LinRel::mk_le(LinExpr::new(vec![2, 1, 0]).unwrap()),
+                LinRel::mk_eq(LinExpr::new(vec![1, 0, 0]).unwrap()),
+            ]
+        );
+        assert!(system.has_trivial_contradiction());
     }
 
     #[test]
@@ -0,0 +1,371 @@
+//! Lowering of the quantifier-free, conjunctive fragment of the front-end AST (see [`crate::ast`])
+//! to the linear IR (see [`crate::lin_sys`]).
+//!
+//! For example, the formula `x + 1 <= y /\ y = 2 * z` lowers to the system
+//!
+//! ```text
+//! 1 + x_1 + (-1) x_2 <= 0
+//!     x_2 + (-2) x_3  = 0
+//! ```
+//!
+//! where the variable indices are assigned in order of first occurrence, and the variable names
+//! are recorded in the [`Space`] of the system.
+
+use crate::ast::{Atom, Formula, Term, Var};
+use crate::lin_expr::LinExpr;
+use crate::lin_rel::LinRel;
+use crate::lin_sys::LinSys;
+use crate::space::Space;
+use crate::types::Rational;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LowerError {
+    /// The formula contains a quantifier binding the given variable
+    Quantified(Var),
+    /// The formula contains a connective other than `/\` or a negation of an atom, rendered here
+    NonConjunctive(String),
+    /// The formula contains a logical (propositional) variable
+    LogicalVar(Var),
+    /// The formula contains a divisibility atom, rendered here, which has no linear counterpart
+    Divides(String),
+}
+
+impl fmt::Display for LowerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Quantified(v) => {
+                write!(f, "Quantifier over {v} in quantifier-free fragment")
+            }
+            Self::NonConjunctive(p) => {
+                write!(f, "Non-conjunctive formula {p}")
+            }
+            Self::LogicalVar(v) => {
+                write!(f, "Logical variable {v} cannot be lowered")
+            }
+            Self::Divides(a) => {
+                write!(f, "Divisibility atom {a} cannot be lowered")
+            }
+        }
+    }
+}
+
+impl Error for LowerError {}
+
+/// Flatten a `Term` tree into a linear expression, interning its variables in `space`.
+///
+/// The resulting expression has exactly `space.dim()` variables, counted after interning.
+///
+/// ```
+/// # use presburger::ast::Term;
+/// # use presburger::lin_expr::LinExpr;
+/// # use presburger::lower::*;
+/// # use presburger::space::Space;
+/// # use presburger::types::Rational;
+/// # fn main () {
+/// // (2 x + 1) + (3 y + x)
+/// let t = Term::tadd(
+///     Term::tadd(Term::scalar_var(Rational::from(2), "x"), Term::num(1)),
+///     Term::tadd(Term::scalar_var(Rational::from(3), "y"), Term::scalar_var(Rational::ONE, "x")),
+/// );
+/// let mut space = Space::new();
+/// assert_eq!(lower_term(&t, &mut space), LinExpr::new(vec![1, 3, 3]).unwrap());
+/// assert_eq!(space.names(), &["x", "y"]);
+/// # }
+/// ```
+pub fn lower_term(t: &Term, space: &mut Space) -> LinExpr {
+    let mut expr = LinExpr::new_zeros(space.dim());
+    accumulate_term(t, space, &mut expr);
+    expr.extend_to(space.dim());
+    expr
+}
+
+/// Add the value of `t` into `expr`, growing `expr` when new variables are interned
+fn accumulate_term(t: &Term, space: &mut Space, expr: &mut LinExpr) {
+    match t {
+        Term::Num(c) => expr.set_const(expr.const_() + c),
+        Term::ScalarVar(a, v) => {
+            let i = space.intern(&v.0);
+            expr.extend_to(i);
+            let ai = expr.coeff_unchecked(i) + a;
+            expr.set_coeff_unchecked(i, ai);
+        }
+        Term::Add(t1, t2) => {
+            accumulate_term(t1, space, expr);
+            accumulate_term(t2, space, expr);
+        }
+    }
+}
+
+/// Lower a single atom `t1 rel t2` to the normalized relation `t1 - t2 rel 0`.
+///
+/// Truth values are lowered to constant relations, `0 <= 0` and `1 <= 0`.
+pub fn lower_atom(a: &Atom, space: &mut Space) -> Result<LinRel, LowerError> {
+    match a {
+        Atom::TruthValue(b) => {
+            let c = if *b { Rational::ZERO } else { Rational::ONE };
+            let mut expr = LinExpr::new_zeros(space.dim());
+            expr.set_const(c);
+            Ok(LinRel::mk_le(expr))
+        }
+        Atom::LogicalVar(v) => Err(LowerError::LogicalVar(v.clone())),
+        Atom::Divides(_, _) => Err(LowerError::Divides(a.to_string())),
+        Atom::Equality(t1, t2) => {
+            let lhs = lower_term(t1, space);
+            Ok(LinRel::mk_eq(lhs - lower_term(t2, space)))
+        }
+        Atom::LessEq(t1, t2) => {
+            let lhs = lower_term(t1, space);
+            Ok(LinRel::mk_le(lhs - lower_term(t2, space)))
+        }
+    }
+}
+
+/// Lower a negated atom `~(t1 rel t2)` to a single relation.
+///
+/// `~(t1 <= t2)` lowers to the strict inequality `t2 - t1 < 0` and `~(t1 = t2)` to the
+/// disequality `t1 - t2 != 0`.
+pub fn lower_negated_atom(a: &Atom, space: &mut Space) -> Result<LinRel, LowerError> {
+    match a {
+        Atom::TruthValue(b) => lower_atom(&Atom::TruthValue(!b), space),
+        Atom::LogicalVar(v) => Err(LowerError::LogicalVar(v.clone())),
+        Atom::Divides(_, _) => Err(LowerError::Divides(a.to_string())),
+        Atom::Equality(t1, t2) => {
+            let lhs = lower_term(t1, space);
+            Ok(LinRel::mk_ne(lhs - lower_term(t2, space)))
+        }
+        Atom::LessEq(t1, t2) => {
+            let lhs = lower_term(t1, space);
+            Ok(LinRel::mk_lt(lower_term(t2, space) - lhs))
+        }
+    }
+}
+
+/// Lower a conjunction of atoms and negated atoms to a linear system.
+///
+/// Variables are added to the space of the returned system in order of first occurrence and every
+/// relation in the system ranges over all of them. Quantifiers, logical variables, and connectives
+/// other than `/\` and `~` applied to an atom are reported as errors.
+///
+/// ```
+/// # use presburger::ast::{Atom, Formula, Term};
+/// # use presburger::lin_expr::LinExpr;
+/// # use presburger::lin_rel::LinRel;
+/// # use presburger::lower::*;
+/// # use presburger::types::Rational;
+/// # fn main () -> Result<(), LowerError> {
+/// // x <= 3 /\ 1 = y
+/// let p = Formula::and(
+///     Formula::atom(Atom::less_eq(Term::scalar_var(Rational::ONE, "x"), Term::num(3))),
+///     Formula::atom(Atom::equality(Term::num(1), Term::scalar_var(Rational::ONE, "y"))),
+/// );
+/// let sys = lower_conjunction(&p)?;
+/// assert_eq!(sys.space().names(), &["x", "y"]);
+/// assert_eq!(
+///     sys.relations(),
+///     &[
+///         LinRel::mk_le(LinExpr::new(vec![-3, 1, 0]).unwrap()),
+///         LinRel::mk_eq(LinExpr::new(vec![1, 0, -1]).unwrap()),
+///     ]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn lower_conjunction(p: &Formula) -> Result<LinSys, LowerError> {
+    lower_conjunction_in(p, Space::new())
+}
+
+/// Lower a conjunction of atoms to a linear system over `space`, which is extended by the
+/// variables of `p` that are not yet part of it.
+pub fn lower_conjunction_in(p: &Formula, mut space: Space) -> Result<LinSys, LowerError> {
+    let mut relations = Vec::new();
+    lower_conjuncts(p, &mut space, &mut relations)?;
+
+    // Relations lowered early may range over fewer variables than were eventually interned
+    let n = space.dim();
+    let mut sys = LinSys::with_space(space);
+    for r in relations {
+        sys.add_relation(r.extend_to(n));
+    }
+    Ok(sys)
+}
+
+fn lower_conjuncts(
+    p: &Formula,
+    space: &mut Space,
+    relations: &mut Vec<LinRel>,
+) -> Result<(), LowerError> {
+    match p {
+        Formula::And(p1, p2) => {
+            lower_conjuncts(p1, space, relations)?;
+            lower_conjuncts(p2, space, relations)
+        }
+        Formula::Atom(a) => {
+            relations.push(lower_atom(a, space)?);
+            Ok(())
+        }
+        Formula::Not(q) => match &**q {
+            Formula::Atom(a) => {
+                relations.push(lower_negated_atom(a, space)?);
+                Ok(())
+            }
+            _ => Err(LowerError::NonConjunctive(p.to_string())),
+        },
+        Formula::Exists(v, _) | Formula::Forall(v, _) => Err(LowerError::Quantified(v.clone())),
+        Formula::Or(_, _) | Formula::Impl(_, _) | Formula::Iff(_, _) => {
+            Err(LowerError::NonConjunctive(p.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fme::{FMESolver, FMEState};
+    use crate::{eq, le};
+
+    fn var(name: &str) -> Term {
+        Term::scalar_var(Rational::ONE, name)
+    }
+
+    #[test]
+    fn test_lower_term_repeated_var() {
+        // x + (-1) x + 2 + x ==> 2 + x
+        let t = Term::tadd(
+            var("x"),
+            Term::tadd(
+                Term::scalar_var(Rational::from(-1), "x"),
+                Term::tadd(Term::num(2), var("x")),
+            ),
+        );
+        let mut space = Space::new();
+        assert_eq!(
+            lower_term(&t, &mut space),
+            LinExpr::new(vec![2, 1]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_lower_atoms() {
+        let mut space = Space::new();
+        // x + 1 <= y
+        let a = Atom::less_eq(Term::tadd(var("x"), Term::num(1)), var("y"));
+        assert_eq!(lower_atom(&a, &mut space).unwrap(), le!(1, 1, -1));
+        // y = 2 z
+        let a = Atom::equality(var("y"), Term::scalar_var(Rational::from(2), "z"));
+        assert_eq!(lower_atom(&a, &mut space).unwrap(), eq!(0, 0, 1, -2));
+        // truth values
+        assert!(
+            lower_atom(&Atom::truth(true), &mut space)
+                .unwrap()
+                .is_trivial()
+        );
+        assert!(
+            lower_atom(&Atom::truth(false), &mut space)
+                .unwrap()
+                .is_trivial_contradiction()
+        );
+        // logical variables
+        assert_eq!(
+            lower_atom(&Atom::var("P"), &mut space),
+            Err(LowerError::LogicalVar(Var::new("P")))
+        );
+        // divisibility
+        assert!(matches!(
+            lower_atom(&Atom::divides(2, var("x")), &mut space),
+            Err(LowerError::Divides(_))
+        ));
+    }
+
+    #[test]
+    fn test_lower_conjunction_pads_relations() {
+        // x <= 0 /\ (y <= x /\ z = 1)
+        let p = Formula::and(
+            Formula::atom(Atom::less_eq(var("x"), Term::num(0))),
+            Formula::and(
+                Formula::atom(Atom::less_eq(var("y"), var("x"))),
+                Formula::atom(Atom::equality(var("z"), Term::num(1))),
+            ),
+        );
+        let sys = lower_conjunction(&p).unwrap();
+        assert_eq!(sys.space().names(), &["x", "y", "z"]);
+        assert!(sys.relations().iter().all(|r| r.nvars() == 3));
+        assert_eq!(
+            sys.relations(),
+            &[le!(0, 1, 0, 0), le!(0, -1, 1, 0), eq!(-1, 0, 0, 1)]
+        );
+    }
+
+    #[test]
+    fn test_lower_negated_atoms() {
+        // ~(x <= y) /\ ~(x = 1) /\ ~false
+        let p = Formula::and(
+            Formula::fnot(Formula::atom(Atom::less_eq(var("x"), var("y")))),
+            Formula::and(
+                Formula::fnot(Formula::atom(Atom::equality(var("x"), Term::num(1)))),
+                Formula::fnot(Formula::atom(Atom::truth(false))),
+            ),
+        );
+        let sys = lower_conjunction(&p).unwrap();
+        assert_eq!(
+            sys.relations(),
+            &[
+                LinRel::mk_lt(LinExpr::new(vec![0, -1, 1]).unwrap()),
+                LinRel::mk_ne(LinExpr::new(vec![-1, 1, 0]).unwrap()),
+                le!(0, 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lower_conjunction_errors() {
+        let atom = Formula::atom(Atom::less_eq(var("x"), Term::num(0)));
+        let quantified = Formula::and(atom.clone(), Formula::exists(Var::new("x"), atom.clone()));
+        assert_eq!(
+            lower_conjunction(&quantified).unwrap_err(),
+            LowerError::Quantified(Var::new("x"))
+        );
+
+        let disjunction = Formula::or(atom.clone(), atom.clone());
+        assert!(matches!(
+            lower_conjunction(&disjunction),
+            Err(LowerError::NonConjunctive(_))
+        ));
+
+        let negation = Formula::fnot(Formula::and(atom.clone(), atom));
+        assert!(matches!(
+            lower_conjunction(&negation),
+            Err(LowerError::NonConjunctive(_))
+        ));
+    }
+
+    #[test]
+    fn test_lower_and_solve() {
+        // 0 <= x /\ x + 1 <= y /\ y <= 1/2 is UNSAT
+        let p = Formula::and(
+            Formula::atom(Atom::less_eq(Term::num(0), var("x"))),
+            Formula::and(
+                Formula::atom(Atom::less_eq(Term::tadd(var("x"), Term::num(1)), var("y"))),
+                Formula::atom(Atom::less_eq(
+                    var("y"),
+                    Term::num(Rational::ONE / Rational::from(2)),
+                )),
+            ),
+        );
+        let sys = lower_conjunction(&p).unwrap();
+        let mut solver = FMESolver::with_space(sys.space().clone());
+        solver.assert_sys(&sys).unwrap();
+        assert_eq!(solver.check(), FMEState::UNSAT);
+
+        // 0 <= x /\ x + 1 <= y is SAT
+        let p = Formula::and(
+            Formula::atom(Atom::less_eq(Term::num(0), var("x"))),
+            Formula::atom(Atom::less_eq(Term::tadd(var("x"), Term::num(1)), var("y"))),
+        );
+        let sys = lower_conjunction(&p).unwrap();
+        let mut solver = FMESolver::with_space(sys.space().clone());
+        solver.assert_sys(&sys).unwrap();
+        assert_eq!(solver.check(), FMEState::SAT);
+    }
+}
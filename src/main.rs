@@ -4,6 +4,7 @@ extern crate lalrpop_util;
 use std::io;
 
 use presburger::nnf;
+use presburger::types::Domain;
 
 lalrpop_mod!(
     #[allow(clippy::all)]
@@ -20,6 +21,13 @@ fn main() {
     let p1 = grammer::FormulaParser::new().parse(&raw_input).unwrap();
     println!("p1: {p1:?}");
 
-    let p2 = nnf::to_nnf(p1);
+    let p2 = nnf::to_nnf(p1.clone());
     println!("{p2:?}");
+
+    for domain in [Domain::Integer, Domain::Rational] {
+        match presburger::decide(&p1, domain) {
+            Ok(b) => println!("{domain:?}: {b}"),
+            Err(e) => println!("{domain:?}: {e}"),
+        }
+    }
 }
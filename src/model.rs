@@ -0,0 +1,183 @@
+//! Models: assignments of rational values to the variables of a space.
+//!
+//! A [`Model`] gives a value to each variable `x_1, ..., x_n` and is used to evaluate expressions
+//! ([`crate::lin_expr::LinExpr::eval`]), relations ([`crate::lin_rel::LinRel::holds`]), and
+//! systems ([`crate::lin_sys::LinSys::satisfied_by`]).
+
+use crate::space::{Space, SpaceError};
+use crate::types::Rational;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelError {
+    /// The variable x_i has a non-zero coefficient but is not assigned by the model
+    Unassigned(usize),
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unassigned(i) => write!(
+                f,
+                "Variable {} is not assigned by the model",
+                Space::default_name(*i)
+            ),
+        }
+    }
+}
+
+impl Error for ModelError {}
+
+/// An assignment of values to the variables `x_1, ..., x_n`.
+///
+/// Evaluating an expression with a non-zero coefficient for a variable beyond `nvars()` is an
+/// error, see [`ModelError`]. Zero coefficients are ignored, so a model of a system can be used to
+/// evaluate relations that are padded with zero coefficients.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Model {
+    // values[i - 1] is the value of x_i
+    values: Vec<Rational>,
+}
+
+/// Display the model as a list of `x_i = value`
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format_values(Space::default_name))
+    }
+}
+
+impl Model {
+    /// Create an empty model
+    pub fn new() -> Self {
+        Self { values: Vec::new() }
+    }
+
+    /// Create a model assigning zero to `nvars` variables
+    pub fn zeros(nvars: usize) -> Self {
+        Self {
+            values: vec![Rational::ZERO; nvars],
+        }
+    }
+
+    /// Create a model from the values of `x_1, ..., x_n`, in order
+    ///
+    /// ```
+    /// # use presburger::model::*;
+    /// # use presburger::types::Rational;
+    /// # fn main () {
+    /// let m = Model::from_values(vec![1, -2]);
+    /// assert_eq!(m.value(2), Some(&Rational::from(-2)));
+    /// assert_eq!(m.value(3), None);
+    /// assert_eq!(m.to_string(), "x_1 = 1, x_2 = -2");
+    /// # }
+    /// ```
+    pub fn from_values(values: Vec<impl Into<Rational>>) -> Self {
+        Self {
+            values: values.into_iter().map(|v| v.into()).collect(),
+        }
+    }
+
+    /// Create a model from `(name, value)` pairs over the variables of `space`. Variables of the
+    /// space that are not given a value are assigned zero.
+    pub fn from_named<S: AsRef<str>>(
+        space: &Space,
+        values: impl IntoIterator<Item = (S, Rational)>,
+    ) -> Result<Self, SpaceError> {
+        let mut model = Self::zeros(space.dim());
+        for (name, v) in values {
+            let name = name.as_ref();
+            let i = space
+                .index_of(name)
+                .ok_or_else(|| SpaceError::UnknownName(name.to_string()))?;
+            model.set(i, v);
+        }
+        Ok(model)
+    }
+
+    /// Number of variables explicitly assigned by the model
+    pub fn nvars(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Get the values of `x_1, ..., x_n`
+    pub fn values(&self) -> &[Rational] {
+        &self.values
+    }
+
+    /// Get the value of `x_i`, or `None` if `i = 0` or `i > nvars()`
+    pub fn value(&self, i: usize) -> Option<&Rational> {
+        i.checked_sub(1).and_then(|i| self.values.get(i))
+    }
+
+    /// Get the value of the variable called `name` in `space`
+    pub fn value_of(&self, space: &Space, name: &str) -> Option<&Rational> {
+        space.index_of(name).and_then(|i| self.value(i))
+    }
+
+    /// Set the value of `x_i` (i > 0), growing the model with zeros if needed
+    pub fn set(&mut self, i: usize, value: impl Into<Rational>) {
+        debug_assert!(i > 0);
+        if self.values.len() < i {
+            self.values.resize(i, Rational::ZERO);
+        }
+        self.values[i - 1] = value.into();
+    }
+
+    /// Display the model using the variable names of `space`
+    pub fn to_string_in(&self, space: &Space) -> String {
+        self.format_values(|i| {
+            space
+                .name(i)
+                .map_or_else(|| Space::default_name(i), |n| n.to_string())
+        })
+    }
+
+    fn format_values(&self, name: impl Fn(usize) -> String) -> String {
+        let values: Vec<String> = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| format!("{} = {}", name(i + 1), v))
+            .collect();
+        values.join(", ")
+    }
+}
+
+/// A relation of a system that does not hold under a model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Index of the relation in the system
+    pub index: usize,
+    /// Value of the left hand side `e` of the relation `e rel 0`, measuring how far it is from
+    /// holding
+    pub value: Rational,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_basic_api() {
+        let mut m = Model::new();
+        assert_eq!(m.nvars(), 0);
+        m.set(3, 5);
+        assert_eq!(
+            m.values(),
+            &[Rational::ZERO, Rational::ZERO, Rational::from(5)]
+        );
+        assert_eq!(m, Model::from_values(vec![0, 0, 5]));
+
+        let space = Space::from_names(["x", "y"]).unwrap();
+        let m = Model::from_named(&space, [("y", Rational::ONE)]).unwrap();
+        assert_eq!(m.value_of(&space, "x"), Some(&Rational::ZERO));
+        assert_eq!(m.value_of(&space, "y"), Some(&Rational::ONE));
+        assert_eq!(m.value_of(&space, "z"), None);
+        assert_eq!(m.to_string_in(&space), "x = 0, y = 1");
+        assert_eq!(
+            Model::from_named(&space, [("z", Rational::ONE)]),
+            Err(SpaceError::UnknownName("z".to_string()))
+        );
+    }
+}
@@ -0,0 +1,634 @@
+//! Pugh's Omega test for the satisfiability of linear systems over the integers, after W. Pugh,
+//! "The Omega Test: a fast and practical integer programming algorithm for dependence analysis"
+//! (1991).
+//!
+//! Relations are normalized to integer coefficients, see `LinRel::normalize_integer()`, and kept
+//! as rows `c + a_1 x_1 + ... + a_n x_n` of either equalities `= 0` or inequalities `<= 0`.
+//!
+//! Equalities are eliminated first. An equality with a unit coefficient `a_k = +-1` is solved for
+//! `x_k`, which is substituted everywhere. Otherwise, with `a_k` the coefficient of least
+//! absolute value and `m = |a_k| + 1`, a new variable `s` is introduced with
+//! `m s = sum_i (a_i mod^ m) x_i + (c mod^ m)`, where `mod^` is the symmetric modulo
+//! [`crate::sym_mod::symmod`]. As `a_k mod^ m = -sign(a_k)`, this equation is solved for `x_k`,
+//! and substituting it shrinks the coefficients of the equality until one of them is a unit.
+//!
+//! Then a variable `x` is eliminated from the inequalities. Combining a lower bound `b x >= B`
+//! with an upper bound `a x <= A` gives the *real shadow* `a B <= b A`, which is implied by the
+//! system, and the *dark shadow* `a B + (a - 1)(b - 1) <= b A`, which implies that an integer `x`
+//! exists between the bounds. If all lower or all upper bounds of `x` have coefficient 1, both
+//! shadows agree and the elimination is exact. Otherwise, if the real shadow has no integer
+//! solution then neither does the system, and if the dark shadow has one then so does the system.
+//! In the remaining case, every integer solution lies close to some lower bound: with `a_max` the
+//! largest coefficient of an upper bound, `b x = B + j` for some lower bound and some
+//! `0 <= j <= (a_max b - a_max - b) / a_max`. Each of these *splinters* of the grey shadow is an
+//! equality, which is added to the system and decided in turn.
+//!
+//! Disequalities `e != 0` are split into `e + 1 <= 0` and `-e + 1 <= 0`.
+
+use crate::certificate::UnsatCertificate;
+use crate::lin_rel::{Constraint, LinRel};
+use crate::lin_sys::LinSys;
+use crate::model::Model;
+use crate::solver::{SatResult, Solver};
+use crate::space::{Space, SpaceError};
+use crate::sym_mod::{div_floor, gcd, symmod};
+use crate::types::{Integer, Rational};
+use dashu::base::Abs;
+use std::collections::HashMap;
+
+/// A row `c + a_1 x_1 + ... + a_n x_n`, stored as `[c, a_1, ..., a_n]`
+type Row = Vec<Integer>;
+
+/// A system of integer equalities `row = 0` and inequalities `row <= 0` over `nvars` variables
+#[derive(Debug, Clone)]
+struct Problem {
+    nvars: usize,
+    eqs: Vec<Row>,
+    les: Vec<Row>,
+}
+
+/// Value of `row` at `values`, where `values[0]` is 1 and `values[i]` is the value of `x_i`
+fn eval(row: &Row, values: &[Integer]) -> Integer {
+    row.iter()
+        .zip(values.iter())
+        .filter(|(a, _)| **a != Integer::ZERO)
+        .map(|(a, v)| a * v)
+        .sum()
+}
+
+/// `x_k` is substituted by `s` in `row`, where `s` does not support `x_k`
+fn substitute(row: &mut Row, k: usize, s: &Row) {
+    let a = std::mem::take(&mut row[k]);
+    if a != Integer::ZERO {
+        for (r, b) in row.iter_mut().zip(s.iter()) {
+            *r += &a * b;
+        }
+    }
+}
+
+impl Problem {
+    /// Divide every row by the gcd of its coefficients, rounding the constant of inequalities up,
+    /// and keep the tightest of the inequalities with the same coefficients. Returns `false` if
+    /// some row is a contradiction.
+    fn normalize(&mut self) -> bool {
+        let mut eqs = Vec::with_capacity(self.eqs.len());
+        for mut row in std::mem::take(&mut self.eqs) {
+            let g = row[1..].iter().fold(Integer::ZERO, |g, a| gcd(&g, a));
+            if g == Integer::ZERO {
+                if row[0] != Integer::ZERO {
+                    return false;
+                }
+                continue;
+            }
+            if &row[0] % &g != Integer::ZERO {
+                return false;
+            }
+            for a in row.iter_mut() {
+                *a /= &g;
+            }
+            eqs.push(row);
+        }
+        self.eqs = eqs;
+
+        // the largest constant of the inequalities with the same coefficients
+        let mut tightest: HashMap<Row, Integer> = HashMap::new();
+        let mut order = Vec::new();
+        for row in std::mem::take(&mut self.les) {
+            let g = row[1..].iter().fold(Integer::ZERO, |g, a| gcd(&g, a));
+            if g == Integer::ZERO {
+                if row[0] > Integer::ZERO {
+                    return false;
+                }
+                continue;
+            }
+            let c = -div_floor(-row[0].clone(), g.clone());
+            let coeffs: Row = row[1..].iter().map(|a| a / &g).collect();
+            match tightest.get_mut(&coeffs) {
+                Some(d) if *d >= c => {}
+                Some(d) => *d = c,
+                None => {
+                    order.push(coeffs.clone());
+                    tightest.insert(coeffs, c);
+                }
+            }
+        }
+        self.les = order
+            .into_iter()
+            .map(|coeffs| {
+                let mut row = vec![tightest[&coeffs].clone()];
+                row.extend(coeffs);
+                row
+            })
+            .collect();
+        true
+    }
+
+    /// Add a variable `x_{nvars + 1}`, returning its index
+    fn add_var(&mut self) -> usize {
+        self.nvars += 1;
+        for row in self.eqs.iter_mut().chain(self.les.iter_mut()) {
+            row.push(Integer::ZERO);
+        }
+        self.nvars
+    }
+
+    /// Substitute `s` for `x_k` in every row
+    fn substitute(&mut self, k: usize, s: &Row) {
+        for row in self.eqs.iter_mut().chain(self.les.iter_mut()) {
+            substitute(row, k, s);
+        }
+    }
+
+    /// Decide the problem, returning the values `[1, x_1, ..., x_n]` of an integer solution if
+    /// there is one
+    fn solve(mut self) -> Option<Vec<Integer>> {
+        if !self.normalize() {
+            return None;
+        }
+        if let Some(eq) = self.eqs.pop() {
+            return self.solve_eq(eq);
+        }
+        let Some((k, exact)) = self.choose_var() else {
+            let mut values = vec![Integer::ZERO; self.nvars + 1];
+            values[0] = Integer::ONE;
+            return Some(values);
+        };
+        let mut lower = Vec::new();
+        let mut upper = Vec::new();
+        let mut rest = Vec::new();
+        for row in std::mem::take(&mut self.les) {
+            match row[k].cmp(&Integer::ZERO) {
+                std::cmp::Ordering::Less => lower.push(row),
+                std::cmp::Ordering::Greater => upper.push(row),
+                std::cmp::Ordering::Equal => rest.push(row),
+            }
+        }
+        let shadow = |dark: bool| {
+            let mut les = rest.clone();
+            for l in lower.iter() {
+                let b = -&l[k];
+                for u in upper.iter() {
+                    let a = &u[k];
+                    // the rows are B - b x and a x - A, and a (B - b x) + b (a x - A) <= 0 is a B <= b A
+                    let mut row: Row = l
+                        .iter()
+                        .zip(u.iter())
+                        .map(|(l, u)| a * l + &b * u)
+                        .collect();
+                    if dark {
+                        row[0] += (a - Integer::ONE) * (&b - Integer::ONE);
+                    }
+                    les.push(row);
+                }
+            }
+            Self {
+                nvars: self.nvars,
+                eqs: Vec::new(),
+                les,
+            }
+        };
+        let between = |values: Option<Vec<Integer>>| {
+            values.map(|mut values| {
+                values[k] = Self::value_between(&values, k, &lower, &upper);
+                values
+            })
+        };
+
+        if exact {
+            return between(shadow(false).solve());
+        }
+        shadow(false).solve()?;
+        if let Some(values) = between(shadow(true).solve()) {
+            return Some(values);
+        }
+        // the grey shadow
+        let a_max = upper.iter().map(|u| u[k].clone()).max().unwrap();
+        for l in lower.iter() {
+            let b = -&l[k];
+            let jmax = div_floor(&a_max * &b - &a_max - &b, a_max.clone());
+            let mut j = Integer::ZERO;
+            while j <= jmax {
+                // b x = B + j
+                let mut eq = l.clone();
+                eq[0] += &j;
+                let mut splinter = self.clone();
+                splinter.les = rest.iter().chain(&lower).chain(&upper).cloned().collect();
+                splinter.eqs.push(eq);
+                if let Some(values) = splinter.solve() {
+                    return Some(values);
+                }
+                j += Integer::ONE;
+            }
+        }
+        None
+    }
+
+    /// Eliminate the equality `eq` and decide the rest of the problem
+    fn solve_eq(mut self, mut eq: Row) -> Option<Vec<Integer>> {
+        let nvars = self.nvars;
+        let unit = (1..=nvars).find(|i| eq[*i] == Integer::ONE || eq[*i] == Integer::NEG_ONE);
+        let (k, s) = match unit {
+            Some(k) => {
+                // a_k x_k + e = 0 gives x_k = -a_k e
+                let a = std::mem::take(&mut eq[k]);
+                let s: Row = eq.iter().map(|e| -&a * e).collect();
+                (k, s)
+            }
+            None => {
+                let k = (1..=nvars)
+                    .filter(|i| eq[*i] != Integer::ZERO)
+                    .min_by_key(|i| eq[*i].clone().abs())
+                    .expect("normalized equalities have a variable");
+                let sign = if eq[k] < Integer::ZERO {
+                    Integer::NEG_ONE
+                } else {
+                    Integer::ONE
+                };
+                let m = eq[k].clone().abs() + Integer::ONE;
+                let sigma = self.add_var();
+                eq.push(Integer::ZERO);
+                // m s = sum_{i != k} (a_i mod^ m) x_i - sign x_k + (c mod^ m)
+                let mut s: Row = eq
+                    .iter()
+                    .map(|a| &sign * symmod(a.clone(), m.clone()))
+                    .collect();
+                s[k] = Integer::ZERO;
+                s[sigma] = -&sign * &m;
+                (k, s)
+            }
+        };
+        substitute(&mut eq, k, &s);
+        self.substitute(k, &s);
+        if unit.is_none() {
+            self.eqs.push(eq);
+        }
+        let mut values = self.solve()?;
+        values[k] = eval(&s, &values);
+        values.truncate(nvars + 1);
+        Some(values)
+    }
+
+    /// The variable to eliminate from the inequalities, and whether its elimination is exact, or
+    /// `None` if no inequality has a variable. Exact eliminations are preferred, then those with
+    /// the fewest combinations of bounds.
+    fn choose_var(&self) -> Option<(usize, bool)> {
+        (1..=self.nvars)
+            .filter_map(|k| {
+                let mut lower = (0usize, true);
+                let mut upper = (0usize, true);
+                for row in self.les.iter() {
+                    let a = &row[k];
+                    let bounds = match a.cmp(&Integer::ZERO) {
+                        std::cmp::Ordering::Less => &mut lower,
+                        std::cmp::Ordering::Greater => &mut upper,
+                        std::cmp::Ordering::Equal => continue,
+                    };
+                    bounds.0 += 1;
+                    bounds.1 &= *a == Integer::ONE || *a == Integer::NEG_ONE;
+                }
+                if lower.0 + upper.0 == 0 {
+                    return None;
+                }
+                let exact = lower.1 || upper.1;
+                Some((k, exact, lower.0 * upper.0))
+            })
+            .min_by_key(|(k, exact, product)| (!exact, *product, *k))
+            .map(|(k, exact, _)| (k, exact))
+    }
+
+    /// An integer value of `x_k` between its bounds at `values`, the least one if it has lower
+    /// bounds. The bounds must have an integer between them.
+    fn value_between(values: &[Integer], k: usize, lower: &[Row], upper: &[Row]) -> Integer {
+        let rest = |row: &Row| {
+            let mut row = row.clone();
+            row[k] = Integer::ZERO;
+            eval(&row, values)
+        };
+        // -b x + B <= 0 gives x >= ceil(B / b)
+        let lo = lower.iter().map(|l| -div_floor(-rest(l), -&l[k])).max();
+        // a x + A <= 0 gives x <= floor(-A / a)
+        let hi = upper
+            .iter()
+            .map(|u| div_floor(-rest(u), u[k].clone()))
+            .min();
+        match (lo, hi) {
+            (Some(lo), _) => lo,
+            (None, Some(hi)) => hi,
+            (None, None) => Integer::ZERO,
+        }
+    }
+}
+
+/// A decision procedure for linear systems over the integers by the Omega test
+#[derive(Debug)]
+pub struct OmegaSolver {
+    state: SatResult,
+    system: LinSys,
+    model: Option<Model>,
+}
+
+impl OmegaSolver {
+    /// Create a fresh solver
+    pub fn new() -> Self {
+        Self::with_space(Space::new())
+    }
+
+    /// Create a fresh solver over the given space of variables
+    pub fn with_space(space: Space) -> Self {
+        Self {
+            state: SatResult::UNKNOWN,
+            system: LinSys::with_space(space),
+            model: None,
+        }
+    }
+
+    /// Get the space of variables of the solver
+    pub fn space(&self) -> &Space {
+        self.system.space()
+    }
+
+    /// Get the asserted relations
+    pub fn assertions(&self) -> &LinSys {
+        &self.system
+    }
+
+    /// Assert a new relation. If it ranges over more variables than the space has, the space is
+    /// grown, see `try_assert()` to reject such relations instead.
+    pub fn assert(&mut self, rel: LinRel) {
+        self.invalidate();
+        self.system.add_relation(rel);
+    }
+
+    /// Assert a new relation, checking that it belongs to the solver's space
+    pub fn try_assert(&mut self, rel: LinRel) -> Result<(), SpaceError> {
+        self.invalidate();
+        self.system.try_add_relation(rel)
+    }
+
+    /// Reset the solver state and clear all assertions, keeping the space
+    pub fn reset(&mut self) {
+        self.invalidate();
+        self.system.clear();
+    }
+
+    /// Forget the result of the last `check`
+    fn invalidate(&mut self) {
+        self.state = SatResult::UNKNOWN;
+        self.model = None;
+    }
+
+    /// An integer model of the assertions, if the last call to `check` returned `SAT`.
+    /// Variables that are not constrained by the assertions are assigned zero.
+    pub fn model(&self) -> Option<&Model> {
+        self.model.as_ref()
+    }
+
+    /// Check whether the assertions have an integer solution
+    ///
+    /// ```
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # use presburger::solver::SatResult;
+    /// # use presburger::omega::OmegaSolver;
+    /// # fn main () {
+    /// let mut solver = OmegaSolver::new();
+    /// // 1 <= 3 x_1 - 3 x_2 <= 2 has rational but no integer solutions
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![1, -3, 3]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![-2, 3, -3]).unwrap()));
+    /// assert_eq!(solver.check(), SatResult::UNSAT);
+    ///
+    /// // 3 x_1 + 5 x_2 = 1, 0 <= x_1 <= 5
+    /// solver.reset();
+    /// solver.assert(LinRel::mk_eq(LinExpr::new(vec![-1, 3, 5]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![0, -1, 0]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![-5, 1, 0]).unwrap()));
+    /// assert_eq!(solver.check(), SatResult::SAT);
+    /// let model = solver.model().unwrap();
+    /// assert!(model.values().iter().all(|v| v.is_int()));
+    /// assert_eq!(solver.assertions().satisfied_by(model), Ok(true));
+    /// # }
+    /// ```
+    pub fn check(&mut self) -> SatResult {
+        self.invalidate();
+        let nvars = self.space().dim();
+        let mut base = Problem {
+            nvars,
+            eqs: Vec::new(),
+            les: Vec::new(),
+        };
+        let mut disequalities = Vec::new();
+        for r in self.system.relations() {
+            let r = r.clone().extend_to(nvars).normalize_integer();
+            let row: Row = std::iter::once(r.const_())
+                .chain(r.coeffs())
+                .map(|a| a.numerator())
+                .collect();
+            match r.constraint() {
+                Constraint::Eq => base.eqs.push(row),
+                Constraint::Ne => disequalities.push(row),
+                _ => base.les.push(row),
+            }
+        }
+        match Self::split_disequalities(base, &disequalities) {
+            Some(values) => {
+                self.state = SatResult::SAT;
+                let values: Vec<Rational> =
+                    values[1..].iter().cloned().map(Rational::from).collect();
+                self.model = Some(Model::from_values(values));
+            }
+            None => self.state = SatResult::UNSAT,
+        }
+        self.state.clone()
+    }
+
+    /// Decide `problem` together with the disequalities `e != 0`, each split into `e + 1 <= 0`
+    /// and `-e + 1 <= 0`
+    fn split_disequalities(problem: Problem, disequalities: &[Row]) -> Option<Vec<Integer>> {
+        let Some((ne, rest)) = disequalities.split_first() else {
+            return problem.solve();
+        };
+        for sign in [Integer::ONE, Integer::NEG_ONE] {
+            let mut branch = problem.clone();
+            let mut row: Row = ne.iter().map(|a| &sign * a).collect();
+            row[0] += Integer::ONE;
+            branch.les.push(row);
+            if let Some(values) = Self::split_disequalities(branch, rest) {
+                return Some(values);
+            }
+        }
+        None
+    }
+}
+
+impl Default for OmegaSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Solver for OmegaSolver {
+    fn assertions(&self) -> &LinSys {
+        OmegaSolver::assertions(self)
+    }
+
+    fn assert(&mut self, rel: LinRel) {
+        OmegaSolver::assert(self, rel);
+    }
+
+    fn try_assert(&mut self, rel: LinRel) -> Result<(), SpaceError> {
+        OmegaSolver::try_assert(self, rel)
+    }
+
+    fn check(&mut self) -> SatResult {
+        OmegaSolver::check(self)
+    }
+
+    fn model(&self) -> Option<&Model> {
+        OmegaSolver::model(self)
+    }
+
+    fn unsat_certificate(&self) -> Option<&UnsatCertificate> {
+        None
+    }
+
+    fn reset(&mut self) {
+        OmegaSolver::reset(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::int_testing::{arb_system, bounding_box, check_valid};
+    use crate::lin_expr::LinExpr;
+    use crate::{eq, le};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_omega_pugh_example() {
+        // 27 <= 11 x + 13 y <= 45, -10 <= 7 x - 9 y <= 4 has rational solutions, but no integer
+        // ones, which is shown by the grey shadow
+        let mut solver = OmegaSolver::new();
+        solver.assert(le!(27, -11, -13));
+        solver.assert(le!(-45, 11, 13));
+        solver.assert(le!(-10, -7, 9));
+        solver.assert(le!(-4, 7, -9));
+        assert_eq!(check_valid(&mut solver), SatResult::UNSAT);
+
+        // widening the second range to 5 lets x = 3, y = 1 through
+        solver.reset();
+        solver.assert(le!(27, -11, -13));
+        solver.assert(le!(-45, 11, 13));
+        solver.assert(le!(-10, -7, 9));
+        solver.assert(le!(-12, 7, -9));
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+    }
+
+    #[test]
+    fn test_omega_equalities() {
+        // 7 x + 12 y + 31 z = 17, 3 x + 5 y + 14 z = 7, 1 <= x <= 40, -50 <= y <= 50, from
+        // Pugh's paper, which needs symmetric modulo steps
+        let mut solver = OmegaSolver::new();
+        solver.assert(eq!(-17, 7, 12, 31));
+        solver.assert(eq!(-7, 3, 5, 14));
+        solver.assert(le!(1, -1, 0, 0));
+        solver.assert(le!(-40, 1, 0, 0));
+        solver.assert(le!(-50, 0, -1, 0));
+        solver.assert(le!(-50, 0, 1, 0));
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+
+        // 2 x = 2 y + 1 has no integer solutions
+        solver.reset();
+        solver.assert(eq!(-1, 2, -2, 0));
+        assert_eq!(check_valid(&mut solver), SatResult::UNSAT);
+
+        // 6 x + 10 y + 15 z = 1
+        solver.reset();
+        solver.assert(eq!(-1, 6, 10, 15));
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+    }
+
+    #[test]
+    fn test_omega_strict_and_disequalities() {
+        let mut solver = OmegaSolver::new();
+        // 0 < 2 x < 4 forces x = 1, which x != 1 rules out
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![0, -2]).unwrap()));
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![-4, 2]).unwrap()));
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+        assert_eq!(solver.model().unwrap(), &Model::from_values(vec![1]));
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![-1, 1]).unwrap()));
+        assert_eq!(check_valid(&mut solver), SatResult::UNSAT);
+
+        // 2 x != 1 always holds
+        solver.reset();
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![-1, 2]).unwrap()));
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut problem = Problem {
+            nvars: 2,
+            eqs: vec![vec![4.into(), 2.into(), (-6).into()]],
+            les: vec![
+                vec![1.into(), 2.into(), 4.into()],
+                vec![3.into(), 1.into(), 2.into()],
+                vec![(-1).into(), 0.into(), 0.into()],
+            ],
+        };
+        assert!(problem.normalize());
+        assert_eq!(problem.eqs, vec![vec![2.into(), 1.into(), (-3).into()]]);
+        // 1 + 2 x + 4 y <= 0 becomes 1 + x + 2 y <= 0, and 3 + x + 2 y <= 0 is tighter
+        let expected: Row = vec![3.into(), 1.into(), 2.into()];
+        assert_eq!(problem.les, vec![expected]);
+
+        problem.eqs.push(vec![1.into(), 2.into(), 0.into()]);
+        assert!(!problem.normalize());
+    }
+
+    /// Is there an integer point of the box `[-bound, bound]^nvars` satisfying all relations?
+    fn brute_force(relations: &[LinRel], nvars: usize, bound: i32) -> bool {
+        let mut point = vec![-bound; nvars];
+        loop {
+            let model = Model::from_values(point.clone());
+            if relations.iter().all(|r| r.holds(&model) == Ok(true)) {
+                return true;
+            }
+            let Some(i) = point.iter().position(|v| *v < bound) else {
+                return false;
+            };
+            point[i] += 1;
+            for v in point[..i].iter_mut() {
+                *v = -bound;
+            }
+        }
+    }
+
+    #[test]
+    fn test_omega_try_assert() {
+        let space = Space::from_names(["x", "y"]).unwrap();
+        let mut solver = OmegaSolver::with_space(space.clone());
+        assert_eq!(solver.try_assert(eq!(-1, 2, -2)), Ok(())); // 2 x - 2 y = 1
+        assert_eq!(
+            solver.try_assert(le!(1, 0, 0, 1)), // 1 + x_3 <= 0
+            Err(SpaceError::DimensionMismatch { nvars: 3, dim: 2 })
+        );
+        assert_eq!(solver.assertions().len(), 1);
+        assert_eq!(solver.space(), &space);
+        assert_eq!(check_valid(&mut solver), SatResult::UNSAT);
+    }
+
+    proptest! {
+        /// The Omega test agrees with enumerating the integer points of a small box
+        #[test]
+        fn omega_agrees_with_brute_force((nvars, relations) in arb_system()) {
+            const BOUND: i32 = 4;
+            let mut solver = OmegaSolver::new();
+            for r in relations.iter().chain(&bounding_box(nvars, BOUND)) {
+                solver.assert(r.clone());
+            }
+            let sat = brute_force(&relations, nvars, BOUND);
+            prop_assert_eq!(check_valid(&mut solver), if sat { SatResult::SAT } else { SatResult::UNSAT });
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! The quantifier-free formulas and the elimination driver shared by the quantifier elimination
+//! procedures, Cooper's method over the integers (see [`crate::cooper`]) and virtual substitution
+//! over the rationals (see [`crate::virtual_subs`]).
+//!
+//! Quantifiers are eliminated innermost first, so that the body of each quantifier is
+//! quantifier-free, and `forall x. p` is treated as `~(exists x. ~p)`. The body of `exists x. p`
+//! is converted to NNF and then to a [`Qf`] over the literals of the procedure. Quantifiers over
+//! logical variables are expanded to `p[T/P] \/ p[F/P]`, and numeric variables are eliminated by
+//! the procedure, see [`Literal::exists`].
+
+use crate::ast::{Atom, Formula, Term, Var};
+use crate::lin_expr::LinExpr;
+use crate::nnf::to_nnf;
+use crate::space::Space;
+
+/// The literals of a quantifier elimination procedure, together with its elimination step
+pub(crate) trait Literal: Clone {
+    type Error;
+
+    /// Convert the linear atom `a`, i.e. an equality, inequality or divisibility, or its negation
+    /// if `negated`, interning its variables in `space`
+    fn from_atom(a: &Atom, negated: bool, space: &mut Space) -> Result<Qf<Self>, Self::Error>;
+
+    /// The negation of the literal
+    fn negate(&self) -> Self;
+
+    /// Extend the literal to range over `nvars` variables
+    fn extend_to(self, nvars: usize) -> Self;
+
+    /// Convert back to a formula over the variables of `space`
+    fn to_formula(&self, space: &Space) -> Formula;
+
+    /// Eliminate `x_k` from `exists x_k. p`, where `p` ranges over `nvars` variables
+    fn exists(p: Qf<Self>, k: usize, nvars: usize) -> Qf<Self>;
+}
+
+/// A quantifier-free formula in NNF over the literals `L`
+#[derive(Clone, Debug)]
+pub(crate) enum Qf<L> {
+    Bool(bool),
+    Lit(L),
+    /// A logical variable or its negation
+    Prop(Formula),
+    And(Box<Qf<L>>, Box<Qf<L>>),
+    Or(Box<Qf<L>>, Box<Qf<L>>),
+}
+
+impl<L: Literal> Qf<L> {
+    pub(crate) fn and(p: Self, q: Self) -> Self {
+        match (p, q) {
+            (Qf::Bool(false), _) | (_, Qf::Bool(false)) => Qf::Bool(false),
+            (Qf::Bool(true), r) | (r, Qf::Bool(true)) => r,
+            (p, q) => Qf::And(Box::new(p), Box::new(q)),
+        }
+    }
+
+    pub(crate) fn or(p: Self, q: Self) -> Self {
+        match (p, q) {
+            (Qf::Bool(true), _) | (_, Qf::Bool(true)) => Qf::Bool(true),
+            (Qf::Bool(false), r) | (r, Qf::Bool(false)) => r,
+            (p, q) => Qf::Or(Box::new(p), Box::new(q)),
+        }
+    }
+
+    /// Replace every leaf `l` of the formula by `f(l)`, simplifying the connectives
+    pub(crate) fn map(&self, f: &mut impl FnMut(&Self) -> Self) -> Self {
+        match self {
+            Qf::And(p, q) => {
+                let p = p.map(f);
+                Qf::and(p, q.map(f))
+            }
+            Qf::Or(p, q) => {
+                let p = p.map(f);
+                Qf::or(p, q.map(f))
+            }
+            leaf => f(leaf),
+        }
+    }
+
+    /// Replace every literal `l` of the formula by `f(l)`
+    pub(crate) fn map_lits(&self, f: &mut impl FnMut(&L) -> Self) -> Self {
+        self.map(&mut |leaf| match leaf {
+            Qf::Lit(l) => f(l),
+            other => other.clone(),
+        })
+    }
+
+    /// Call `f` on every literal of the formula
+    pub(crate) fn for_each_lit(&self, f: &mut impl FnMut(&L)) {
+        match self {
+            Qf::And(p, q) | Qf::Or(p, q) => {
+                p.for_each_lit(f);
+                q.for_each_lit(f);
+            }
+            Qf::Lit(l) => f(l),
+            _ => (),
+        }
+    }
+
+    /// The negation of the formula, in NNF
+    pub(crate) fn negate(&self) -> Self {
+        match self {
+            Qf::Bool(b) => Qf::Bool(!b),
+            Qf::Lit(l) => Qf::Lit(l.negate()),
+            Qf::Prop(Formula::Not(p)) => Qf::Prop(*p.clone()),
+            Qf::Prop(p) => Qf::Prop(Formula::fnot(p.clone())),
+            Qf::And(p, q) => Qf::or(p.negate(), q.negate()),
+            Qf::Or(p, q) => Qf::and(p.negate(), q.negate()),
+        }
+    }
+
+    /// Whether the logical variable `v` occurs in the formula
+    fn has_prop(&self, v: &Var) -> bool {
+        match self {
+            Qf::And(p, q) | Qf::Or(p, q) => p.has_prop(v) || q.has_prop(v),
+            Qf::Prop(p) => prop_var(p) == Some(v),
+            _ => false,
+        }
+    }
+
+    /// Convert a quantifier-free formula in NNF, interning its variables in `space`
+    fn from_formula(p: &Formula, space: &mut Space) -> Result<Self, L::Error> {
+        match p {
+            Formula::And(p, q) => {
+                let p = Self::from_formula(p, space)?;
+                Ok(Qf::and(p, Self::from_formula(q, space)?))
+            }
+            Formula::Or(p, q) => {
+                let p = Self::from_formula(p, space)?;
+                Ok(Qf::or(p, Self::from_formula(q, space)?))
+            }
+            Formula::Atom(a) => Self::from_atom(a, false, space),
+            Formula::Not(q) => match &**q {
+                Formula::Atom(a) => Self::from_atom(a, true, space),
+                _ => panic!("formula is not in NNF: {p}"),
+            },
+            _ => panic!("formula is not quantifier-free and in NNF: {p}"),
+        }
+    }
+
+    /// Convert the atom `a`, or its negation if `negated`
+    fn from_atom(a: &Atom, negated: bool, space: &mut Space) -> Result<Self, L::Error> {
+        match a {
+            Atom::TruthValue(b) => Ok(Qf::Bool(*b != negated)),
+            Atom::LogicalVar(_) => {
+                let p = Formula::atom(a.clone());
+                Ok(Qf::Prop(if negated { Formula::fnot(p) } else { p }))
+            }
+            _ => L::from_atom(a, negated, space),
+        }
+    }
+
+    /// Convert back to a formula over the variables of `space`
+    fn to_formula(&self, space: &Space) -> Formula {
+        match self {
+            Qf::Bool(b) => Formula::atom(Atom::truth(*b)),
+            Qf::Lit(l) => l.to_formula(space),
+            Qf::Prop(p) => p.clone(),
+            Qf::And(p, q) => Formula::and(p.to_formula(space), q.to_formula(space)),
+            Qf::Or(p, q) => Formula::or(p.to_formula(space), q.to_formula(space)),
+        }
+    }
+}
+
+/// The variable of a literal over a logical variable
+fn prop_var(p: &Formula) -> Option<&Var> {
+    match p {
+        Formula::Atom(a) => match &**a {
+            Atom::LogicalVar(v) => Some(v),
+            _ => None,
+        },
+        Formula::Not(q) => prop_var(q),
+        _ => None,
+    }
+}
+
+/// Render `e` as a right-nested sum of its monomials, followed by its constant
+pub(crate) fn to_term(e: &LinExpr, space: &Space) -> Term {
+    let mut terms: Vec<Term> = e
+        .terms()
+        .map(|(i, a)| Term::scalar_var(a.clone(), space.name(i).unwrap()))
+        .collect();
+    if !e.const_().is_zero() || terms.is_empty() {
+        terms.push(Term::num(e.const_().clone()));
+    }
+    terms
+        .into_iter()
+        .rev()
+        .reduce(|acc, t| Term::tadd(t, acc))
+        .unwrap()
+}
+
+/// Eliminate `exists v. p` for a quantifier-free `p`, returning the negation of the result if
+/// `negate`
+fn exists_qf<L: Literal>(v: &Var, p: &Formula, negate: bool) -> Result<Formula, L::Error> {
+    let mut space = Space::new();
+    let q = Qf::<L>::from_formula(&to_nnf(p.clone()), &mut space)?;
+    let n = space.dim();
+    let mut q = q.map_lits(&mut |l| Qf::Lit(l.clone().extend_to(n)));
+    if q.has_prop(v) {
+        let assign = |b: bool| {
+            q.map(&mut |leaf| match leaf {
+                Qf::Prop(p) if prop_var(p) == Some(v) => {
+                    Qf::Bool(b == matches!(p, Formula::Atom(_)))
+                }
+                other => other.clone(),
+            })
+        };
+        let (t, f) = (assign(true), assign(false));
+        q = Qf::or(t, f);
+    }
+    if let Some(k) = space.index_of(&v.0) {
+        q = L::exists(q, k, n);
+    }
+    if negate {
+        q = q.negate();
+    }
+    Ok(q.to_formula(&space))
+}
+
+/// Eliminate `exists v. p`, returning an equivalent quantifier-free formula
+pub(crate) fn eliminate_exists<L: Literal>(v: &Var, p: &Formula) -> Result<Formula, L::Error> {
+    exists_qf::<L>(v, &eliminate::<L>(p)?, false)
+}
+
+/// Eliminate all quantifiers of `p`, innermost first, returning an equivalent quantifier-free
+/// formula. The result is in NNF where a quantifier was eliminated.
+pub(crate) fn eliminate<L: Literal>(p: &Formula) -> Result<Formula, L::Error> {
+    Ok(match p {
+        Formula::Not(q) => Formula::fnot(eliminate::<L>(q)?),
+        Formula::And(p, q) => Formula::and(eliminate::<L>(p)?, eliminate::<L>(q)?),
+        Formula::Or(p, q) => Formula::or(eliminate::<L>(p)?, eliminate::<L>(q)?),
+        Formula::Impl(p, q) => Formula::implies(eliminate::<L>(p)?, eliminate::<L>(q)?),
+        Formula::Iff(p, q) => Formula::iff(eliminate::<L>(p)?, eliminate::<L>(q)?),
+        Formula::Exists(v, q) => exists_qf::<L>(v, &eliminate::<L>(q)?, false)?,
+        // forall v. q iff ~(exists v. ~q)
+        Formula::Forall(v, q) => exists_qf::<L>(v, &Formula::fnot(eliminate::<L>(q)?), true)?,
+        Formula::Atom(_) => p.clone(),
+    })
+}
@@ -0,0 +1,106 @@
+//! Test fixtures shared by the quantifier elimination procedures, [`crate::cooper`] and
+//! [`crate::virtual_subs`], and by [`crate::decide`]
+
+use crate::ast::{Atom, Formula, Term};
+use crate::types::Rational;
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+pub(crate) type Env = HashMap<String, Rational>;
+
+pub(crate) fn var(a: i64, name: &str) -> Term {
+    Term::scalar_var(Rational::from(a), name)
+}
+
+pub(crate) fn env<T: Clone + Into<Rational>>(vals: &[(&str, T)]) -> Env {
+    vals.iter()
+        .map(|(n, v)| (n.to_string(), v.clone().into()))
+        .collect()
+}
+
+pub(crate) fn eval_term(t: &Term, env: &Env) -> Rational {
+    t.eval(&|v| env.get(&v.0).cloned()).unwrap()
+}
+
+/// Evaluate a quantifier-free formula, where logical variables are true if they are non-zero
+pub(crate) fn eval(p: &Formula, env: &Env) -> bool {
+    match p {
+        Formula::Not(q) => !eval(q, env),
+        Formula::And(p, q) => eval(p, env) && eval(q, env),
+        Formula::Or(p, q) => eval(p, env) || eval(q, env),
+        Formula::Impl(p, q) => !eval(p, env) || eval(q, env),
+        Formula::Iff(p, q) => eval(p, env) == eval(q, env),
+        Formula::Atom(a) => a.eval(&|v| env.get(&v.0).cloned()).unwrap(),
+        Formula::Exists(_, _) | Formula::Forall(_, _) => panic!("quantified formula {p}"),
+    }
+}
+
+pub(crate) fn is_quantifier_free(p: &Formula) -> bool {
+    match p {
+        Formula::Not(q) => is_quantifier_free(q),
+        Formula::And(p, q) | Formula::Or(p, q) | Formula::Impl(p, q) | Formula::Iff(p, q) => {
+            is_quantifier_free(p) && is_quantifier_free(q)
+        }
+        Formula::Exists(_, _) | Formula::Forall(_, _) => false,
+        Formula::Atom(_) => true,
+    }
+}
+
+/// Quantifier-free formulas over the atoms `atom`, with negation, conjunction, disjunction and
+/// equivalence
+pub(crate) fn arb_qf_formula(
+    atom: impl Strategy<Value = Atom> + 'static,
+) -> impl Strategy<Value = Formula> {
+    atom.prop_map(Formula::atom)
+        .prop_recursive(3, 8, 2, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(Formula::fnot),
+                (inner.clone(), inner.clone()).prop_map(|(p, q)| Formula::and(p, q)),
+                (inner.clone(), inner.clone()).prop_map(|(p, q)| Formula::or(p, q)),
+                (inner.clone(), inner).prop_map(|(p, q)| Formula::iff(p, q)),
+            ]
+        })
+}
+
+/// The values of `x` at which the equalities and inequalities of `p` change their truth value,
+/// for the values of the other variables in `env`
+fn roots(p: &Formula, x: &str, env: &Env, roots: &mut Vec<Rational>) {
+    match p {
+        Formula::Not(q) => self::roots(q, x, env, roots),
+        Formula::And(p, q) | Formula::Or(p, q) | Formula::Impl(p, q) | Formula::Iff(p, q) => {
+            self::roots(p, x, env, roots);
+            self::roots(q, x, env, roots);
+        }
+        Formula::Atom(a) => match &**a {
+            Atom::Equality(t1, t2) | Atom::LessEq(t1, t2) => {
+                // a x + c where c = t(0)
+                let at = |c: i64, t: &Term| {
+                    let mut env = env.clone();
+                    env.insert(x.to_string(), Rational::from(c));
+                    eval_term(t, &env)
+                };
+                let c = at(0, t1) - at(0, t2);
+                let a = at(1, t1) - at(1, t2) - &c;
+                if !a.is_zero() {
+                    roots.push(-c / a);
+                }
+            }
+            _ => (),
+        },
+        Formula::Exists(_, _) | Formula::Forall(_, _) => panic!("quantified formula {p}"),
+    }
+}
+
+/// Values of `x` that cover every truth value of `p` over the rationals, for the values of the
+/// other variables in `env`: the roots of its atoms, the points between them, and beyond them
+pub(crate) fn test_points(p: &Formula, x: &str, env: &Env) -> Vec<Rational> {
+    let mut rs = vec![Rational::ZERO];
+    roots(p, x, env, &mut rs);
+    rs.sort();
+    let mut points = vec![&rs[0] - Rational::ONE, &rs[rs.len() - 1] + Rational::ONE];
+    for w in rs.windows(2) {
+        points.push((&w[0] + &w[1]) / Rational::from(2));
+    }
+    points.extend(rs);
+    points
+}
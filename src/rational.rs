@@ -0,0 +1,542 @@
+//! Arbitrary precision rational numbers with a machine integer fast path.
+//!
+//! [`Rational`] stores values whose numerator and denominator fit in an `i64` inline and operates
+//! on them with checked `i64`/`i128` arithmetic. When a result does not fit it is promoted
+//! transparently to a `dashu` [`RBig`], and results of `RBig` arithmetic that fit again are
+//! demoted. Coefficients in FME and LIA problems are overwhelmingly small, so most operations never
+//! allocate or normalize a big integer gcd.
+//!
+//! The API follows the subset of `dashu::rational::RBig` used in this crate, so `Rational` is a
+//! drop-in replacement; see [`crate::types`].
+//!
+//! The `rbig-only` feature disables the fast path, so that every value is stored as a `RBig`. It
+//! gives the baseline against which the fast path is measured, e.g. by `benches/fme.rs`.
+
+use crate::types::Integer;
+use dashu::integer::UBig;
+use dashu::rational::RBig;
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// Rational number, see the module documentation
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Rational(Repr);
+
+// Invariant: a value is `Small` iff its reduced numerator `n` satisfies `|n| <= i64::MAX` and its
+// reduced denominator `d` satisfies `d <= i64::MAX`. In `Small(n, d)`, `d > 0` and `gcd(n, d) = 1`.
+// The representation is thus canonical, which makes the derived `PartialEq` and `Hash` correct.
+// With the `rbig-only` feature, every value is `Big` instead, which is canonical as well.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Repr {
+    Small(i64, i64),
+    Big(RBig),
+}
+
+/// Greatest common divisor (Stein's algorithm), with gcd(0, 0) = 0
+fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    if a == 0 || b == 0 {
+        return a | b;
+    }
+    let shift = (a | b).trailing_zeros();
+    a >>= a.trailing_zeros();
+    loop {
+        b >>= b.trailing_zeros();
+        if a > b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        b -= a;
+        if b == 0 {
+            return a << shift;
+        }
+    }
+}
+
+fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    if a <= u64::MAX as u128 && b <= u64::MAX as u128 {
+        return gcd_u64(a as u64, b as u64) as u128;
+    }
+    if a == 0 || b == 0 {
+        return a | b;
+    }
+    let shift = (a | b).trailing_zeros();
+    a >>= a.trailing_zeros();
+    loop {
+        b >>= b.trailing_zeros();
+        if a > b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        b -= a;
+        if b == 0 {
+            return a << shift;
+        }
+    }
+}
+
+/// Whether values that fit are stored inline, see the module documentation
+const FAST_PATH: bool = !cfg!(feature = "rbig-only");
+
+#[cfg(not(feature = "rbig-only"))]
+impl Rational {
+    pub const ZERO: Self = Self(Repr::Small(0, 1));
+    pub const ONE: Self = Self(Repr::Small(1, 1));
+    pub const NEG_ONE: Self = Self(Repr::Small(-1, 1));
+}
+
+#[cfg(feature = "rbig-only")]
+impl Rational {
+    pub const ZERO: Self = Self(Repr::Big(RBig::ZERO));
+    pub const ONE: Self = Self(Repr::Big(RBig::ONE));
+    pub const NEG_ONE: Self = Self(Repr::Big(RBig::NEG_ONE));
+}
+
+impl Rational {
+    /// The integer `n`, which must satisfy `|n| <= i64::MAX`
+    fn small_int(n: i64) -> Self {
+        if FAST_PATH {
+            Self(Repr::Small(n, 1))
+        } else {
+            Self(Repr::Big(RBig::from(n)))
+        }
+    }
+
+    /// Build `n / d` from `i128` parts, where `d > 0`, reducing and promoting as needed
+    fn from_i128_parts(n: i128, d: i128) -> Self {
+        debug_assert!(d > 0);
+        let g = gcd_u128(n.unsigned_abs(), d as u128) as i128;
+        let (n, d) = if g > 1 { (n / g, d / g) } else { (n, d) };
+        if FAST_PATH && -(i64::MAX as i128) <= n && n <= i64::MAX as i128 && d <= i64::MAX as i128 {
+            Self(Repr::Small(n as i64, d as i64))
+        } else {
+            Self(Repr::Big(RBig::from_parts(
+                Integer::from(n),
+                UBig::from(d as u128),
+            )))
+        }
+    }
+
+    /// Build a `Rational` from a `RBig`, demoting it if it fits
+    fn from_big(r: RBig) -> Self {
+        match (i64::try_from(r.numerator()), i64::try_from(r.denominator())) {
+            (Ok(n), Ok(d)) if FAST_PATH && n != i64::MIN => Self(Repr::Small(n, d)),
+            _ => Self(Repr::Big(r)),
+        }
+    }
+
+    /// Convert to a `dashu` rational
+    pub fn to_big(&self) -> RBig {
+        match &self.0 {
+            Repr::Small(n, d) => RBig::from_parts(Integer::from(*n), UBig::from(*d as u64)),
+            Repr::Big(r) => r.clone(),
+        }
+    }
+
+    /// Is the value stored inline, i.e. its numerator and denominator fit in an `i64`?
+    pub fn is_small(&self) -> bool {
+        matches!(self.0, Repr::Small(_, _))
+    }
+
+    /// Create a rational number from a signed numerator and a signed denominator
+    pub fn from_parts_signed(numerator: Integer, denominator: Integer) -> Self {
+        Self::from_big(RBig::from_parts_signed(numerator, denominator))
+    }
+
+    /// Get the numerator of the reduced fraction
+    pub fn numerator(&self) -> Integer {
+        match &self.0 {
+            Repr::Small(n, _) => Integer::from(*n),
+            Repr::Big(r) => r.numerator().clone(),
+        }
+    }
+
+    /// Get the (positive) denominator of the reduced fraction
+    pub fn denominator(&self) -> Integer {
+        match &self.0 {
+            Repr::Small(_, d) => Integer::from(*d),
+            Repr::Big(r) => Integer::from(r.denominator().clone()),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match &self.0 {
+            Repr::Small(n, _) => *n == 0,
+            Repr::Big(r) => !FAST_PATH && r.is_zero(),
+        }
+    }
+
+    pub fn is_one(&self) -> bool {
+        match &self.0 {
+            Repr::Small(n, d) => *n == 1 && *d == 1,
+            Repr::Big(r) => !FAST_PATH && r.is_one(),
+        }
+    }
+
+    /// Is the value an integer?
+    pub fn is_int(&self) -> bool {
+        match &self.0 {
+            Repr::Small(_, d) => *d == 1,
+            Repr::Big(r) => r.is_int(),
+        }
+    }
+
+    /// Is the value strictly negative?
+    pub fn is_negative(&self) -> bool {
+        match &self.0 {
+            Repr::Small(n, _) => *n < 0,
+            Repr::Big(r) => r < &RBig::ZERO,
+        }
+    }
+
+    /// Absolute value
+    pub fn abs(self) -> Self {
+        if self.is_negative() { -self } else { self }
+    }
+
+    /// Largest integer less than or equal to `self`
+    pub fn floor(&self) -> Integer {
+        match &self.0 {
+            Repr::Small(n, d) => Integer::from(n.div_euclid(*d)),
+            Repr::Big(r) => r.floor(),
+        }
+    }
+
+    /// Smallest integer greater than or equal to `self`
+    pub fn ceil(&self) -> Integer {
+        match &self.0 {
+            Repr::Small(n, d) => Integer::from(-((-n).div_euclid(*d))),
+            Repr::Big(r) => r.ceil(),
+        }
+    }
+
+    fn add_ref(&self, rhs: &Self) -> Self {
+        match (&self.0, &rhs.0) {
+            (Repr::Small(n1, 1), Repr::Small(n2, 1)) => match n1.checked_add(*n2) {
+                Some(n) if n != i64::MIN => Self::small_int(n),
+                _ => Self::from_i128_parts(*n1 as i128 + *n2 as i128, 1),
+            },
+            (Repr::Small(n1, d1), Repr::Small(n2, d2)) => {
+                let (n1, d1, n2, d2) = (*n1 as i128, *d1 as i128, *n2 as i128, *d2 as i128);
+                if d1 == d2 {
+                    Self::from_i128_parts(n1 + n2, d1)
+                } else {
+                    Self::from_i128_parts(n1 * d2 + n2 * d1, d1 * d2)
+                }
+            }
+            _ => Self::from_big(self.to_big() + rhs.to_big()),
+        }
+    }
+
+    fn mul_ref(&self, rhs: &Self) -> Self {
+        match (&self.0, &rhs.0) {
+            (Repr::Small(n1, 1), Repr::Small(n2, 1)) => match n1.checked_mul(*n2) {
+                Some(n) if n != i64::MIN => Self::small_int(n),
+                _ => Self::from_i128_parts(*n1 as i128 * *n2 as i128, 1),
+            },
+            (Repr::Small(n1, d1), Repr::Small(n2, d2)) => {
+                Self::from_i128_parts(*n1 as i128 * *n2 as i128, *d1 as i128 * *d2 as i128)
+            }
+            _ => Self::from_big(self.to_big() * rhs.to_big()),
+        }
+    }
+
+    fn div_ref(&self, rhs: &Self) -> Self {
+        match (&self.0, &rhs.0) {
+            (_, Repr::Small(0, _)) => panic!("attempt to divide by zero"),
+            (Repr::Small(n1, d1), Repr::Small(n2, d2)) => {
+                let n = *n1 as i128 * *d2 as i128;
+                let d = *d1 as i128 * *n2 as i128;
+                if d < 0 {
+                    Self::from_i128_parts(-n, -d)
+                } else {
+                    Self::from_i128_parts(n, d)
+                }
+            }
+            _ => Self::from_big(self.to_big() / rhs.to_big()),
+        }
+    }
+
+    fn neg_ref(&self) -> Self {
+        match &self.0 {
+            // -i64::MAX..=i64::MAX is closed under negation
+            Repr::Small(n, d) => Self(Repr::Small(-n, *d)),
+            Repr::Big(r) => Self::from_big(-r),
+        }
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::Small(n, 1) => write!(f, "{n}"),
+            Repr::Small(n, d) => write!(f, "{n}/{d}"),
+            Repr::Big(r) => write!(f, "{r}"),
+        }
+    }
+}
+
+impl fmt::Debug for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::Small(n, d) => write!(f, "{n} / {d}"),
+            Repr::Big(r) => write!(f, "{r:?}"),
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (&self.0, &other.0) {
+            (Repr::Small(n1, 1), Repr::Small(n2, 1)) => n1.cmp(n2),
+            (Repr::Small(n1, d1), Repr::Small(n2, d2)) => {
+                (*n1 as i128 * *d2 as i128).cmp(&(*n2 as i128 * *d1 as i128))
+            }
+            _ => self.to_big().cmp(&other.to_big()),
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+macro_rules! impl_from_signed {
+    ($($t:ty)*) => {$(
+        impl From<$t> for Rational {
+            fn from(value: $t) -> Self {
+                Self::from_i128_parts(value as i128, 1)
+            }
+        }
+    )*};
+}
+impl_from_signed!(i8 i16 i32 i64 isize u8 u16 u32 u64 usize);
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        Self::from_big(RBig::from(value))
+    }
+}
+
+impl From<Integer> for Rational {
+    fn from(value: Integer) -> Self {
+        match i64::try_from(&value) {
+            Ok(n) if FAST_PATH && n != i64::MIN => Self::small_int(n),
+            _ => Self(Repr::Big(RBig::from(value))),
+        }
+    }
+}
+
+impl From<RBig> for Rational {
+    fn from(value: RBig) -> Self {
+        Self::from_big(value)
+    }
+}
+
+impl From<Rational> for RBig {
+    fn from(value: Rational) -> Self {
+        match value.0 {
+            Repr::Big(r) => r,
+            small => Rational(small).to_big(),
+        }
+    }
+}
+
+/// Implement the by-value and by-reference variants of an arithmetic operator and its assigning
+/// variant in terms of a method on references
+macro_rules! impl_binop {
+    ($Op:ident, $op:ident, $OpAssign:ident, $op_assign:ident, $method:ident) => {
+        impl $Op<&Rational> for &Rational {
+            type Output = Rational;
+            fn $op(self, rhs: &Rational) -> Rational {
+                self.$method(rhs)
+            }
+        }
+
+        impl $Op<Rational> for &Rational {
+            type Output = Rational;
+            fn $op(self, rhs: Rational) -> Rational {
+                self.$method(&rhs)
+            }
+        }
+
+        impl $Op<&Rational> for Rational {
+            type Output = Rational;
+            fn $op(self, rhs: &Rational) -> Rational {
+                self.$method(rhs)
+            }
+        }
+
+        impl $Op<Rational> for Rational {
+            type Output = Rational;
+            fn $op(self, rhs: Rational) -> Rational {
+                self.$method(&rhs)
+            }
+        }
+
+        impl $OpAssign<&Rational> for Rational {
+            fn $op_assign(&mut self, rhs: &Rational) {
+                *self = self.$method(rhs);
+            }
+        }
+
+        impl $OpAssign<Rational> for Rational {
+            fn $op_assign(&mut self, rhs: Rational) {
+                *self = self.$method(&rhs);
+            }
+        }
+    };
+}
+
+impl Rational {
+    fn sub_ref(&self, rhs: &Self) -> Self {
+        self.add_ref(&rhs.neg_ref())
+    }
+}
+
+impl_binop!(Add, add, AddAssign, add_assign, add_ref);
+impl_binop!(Sub, sub, SubAssign, sub_assign, sub_ref);
+impl_binop!(Mul, mul, MulAssign, mul_assign, mul_ref);
+impl_binop!(Div, div, DivAssign, div_assign, div_ref);
+
+impl Neg for Rational {
+    type Output = Rational;
+    fn neg(self) -> Rational {
+        self.neg_ref()
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+    fn neg(self) -> Rational {
+        self.neg_ref()
+    }
+}
+
+impl Sum for Rational {
+    fn sum<I: Iterator<Item = Rational>>(iter: I) -> Rational {
+        iter.fold(Rational::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<'a> Sum<&'a Rational> for Rational {
+    fn sum<I: Iterator<Item = &'a Rational>>(iter: I) -> Rational {
+        iter.fold(Rational::ZERO, |acc, x| acc + x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn q(n: i64, d: i64) -> Rational {
+        Rational::from(n) / Rational::from(d)
+    }
+
+    #[test]
+    fn test_small_arith() {
+        assert_eq!(q(1, 2) + q(1, 3), q(5, 6));
+        assert_eq!(q(1, 2) - q(1, 2), Rational::ZERO);
+        assert_eq!(q(2, 3) * q(3, 4), q(1, 2));
+        assert_eq!(q(2, 3) / q(-4, 3), q(-1, 2));
+        assert_eq!(-q(5, 7), q(-5, 7));
+        assert!(q(1, 3) < q(1, 2));
+        assert!(q(-1, 3) > q(-1, 2));
+        assert!((q(1, 2) + q(1, 2)).is_one());
+        assert!(q(6, 3).is_int());
+        assert_eq!(q(6, 4).numerator(), Integer::from(3));
+        assert_eq!(q(6, 4).denominator(), Integer::from(2));
+        assert_eq!(q(-6, 4).abs(), q(3, 2));
+    }
+
+    #[test]
+    fn test_display_matches_dashu() {
+        for (n, d) in [(0, 1), (5, 1), (-10, 1), (3, 4), (-5, 10)] {
+            let r = q(n, d);
+            assert_eq!(r.to_string(), r.to_big().to_string());
+        }
+        let big = Rational::from(i64::MAX) * Rational::from(4);
+        assert_eq!(big.to_string(), big.to_big().to_string());
+    }
+
+    #[test]
+    fn test_floor_ceil() {
+        assert_eq!(q(7, 2).floor(), Integer::from(3));
+        assert_eq!(q(7, 2).ceil(), Integer::from(4));
+        assert_eq!(q(-7, 2).floor(), Integer::from(-4));
+        assert_eq!(q(-7, 2).ceil(), Integer::from(-3));
+        assert_eq!(q(4, 1).floor(), Integer::from(4));
+        assert_eq!(q(4, 1).ceil(), Integer::from(4));
+    }
+
+    #[test]
+    #[cfg(not(feature = "rbig-only"))]
+    fn test_promotion_and_demotion() {
+        let max = Rational::from(i64::MAX);
+        assert!(max.is_small());
+        let over = &max + Rational::ONE;
+        assert!(!over.is_small());
+        assert_eq!(over.to_big(), RBig::from(i64::MAX) + RBig::ONE);
+        let back = over - Rational::ONE;
+        assert!(back.is_small());
+        assert_eq!(back, max);
+
+        // i64::MIN is not stored inline, so that negation never overflows
+        let min = Rational::from(i64::MIN);
+        assert!(!min.is_small());
+        assert_eq!(-(-min.clone()), min);
+
+        // denominators promote too
+        let tiny = Rational::ONE / (&max * &max);
+        assert!(!tiny.is_small());
+        assert!((tiny * &max * &max).is_one());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_divide_by_zero() {
+        let _ = Rational::ONE / Rational::ZERO;
+    }
+
+    fn arb_rational() -> impl Strategy<Value = Rational> {
+        prop_oneof![
+            (-20i64..20, 1i64..20).prop_map(|(n, d)| q(n, d)),
+            (any::<i64>(), 1i64..1000).prop_map(|(n, d)| q(n, d)),
+            (any::<i64>(), any::<i64>())
+                .prop_filter("non-zero denominator", |(_, d)| *d != 0)
+                .prop_map(|(n, d)| q(n, d)),
+        ]
+    }
+
+    proptest! {
+        /// The fast path agrees with `RBig` arithmetic and keeps the representation canonical
+        #[test]
+        fn rational_agrees_with_rbig(a in arb_rational(), b in arb_rational()) {
+            let (ba, bb) = (a.to_big(), b.to_big());
+            let results = [
+                (&a + &b, &ba + &bb),
+                (&a - &b, &ba - &bb),
+                (&a * &b, &ba * &bb),
+                (-&a, -&ba),
+            ];
+            for (r, br) in results {
+                prop_assert_eq!(r.to_big(), br.clone());
+                prop_assert_eq!(r, Rational::from(br));
+            }
+            if !b.is_zero() {
+                prop_assert_eq!((&a / &b).to_big(), &ba / &bb);
+            }
+            prop_assert_eq!(a.cmp(&b), ba.cmp(&bb));
+            prop_assert_eq!(a.floor(), ba.floor());
+            prop_assert_eq!(a.ceil(), ba.ceil());
+        }
+    }
+}
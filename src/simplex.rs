@@ -0,0 +1,838 @@
+//! A general simplex solver for linear arithmetic over the rationals, after Dutertre and de Moura,
+//! "A Fast Linear-Arithmetic Solver for DPLL(T)" (CAV 2006).
+//!
+//! Every assertion `a_1 x_1 + ... + a_n x_n + c rel 0` becomes a bound on a single variable: on
+//! `x_i` itself if `a_i` is the only non-zero coefficient, and otherwise on a slack variable
+//! `s = (a_1 x_1 + ... + a_n x_n) / a_k`, where `a_k` is the first non-zero coefficient, so that
+//! parallel assertions share a slack. The definitions of the slacks form a tableau of equalities
+//! `x_b = sum_j t_bj x_j`, which define the *basic* variables `x_b` in terms of the *non-basic*
+//! ones. Non-basic variables always take values within their bounds, and `check` repairs the
+//! basic variables that are out of bounds by pivoting, choosing variables by Bland's rule so that
+//! it terminates. When a basic variable cannot be repaired, its row and the bounds of the
+//! variables in it give a Farkas certificate, see [`crate::certificate`].
+//!
+//! Strict bounds are handled by computing with values `c + k d`, where `d` is a positive
+//! infinitesimal, which is replaced by a small enough rational when building a model.
+//! Disequalities are decided separately after the other relations, as in [`crate::fme`].
+//!
+//! Bounds are asserted incrementally, and `pop` restores the bounds of the matching `push`. Since
+//! this only relaxes bounds, the tableau and the values of the variables remain valid.
+
+use crate::certificate::{Combination, UnsatCertificate};
+use crate::lin_expr::LinExpr;
+use crate::lin_rel::{Constraint, LinRel};
+use crate::lin_sys::LinSys;
+use crate::model::Model;
+use crate::solver::{SatResult, Solver};
+use crate::space::{Space, SpaceError};
+use crate::types::Rational;
+use crate::witness;
+use std::collections::HashMap;
+
+/// A value `c + k d`, where `d` is a positive infinitesimal. Values are ordered
+/// lexicographically.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct DeltaRational {
+    c: Rational,
+    k: Rational,
+}
+
+impl DeltaRational {
+    fn new(c: Rational, k: Rational) -> Self {
+        Self { c, k }
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Self::new(&self.c + &other.c, &self.k + &other.k)
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        Self::new(&self.c - &other.c, &self.k - &other.k)
+    }
+
+    fn mul(&self, a: &Rational) -> Self {
+        Self::new(&self.c * a, &self.k * a)
+    }
+
+    /// The rational value for `d = delta`
+    fn eval(&self, delta: &Rational) -> Rational {
+        &self.c + &self.k * delta
+    }
+}
+
+/// A bound on a variable `v` from the assertion with the given index, which is
+/// `alpha (v - value) rel 0`
+#[derive(Debug, Clone)]
+struct VarBound {
+    value: DeltaRational,
+    index: usize,
+    alpha: Rational,
+}
+
+/// A row of the tableau, the non-zero coefficients `(j, t_j)` of its variables sorted by `j`
+type Row = Vec<(usize, Rational)>;
+
+/// The coefficient of `v_j` in `t`, if it is non-zero
+fn coeff(t: &Row, j: usize) -> Option<&Rational> {
+    t.binary_search_by_key(&j, |(k, _)| *k)
+        .ok()
+        .map(|k| &t[k].1)
+}
+
+/// `t + c u`, without the coefficients that cancel out
+fn add_scaled(t: &Row, c: &Rational, u: &Row) -> Row {
+    let mut sum = Row::with_capacity(t.len() + u.len());
+    let (mut t, mut u) = (t.iter().peekable(), u.iter().peekable());
+    loop {
+        let (j, a) = match (t.peek(), u.peek()) {
+            (Some((i, a)), Some((j, b))) if i == j => {
+                let (i, a) = (*i, a + c * b);
+                t.next();
+                u.next();
+                (i, a)
+            }
+            (Some((i, a)), Some((j, _))) if i < j => {
+                t.next();
+                (*i, a.clone())
+            }
+            (_, Some((j, b))) => {
+                u.next();
+                (*j, c * b)
+            }
+            (Some((i, a)), None) => {
+                t.next();
+                (*i, a.clone())
+            }
+            (None, None) => return sum,
+        };
+        if !a.is_zero() {
+            sum.push((j, a));
+        }
+    }
+}
+
+/// The state to restore when popping a scope
+#[derive(Debug, Clone)]
+struct Scope {
+    assertions: usize,
+    trail: usize,
+    conflict: Option<Combination>,
+}
+
+#[derive(Debug)]
+pub struct SimplexSolver {
+    state: SatResult,
+    system: LinSys,
+    // vars[i - 1] is the tableau variable of x_i, if it has one
+    vars: Vec<Option<usize>>,
+    // the slack variable of each normalized linear form, given by its non-zero terms
+    slacks: HashMap<Vec<(usize, Rational)>, usize>,
+    values: Vec<DeltaRational>,
+    lower: Vec<Option<VarBound>>,
+    upper: Vec<Option<VarBound>>,
+    // row_of[v] is the row defining v, if v is basic
+    row_of: Vec<Option<usize>>,
+    // each row `(b, t)` stands for `v_b = sum_j t_j v_j`, where `t` only has non-basic `v_j`
+    rows: Vec<(usize, Row)>,
+    // the bounds of a variable before they were changed, in order of change
+    trail: Vec<(usize, Option<VarBound>, Option<VarBound>)>,
+    scopes: Vec<Scope>,
+    // a contradictory combination of the bounds, found when asserting them
+    conflict: Option<Combination>,
+    model: Option<Model>,
+    certificate: Option<UnsatCertificate>,
+}
+
+impl SimplexSolver {
+    /// Create a fresh solver
+    pub fn new() -> Self {
+        Self::with_space(Space::new())
+    }
+
+    /// Create a fresh solver over the given space of variables
+    pub fn with_space(space: Space) -> Self {
+        Self {
+            state: SatResult::UNKNOWN,
+            system: LinSys::with_space(space),
+            vars: Vec::new(),
+            slacks: HashMap::new(),
+            values: Vec::new(),
+            lower: Vec::new(),
+            upper: Vec::new(),
+            row_of: Vec::new(),
+            rows: Vec::new(),
+            trail: Vec::new(),
+            scopes: Vec::new(),
+            conflict: None,
+            model: None,
+            certificate: None,
+        }
+    }
+
+    /// Get the space of variables of the solver
+    pub fn space(&self) -> &Space {
+        self.system.space()
+    }
+
+    /// Get the asserted relations
+    pub fn assertions(&self) -> &LinSys {
+        &self.system
+    }
+
+    /// Assert a new relation. If it ranges over more variables than the space has, the space is
+    /// grown, see `try_assert()` to reject such relations instead.
+    ///
+    /// ```
+    /// # use presburger::solver::SatResult;
+    /// # use presburger::lin_expr::LinExpr;
+    /// # use presburger::lin_rel::LinRel;
+    /// # use presburger::simplex::SimplexSolver;
+    /// # fn main () {
+    /// let mut solver = SimplexSolver::new();
+    /// // x_1 + x_2 <= 2, x_1 - x_2 < 0, 1 <= x_1
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![-2, 1, 1]).unwrap()));
+    /// solver.assert(LinRel::mk_lt(LinExpr::new(vec![0, 1, -1]).unwrap()));
+    /// solver.assert(LinRel::mk_le(LinExpr::new(vec![1, -1, 0]).unwrap()));
+    /// assert_eq!(solver.check(), SatResult::UNSAT);
+    /// let certificate = solver.unsat_certificate().unwrap();
+    /// assert!(certificate.verify(solver.assertions()).is_ok());
+    /// # }
+    /// ```
+    pub fn assert(&mut self, rel: LinRel) {
+        self.invalidate();
+        let index = self.system.len();
+        self.system.add_relation(rel);
+        let rel = &self.system.relations()[index];
+        if !rel.is_disequality() {
+            let rel = rel.clone();
+            self.assert_bounds(index, &rel);
+        }
+    }
+
+    /// Assert a new relation, checking that it belongs to the solver's space
+    pub fn try_assert(&mut self, rel: LinRel) -> Result<(), SpaceError> {
+        self.space().check_expr(rel.lhs())?;
+        self.assert(rel);
+        Ok(())
+    }
+
+    /// Open a new scope. The assertions made from now on are retracted by the matching `pop`.
+    pub fn push(&mut self) {
+        self.scopes.push(Scope {
+            assertions: self.system.len(),
+            trail: self.trail.len(),
+            conflict: self.conflict.clone(),
+        });
+    }
+
+    /// Close the `n` innermost scopes, retracting the assertions made since they were opened.
+    /// Variables added to the space in these scopes are kept.
+    ///
+    /// Panics if fewer than `n` scopes are open.
+    pub fn pop(&mut self, n: usize) {
+        let depth = self.scopes.len();
+        assert!(n <= depth, "cannot pop {n} scopes, only {depth} are open");
+        if n == 0 {
+            return;
+        }
+        self.invalidate();
+        let scope = self.scopes[depth - n].clone();
+        self.scopes.truncate(depth - n);
+        self.system.truncate(scope.assertions);
+        self.conflict = scope.conflict;
+        for (v, lower, upper) in self.trail.drain(scope.trail..).rev() {
+            self.lower[v] = lower;
+            self.upper[v] = upper;
+        }
+    }
+
+    /// Get the number of open scopes
+    pub fn num_scopes(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Reset the solver state and clear all assertions and scopes, keeping the space
+    pub fn reset(&mut self) {
+        *self = Self::with_space(self.space().clone());
+    }
+
+    /// Forget the result of the last `check`
+    fn invalidate(&mut self) {
+        self.state = SatResult::UNKNOWN;
+        self.model = None;
+        self.certificate = None;
+    }
+
+    /// A model of the assertions, if the last call to `check` returned `SAT`. Variables that are
+    /// not constrained by the assertions are assigned zero.
+    pub fn model(&self) -> Option<&Model> {
+        self.model.as_ref()
+    }
+
+    /// A certificate that the assertions are unsatisfiable, if the last call to `check` returned
+    /// `UNSAT`
+    pub fn unsat_certificate(&self) -> Option<&UnsatCertificate> {
+        self.certificate.as_ref()
+    }
+
+    /// Check satisfiablility of the assertions
+    pub fn check(&mut self) -> SatResult {
+        self.invalidate();
+        match self.decide() {
+            Ok(model) => {
+                self.state = SatResult::SAT;
+                self.model = Some(model);
+            }
+            Err(certificate) => {
+                self.state = SatResult::UNSAT;
+                self.certificate = Some(certificate);
+            }
+        }
+        self.state.clone()
+    }
+
+    /// Decide the assertions, returning a model or a certificate of unsatisfiability. Each
+    /// disequality `e != 0` is decided by checking `e < 0` and `-e < 0` in a new scope.
+    fn decide(&mut self) -> Result<Model, UnsatCertificate> {
+        self.repair().map_err(UnsatCertificate::Farkas)?;
+        let mut model = self.current_model();
+        let disequalities: Vec<(usize, LinRel)> = (self.system.relations().iter().enumerate())
+            .filter(|(_, r)| r.is_disequality())
+            .map(|(j, r)| (j, r.clone()))
+            .collect();
+        let nes: Vec<LinRel> = disequalities.iter().map(|(_, r)| r.clone()).collect();
+        for (k, (index, ne)) in disequalities.iter().enumerate() {
+            let witness = match self.decide_side(*index, ne.lhs().clone()) {
+                Ok(w) => w,
+                Err(below) => self.decide_side(*index, -ne.lhs()).map_err(|above| {
+                    UnsatCertificate::Disequality {
+                        index: *index,
+                        below,
+                        above,
+                    }
+                })?,
+            };
+            model = witness::avoid_hyperplanes(model, &witness, &nes[..=k]);
+        }
+        Ok(model)
+    }
+
+    /// Decide the relations other than disequalities together with `e < 0`, which stands for the
+    /// assertion with the given index
+    fn decide_side(&mut self, index: usize, e: LinExpr) -> Result<Model, Combination> {
+        self.push();
+        self.assert_bounds(index, &LinRel::mk_lt(e));
+        let result = self.repair().map(|_| self.current_model());
+        self.pop(1);
+        result
+    }
+
+    /// The tableau variable of `x_i`, which is created if needed
+    fn var_of(&mut self, i: usize) -> usize {
+        if self.vars.len() < i {
+            self.vars.resize(i, None);
+        }
+        match self.vars[i - 1] {
+            Some(v) => v,
+            None => {
+                let v = self.new_var();
+                self.vars[i - 1] = Some(v);
+                v
+            }
+        }
+    }
+
+    /// Create a non-basic variable with value zero and no bounds
+    fn new_var(&mut self) -> usize {
+        let v = self.values.len();
+        self.values.push(DeltaRational::default());
+        self.lower.push(None);
+        self.upper.push(None);
+        self.row_of.push(None);
+        v
+    }
+
+    /// The slack variable defined by `sum_i a_i x_i` for the non-zero terms `(i, a_i)` of `form`,
+    /// which is created if needed
+    fn slack_of(&mut self, form: Vec<(usize, Rational)>) -> usize {
+        if let Some(s) = self.slacks.get(&form) {
+            return *s;
+        }
+        let vars: Vec<(usize, Rational)> = (form.iter())
+            .map(|(i, a)| (self.var_of(*i), a.clone()))
+            .collect();
+        let s = self.new_var();
+        // substitute the rows of basic variables, so that the row only has non-basic ones
+        let mut t = Row::new();
+        let mut value = DeltaRational::default();
+        for (v, a) in vars {
+            value = value.add(&self.values[v].mul(&a));
+            t = match self.row_of[v] {
+                Some(r) => add_scaled(&t, &a, &self.rows[r].1),
+                None => add_scaled(&t, &a, &vec![(v, Rational::ONE)]),
+            };
+        }
+        self.values[s] = value;
+        self.row_of[s] = Some(self.rows.len());
+        self.rows.push((s, t));
+        self.slacks.insert(form, s);
+        s
+    }
+
+    /// Assert the bounds given by `rel`, which is not a disequality and stands for the assertion
+    /// with the given index
+    fn assert_bounds(&mut self, index: usize, rel: &LinRel) {
+        let support: Vec<usize> = rel.lhs().terms().map(|(i, _)| i).collect();
+        if support.is_empty() {
+            if rel.is_trivial_contradiction() && self.conflict.is_none() {
+                self.conflict = Some(Combination::unit(index));
+            }
+            return;
+        }
+        let alpha = rel.lhs().coeff_unchecked(support[0]).clone();
+        let v = if support.len() == 1 {
+            self.var_of(support[0])
+        } else {
+            self.slack_of(rel.lhs().terms().map(|(i, a)| (i, a / &alpha)).collect())
+        };
+        // rel is `alpha v + c rel 0`
+        let b = -rel.const_() / &alpha;
+        let bound = |k: i32| VarBound {
+            value: DeltaRational::new(b.clone(), Rational::from(k)),
+            index,
+            alpha: alpha.clone(),
+        };
+        match (rel.constraint(), !alpha.is_negative()) {
+            (Constraint::Eq, _) => {
+                self.assert_lower(v, bound(0));
+                self.assert_upper(v, bound(0));
+            }
+            (Constraint::Le, true) => self.assert_upper(v, bound(0)),
+            (Constraint::Le, false) => self.assert_lower(v, bound(0)),
+            (Constraint::Lt, true) => self.assert_upper(v, bound(-1)),
+            (Constraint::Lt, false) => self.assert_lower(v, bound(1)),
+            (Constraint::Ne, _) => unreachable!("disequalities are not bounds"),
+        }
+    }
+
+    /// Tighten the upper bound of `v`
+    fn assert_upper(&mut self, v: usize, bound: VarBound) {
+        if self.conflict.is_some()
+            || self.upper[v]
+                .as_ref()
+                .is_some_and(|u| u.value <= bound.value)
+        {
+            return;
+        }
+        self.trail
+            .push((v, self.lower[v].clone(), self.upper[v].clone()));
+        if let Some(l) = self.lower[v].as_ref().filter(|l| l.value > bound.value) {
+            let mut conflict = Combination::new();
+            add_lower(&mut conflict, l, &Rational::ONE);
+            add_upper(&mut conflict, &bound, &Rational::ONE);
+            self.conflict = Some(conflict);
+        } else if self.row_of[v].is_none() && self.values[v] > bound.value {
+            self.update(v, bound.value.clone());
+        }
+        self.upper[v] = Some(bound);
+    }
+
+    /// Tighten the lower bound of `v`
+    fn assert_lower(&mut self, v: usize, bound: VarBound) {
+        if self.conflict.is_some()
+            || self.lower[v]
+                .as_ref()
+                .is_some_and(|l| l.value >= bound.value)
+        {
+            return;
+        }
+        self.trail
+            .push((v, self.lower[v].clone(), self.upper[v].clone()));
+        if let Some(u) = self.upper[v].as_ref().filter(|u| u.value < bound.value) {
+            let mut conflict = Combination::new();
+            add_lower(&mut conflict, &bound, &Rational::ONE);
+            add_upper(&mut conflict, u, &Rational::ONE);
+            self.conflict = Some(conflict);
+        } else if self.row_of[v].is_none() && self.values[v] < bound.value {
+            self.update(v, bound.value.clone());
+        }
+        self.lower[v] = Some(bound);
+    }
+
+    /// Set the non-basic variable `v` to `value`, and update the basic variables
+    fn update(&mut self, v: usize, value: DeltaRational) {
+        let delta = value.sub(&self.values[v]);
+        for (b, t) in self.rows.iter() {
+            if let Some(a) = coeff(t, v) {
+                self.values[*b] = self.values[*b].add(&delta.mul(a));
+            }
+        }
+        self.values[v] = value;
+    }
+
+    /// Bring every basic variable within its bounds, or return a contradictory combination of the
+    /// assertions
+    fn repair(&mut self) -> Result<(), Combination> {
+        if let Some(conflict) = &self.conflict {
+            return Err(conflict.clone());
+        }
+        loop {
+            // the smallest basic variable out of its bounds, and whether it is below them
+            let Some((r, below)) = (0..self.rows.len())
+                .filter_map(|r| {
+                    let b = self.rows[r].0;
+                    let value = &self.values[b];
+                    if self.lower[b].as_ref().is_some_and(|l| *value < l.value) {
+                        Some((r, true))
+                    } else if self.upper[b].as_ref().is_some_and(|u| *value > u.value) {
+                        Some((r, false))
+                    } else {
+                        None
+                    }
+                })
+                .min_by_key(|(r, _)| self.rows[*r].0)
+            else {
+                return Ok(());
+            };
+            // the smallest non-basic variable that can move in the direction that repairs it
+            let t = &self.rows[r].1;
+            let can_increase =
+                |j: usize| (self.upper[j].as_ref()).is_none_or(|u| self.values[j] < u.value);
+            let can_decrease =
+                |j: usize| (self.lower[j].as_ref()).is_none_or(|l| self.values[j] > l.value);
+            let j = t
+                .iter()
+                .find(|(j, a)| {
+                    if a.is_negative() != below {
+                        can_increase(*j)
+                    } else {
+                        can_decrease(*j)
+                    }
+                })
+                .map(|(j, _)| *j);
+            let b = self.rows[r].0;
+            match j {
+                Some(j) => {
+                    let target = if below {
+                        self.lower[b].as_ref().unwrap().value.clone()
+                    } else {
+                        self.upper[b].as_ref().unwrap().value.clone()
+                    };
+                    self.pivot_and_update(r, j, target);
+                }
+                None => return Err(self.explain(r, below)),
+            }
+        }
+    }
+
+    /// The contradictory combination of the bounds of the variables of row `r`, whose basic
+    /// variable is below its lower bound (or above its upper bound) and cannot be repaired.
+    ///
+    /// For a basic variable `x_b = sum_j t_j x_j` below its lower bound `l_b`, this is
+    /// `(l_b - x_b) + sum_{t_j > 0} t_j (x_j - u_j) + sum_{t_j < 0} -t_j (l_j - x_j) <= 0`, whose
+    /// variables cancel out, leaving `l_b - sum_{t_j > 0} t_j u_j - sum_{t_j < 0} t_j l_j <= 0`,
+    /// which is false.
+    fn explain(&self, r: usize, below: bool) -> Combination {
+        let (b, t) = &self.rows[r];
+        let lower = |v: usize| self.lower[v].as_ref().expect("bound blocks repair");
+        let upper = |v: usize| self.upper[v].as_ref().expect("bound blocks repair");
+        let mut conflict = Combination::new();
+        if below {
+            add_lower(&mut conflict, lower(*b), &Rational::ONE);
+        } else {
+            add_upper(&mut conflict, upper(*b), &Rational::ONE);
+        }
+        for (j, a) in t.iter() {
+            if a.is_negative() != below {
+                add_upper(&mut conflict, upper(*j), &a.clone().abs());
+            } else {
+                add_lower(&mut conflict, lower(*j), &a.clone().abs());
+            }
+        }
+        conflict
+    }
+
+    /// Set the basic variable of row `r` to `value` by changing the non-basic variable `v_j`, and
+    /// swap them
+    fn pivot_and_update(&mut self, r: usize, j: usize, value: DeltaRational) {
+        let b = self.rows[r].0;
+        let a = coeff(&self.rows[r].1, j).expect("v_j is in row r");
+        let theta = value.sub(&self.values[b]).mul(&(Rational::ONE / a));
+        self.values[b] = value;
+        self.values[j] = self.values[j].add(&theta);
+        for (k, (b2, t)) in self.rows.iter().enumerate() {
+            if k == r {
+                continue;
+            }
+            if let Some(a) = coeff(t, j) {
+                self.values[*b2] = self.values[*b2].add(&theta.mul(a));
+            }
+        }
+        self.pivot(r, j);
+    }
+
+    /// Make the non-basic variable `v_j` basic in row `r`, and its basic variable non-basic
+    fn pivot(&mut self, r: usize, j: usize) {
+        // row r is left empty until it is replaced
+        let (b, mut t) = std::mem::take(&mut self.rows[r]);
+        // v_b = a v_j + sum_k t_k v_k gives v_j = (v_b - sum_k t_k v_k) / a
+        let position = |t: &Row, j: usize| t.binary_search_by_key(&j, |(k, _)| *k);
+        let (_, a) = t.remove(position(&t, j).expect("v_j is in row r"));
+        let m = -Rational::ONE / &a;
+        for (_, tk) in t.iter_mut() {
+            *tk *= &m;
+        }
+        // v_b is basic, so it is not in the row
+        let k = position(&t, b).unwrap_err();
+        t.insert(k, (b, Rational::ONE / a));
+        for (_, t2) in self.rows.iter_mut() {
+            if let Ok(k) = position(t2, j) {
+                let (_, c) = t2.remove(k);
+                *t2 = add_scaled(t2, &c, &t);
+            }
+        }
+        self.rows[r] = (j, t);
+        self.row_of[b] = None;
+        self.row_of[j] = Some(r);
+    }
+
+    /// The model given by the values of the variables, with the infinitesimal replaced by a
+    /// rational small enough that all bounds still hold
+    fn current_model(&self) -> Model {
+        let mut delta = Rational::ONE;
+        // lo <= hi must hold after replacing the infinitesimal
+        let mut fit = |lo: &DeltaRational, hi: &DeltaRational| {
+            if lo.c < hi.c && lo.k > hi.k {
+                let d = (&hi.c - &lo.c) / (&lo.k - &hi.k);
+                if d < delta {
+                    delta = d;
+                }
+            }
+        };
+        for (v, value) in self.values.iter().enumerate() {
+            if let Some(l) = &self.lower[v] {
+                fit(&l.value, value);
+            }
+            if let Some(u) = &self.upper[v] {
+                fit(value, &u.value);
+            }
+        }
+        let mut model = Model::zeros(self.space().dim());
+        for (i, v) in self.vars.iter().enumerate() {
+            if let Some(v) = v {
+                model.set(i + 1, self.values[*v].eval(&delta));
+            }
+        }
+        model
+    }
+}
+
+/// Add the lower bound `value <= v`, i.e. `-(alpha (v - value)) / alpha <= 0`, times `scale`
+fn add_lower(combination: &mut Combination, bound: &VarBound, scale: &Rational) {
+    combination.add_scaled(&Combination::unit(bound.index), &(-scale / &bound.alpha));
+}
+
+/// Add the upper bound `v <= value`, i.e. `alpha (v - value) / alpha <= 0`, times `scale`
+fn add_upper(combination: &mut Combination, bound: &VarBound, scale: &Rational) {
+    combination.add_scaled(&Combination::unit(bound.index), &(scale / &bound.alpha));
+}
+
+impl Default for SimplexSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Solver for SimplexSolver {
+    fn assertions(&self) -> &LinSys {
+        SimplexSolver::assertions(self)
+    }
+
+    fn assert(&mut self, rel: LinRel) {
+        SimplexSolver::assert(self, rel);
+    }
+
+    fn try_assert(&mut self, rel: LinRel) -> Result<(), SpaceError> {
+        SimplexSolver::try_assert(self, rel)
+    }
+
+    fn check(&mut self) -> SatResult {
+        SimplexSolver::check(self)
+    }
+
+    fn model(&self) -> Option<&Model> {
+        SimplexSolver::model(self)
+    }
+
+    fn unsat_certificate(&self) -> Option<&UnsatCertificate> {
+        SimplexSolver::unsat_certificate(self)
+    }
+
+    fn reset(&mut self) {
+        SimplexSolver::reset(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fme::FMESolver;
+    use crate::{eq, le};
+    use proptest::prelude::*;
+
+    /// Check `solver`, and that its model or certificate is valid
+    fn check_valid(solver: &mut SimplexSolver) -> SatResult {
+        let state = solver.check();
+        match state {
+            SatResult::SAT => {
+                let model = solver.model().expect("SAT result has a model");
+                assert_eq!(model.nvars(), solver.space().dim());
+                assert_eq!(solver.assertions().violations(model), Ok(vec![]));
+            }
+            SatResult::UNSAT => {
+                let certificate = solver
+                    .unsat_certificate()
+                    .expect("UNSAT result has a certificate");
+                assert_eq!(certificate.verify(solver.assertions()), Ok(()));
+            }
+            SatResult::UNKNOWN => panic!("simplex always decides"),
+        }
+        state
+    }
+
+    #[test]
+    fn test_delta_rational() {
+        let a = DeltaRational::new(Rational::ONE, Rational::NEG_ONE);
+        let b = DeltaRational::new(Rational::ONE, Rational::ZERO);
+        assert!(a < b);
+        assert!(b < DeltaRational::new(Rational::from(2), Rational::from(-5)));
+        assert_eq!(
+            a.sub(&b),
+            DeltaRational::new(Rational::ZERO, Rational::NEG_ONE)
+        );
+        assert_eq!(
+            a.add(&b).mul(&Rational::from(2)).eval(&Rational::ONE),
+            Rational::from(2)
+        );
+    }
+
+    #[test]
+    fn test_simplex() {
+        let mut solver = SimplexSolver::new();
+        // x1 + x2 <= 4, x1 - x2 <= 0, 1 <= x1
+        solver.assert(le!(-4, 1, 1));
+        solver.assert(le!(0, 1, -1));
+        solver.assert(le!(1, -1, 0));
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+        // x1 + x2 shares its slack with 2 x1 + 2 x2 = 8
+        solver.assert(eq!(-8, 2, 2));
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+        assert_eq!(solver.slacks.len(), 2);
+        let model = solver.model().unwrap();
+        assert_eq!(
+            model.value(1).unwrap() + model.value(2).unwrap(),
+            Rational::from(4)
+        );
+
+        // 3 < x1 contradicts x1 <= x2 and x1 + x2 = 4 through the tableau
+        solver.push();
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![3, -1, 0]).unwrap()));
+        assert_eq!(check_valid(&mut solver), SatResult::UNSAT);
+        solver.pop(1);
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+
+        // 2 < x1 as well, since x1 <= x2 and x1 + x2 <= 4 give x1 <= 2
+        solver.push();
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![2, -1, 0]).unwrap()));
+        assert_eq!(check_valid(&mut solver), SatResult::UNSAT);
+        assert_eq!(solver.unsat_certificate().unwrap().support(), vec![0, 1, 4]);
+        solver.pop(1);
+
+        // x1 < 2 forces a strict bound on x2
+        solver.assert(LinRel::mk_lt(LinExpr::new(vec![-2, 1, 0]).unwrap()));
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+        let model = solver.model().unwrap();
+        assert!(model.value(2).unwrap() > &Rational::from(2));
+
+        solver.reset();
+        assert!(solver.assertions().is_empty());
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+    }
+
+    #[test]
+    fn test_simplex_constants_and_disequalities() {
+        let mut solver = SimplexSolver::new();
+        // 0 <= x1 <= 1, x1 != 0, x1 != 1, x1 != 1/2
+        solver.assert(le!(0, -1));
+        solver.assert(le!(-1, 1));
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![0, 1]).unwrap()));
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![-1, 1]).unwrap()));
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![-1, 2]).unwrap()));
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+
+        // x1 = x2 and x1 != x2
+        solver.push();
+        solver.assert(eq!(0, 1, -1));
+        solver.assert(LinRel::mk_ne(LinExpr::new(vec![0, -1, 1]).unwrap()));
+        assert_eq!(check_valid(&mut solver), SatResult::UNSAT);
+        assert!(matches!(
+            solver.unsat_certificate(),
+            Some(UnsatCertificate::Disequality { index: 6, .. })
+        ));
+        solver.pop(1);
+
+        // 1 <= 0
+        solver.push();
+        solver.assert(le!(1, 0));
+        assert_eq!(check_valid(&mut solver), SatResult::UNSAT);
+        assert_eq!(solver.unsat_certificate().unwrap().support(), vec![5]);
+        solver.pop(1);
+        assert_eq!(check_valid(&mut solver), SatResult::SAT);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot pop 1 scopes, only 0 are open")]
+    fn test_simplex_pop_too_many() {
+        SimplexSolver::new().pop(1);
+    }
+
+    fn arb_relation(nvars: usize) -> impl Strategy<Value = LinRel> {
+        (prop::collection::vec(-3i32..=3, nvars + 1), 0..4).prop_map(|(coeffs, c)| {
+            let lhs = LinExpr::new(coeffs).unwrap();
+            match c {
+                0 => LinRel::mk_eq(lhs),
+                1 => LinRel::mk_lt(lhs),
+                2 => LinRel::mk_ne(lhs),
+                _ => LinRel::mk_le(lhs),
+            }
+        })
+    }
+
+    fn arb_system() -> impl Strategy<Value = Vec<LinRel>> {
+        (1usize..5).prop_flat_map(|n| prop::collection::vec(arb_relation(n), 1..8))
+    }
+
+    proptest! {
+        /// Simplex agrees with FME, with valid models and certificates, also when the
+        /// assertions are split into scopes
+        #[test]
+        fn simplex_agrees_with_fme(relations in arb_system(), split in 0usize..8) {
+            let mut fme = FMESolver::new();
+            let mut simplex = SimplexSolver::new();
+            let split = split.min(relations.len());
+            for (j, r) in relations.iter().enumerate() {
+                if j == split {
+                    prop_assert_eq!(check_valid(&mut simplex), fme.check());
+                    simplex.push();
+                    fme.push();
+                }
+                simplex.assert(r.clone());
+                fme.assert(r.clone());
+            }
+            prop_assert_eq!(check_valid(&mut simplex), fme.check());
+            simplex.pop(simplex.num_scopes());
+            fme.pop(fme.num_scopes());
+            prop_assert_eq!(check_valid(&mut simplex), fme.check());
+        }
+    }
+}
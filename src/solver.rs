@@ -0,0 +1,75 @@
+//! A common interface to the decision procedures for conjunctions of linear relations, so that
+//! callers can switch between [`crate::fme::FMESolver`] and [`crate::simplex::SimplexSolver`]
+//! over the rationals, or [`crate::omega::OmegaSolver`] and [`crate::branch_bound::BranchAndBound`]
+//! over the integers.
+
+use crate::certificate::UnsatCertificate;
+use crate::lin_rel::LinRel;
+use crate::lin_sys::LinSys;
+use crate::model::Model;
+use crate::space::SpaceError;
+
+/// The result of checking the satisfiability of a set of relations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SatResult {
+    /// `check` has not been called, or it gave up
+    UNKNOWN,
+    /// system of (in)equalities is satisfiable
+    SAT,
+    /// system of (in)equalities is unsatisfiable
+    UNSAT,
+}
+
+/// A solver for conjunctions of linear relations
+///
+/// ```
+/// # use presburger::branch_bound::BranchAndBound;
+/// # use presburger::fme::FMESolver;
+/// # use presburger::lin_expr::LinExpr;
+/// # use presburger::lin_rel::LinRel;
+/// # use presburger::omega::OmegaSolver;
+/// # use presburger::simplex::SimplexSolver;
+/// # use presburger::solver::{SatResult, Solver};
+/// # fn main () {
+/// let backends: Vec<Box<dyn Solver>> = vec![Box::new(FMESolver::new()), Box::new(SimplexSolver::new())];
+/// for mut solver in backends {
+///     // x_1 < x_2, x_2 <= 0
+///     solver.assert(LinRel::mk_lt(LinExpr::new(vec![0, 1, -1]).unwrap()));
+///     solver.assert(LinRel::mk_le(LinExpr::new(vec![0, 0, 1]).unwrap()));
+///     assert_eq!(solver.check(), SatResult::SAT);
+///     assert!(solver.model().is_some());
+///     solver.reset();
+/// }
+///
+/// // 2 x_1 = 1 has rational solutions but no integer ones
+/// let backends: Vec<Box<dyn Solver>> = vec![Box::new(OmegaSolver::new()), Box::new(BranchAndBound::new())];
+/// for mut solver in backends {
+///     solver.assert(LinRel::mk_eq(LinExpr::new(vec![-1, 2]).unwrap()));
+///     assert_eq!(solver.check(), SatResult::UNSAT);
+///     assert!(solver.unsat_certificate().is_none());
+/// }
+/// # }
+/// ```
+pub trait Solver {
+    /// Get the asserted relations
+    fn assertions(&self) -> &LinSys;
+
+    /// Assert a new relation. The space grows if it ranges over more variables.
+    fn assert(&mut self, rel: LinRel);
+
+    /// Assert a new relation, checking that it belongs to the solver's space
+    fn try_assert(&mut self, rel: LinRel) -> Result<(), SpaceError>;
+
+    /// Check satisfiability of the assertions
+    fn check(&mut self) -> SatResult;
+
+    /// A model of the assertions, if the last call to `check` returned `SAT`
+    fn model(&self) -> Option<&Model>;
+
+    /// A certificate that the assertions are unsatisfiable, if the last call to `check` returned
+    /// `UNSAT`. The solvers over the integers give none.
+    fn unsat_certificate(&self) -> Option<&UnsatCertificate>;
+
+    /// Clear all assertions, keeping the space
+    fn reset(&mut self);
+}
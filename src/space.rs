@@ -0,0 +1,245 @@
+//! Variable spaces: ordered lists of named variables giving meaning to the positional
+//! coefficients of [`crate::lin_expr::LinExpr`].
+//!
+//! The variable with index `i` (> 0) in an expression is the `i`-th variable of the space. An
+//! expression, relation, or system belongs to a space if it ranges over at most `dim()`
+//! variables.
+
+use crate::lin_expr::LinExpr;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpaceError {
+    /// A variable name occurs more than once in a space
+    DuplicateName(String),
+    /// A variable name is not part of the space
+    UnknownName(String),
+    /// An expression ranges over more variables than the space has
+    DimensionMismatch { nvars: usize, dim: usize },
+    /// Two spaces that were expected to be the same differ
+    SpaceMismatch,
+}
+
+impl fmt::Display for SpaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateName(name) => {
+                write!(f, "Duplicate variable name {name}")
+            }
+            Self::UnknownName(name) => {
+                write!(f, "Unknown variable name {name}")
+            }
+            Self::DimensionMismatch { nvars, dim } => {
+                write!(
+                    f,
+                    "Expression over {nvars} variables in a space of dimension {dim}"
+                )
+            }
+            Self::SpaceMismatch => {
+                write!(f, "Variable spaces differ")
+            }
+        }
+    }
+}
+
+impl Error for SpaceError {}
+
+/// An ordered list of distinct variable names
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Space {
+    names: Vec<String>,
+}
+
+impl fmt::Display for Space {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.names.join(", "))
+    }
+}
+
+impl Space {
+    /// Create an empty space
+    pub fn new() -> Self {
+        Self { names: Vec::new() }
+    }
+
+    /// Create a space of `nvars` variables with the default names `x_1, ..., x_n`
+    pub fn anonymous(nvars: usize) -> Self {
+        Self {
+            names: (1..=nvars).map(Self::default_name).collect(),
+        }
+    }
+
+    /// Create a space from a list of distinct names
+    ///
+    /// ```
+    /// # use presburger::space::*;
+    /// # fn main () {
+    /// let space = Space::from_names(["x", "y"]).unwrap();
+    /// assert_eq!(space.index_of("y"), Some(2));
+    /// assert!(Space::from_names(["x", "x"]).is_err());
+    /// # }
+    /// ```
+    pub fn from_names<S: Into<String>>(
+        names: impl IntoIterator<Item = S>,
+    ) -> Result<Self, SpaceError> {
+        let mut space = Self::new();
+        for name in names {
+            space.add_var(name)?;
+        }
+        Ok(space)
+    }
+
+    /// Default name of the variable with index `i`
+    pub fn default_name(i: usize) -> String {
+        format!("x_{i}")
+    }
+
+    /// Number of variables in the space
+    pub fn dim(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Get the names of all variables, in order
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Name of the variable with index `i` (> 0), if it is present
+    pub fn name(&self, i: usize) -> Option<&str> {
+        if i == 0 {
+            None
+        } else {
+            self.names.get(i - 1).map(|s| s.as_str())
+        }
+    }
+
+    /// Index of the variable called `name`, if it is present
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name).map(|i| i + 1)
+    }
+
+    /// Add a new variable at index `dim() + 1` and return its index
+    pub fn add_var(&mut self, name: impl Into<String>) -> Result<usize, SpaceError> {
+        let name = name.into();
+        if self.index_of(&name).is_some() {
+            return Err(SpaceError::DuplicateName(name));
+        }
+        self.names.push(name);
+        Ok(self.names.len())
+    }
+
+    /// Return the index of `name`, adding it as a new variable if it is not yet present
+    pub fn intern(&mut self, name: &str) -> usize {
+        match self.index_of(name) {
+            Some(i) => i,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len()
+            }
+        }
+    }
+
+    /// Grow the space with default-named variables until it has at least `nvars` variables
+    pub fn extend_to(&mut self, nvars: usize) {
+        while self.dim() < nvars {
+            let i = self.dim() + 1;
+            let name = Self::default_name(i);
+            // default names can only collide with user names chosen to look like them
+            let name = if self.index_of(&name).is_some() {
+                format!("{name}'")
+            } else {
+                name
+            };
+            self.names.push(name);
+        }
+    }
+
+    /// The space of the variables other than those with indices in `vars`, in order
+    pub fn remove_vars(&self, vars: &[usize]) -> Self {
+        Self {
+            names: self
+                .names
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !vars.contains(&(i + 1)))
+                .map(|(_, n)| n.clone())
+                .collect(),
+        }
+    }
+
+    /// Check that `expr` ranges over variables of this space.
+    ///
+    /// Trailing zero coefficients beyond the dimension of the space are allowed.
+    pub fn check_expr(&self, expr: &LinExpr) -> Result<(), SpaceError> {
+        let nvars = expr.nvars();
+        if expr.terms().any(|(i, _)| i > self.dim()) {
+            Err(SpaceError::DimensionMismatch {
+                nvars,
+                dim: self.dim(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check that `other` is the same space as `self`
+    pub fn check_same(&self, other: &Space) -> Result<(), SpaceError> {
+        if self == other {
+            Ok(())
+        } else {
+            Err(SpaceError::SpaceMismatch)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_space_basic_api() {
+        let mut space = Space::new();
+        assert_eq!(space.dim(), 0);
+        assert_eq!(space.add_var("x"), Ok(1));
+        assert_eq!(space.add_var("y"), Ok(2));
+        assert_eq!(
+            space.add_var("x"),
+            Err(SpaceError::DuplicateName("x".to_string()))
+        );
+        assert_eq!(space.intern("y"), 2);
+        assert_eq!(space.intern("z"), 3);
+        assert_eq!(space.dim(), 3);
+        assert_eq!(space.name(1), Some("x"));
+        assert_eq!(space.name(0), None);
+        assert_eq!(space.name(4), None);
+        assert_eq!(space.to_string(), "[x, y, z]");
+    }
+
+    #[test]
+    fn test_anonymous_and_extend() {
+        let space = Space::anonymous(2);
+        assert_eq!(space.names(), &["x_1", "x_2"]);
+
+        let mut space = Space::from_names(["x_2"]).unwrap();
+        space.extend_to(3);
+        assert_eq!(space.names(), &["x_2", "x_2'", "x_3"]);
+    }
+
+    #[test]
+    fn test_check_expr() {
+        let space = Space::from_names(["x", "y"]).unwrap();
+        assert!(space.check_expr(&LinExpr::new(vec![1, 2]).unwrap()).is_ok());
+        assert!(
+            space
+                .check_expr(&LinExpr::new(vec![1, 2, 3, 0]).unwrap())
+                .is_ok()
+        );
+        assert_eq!(
+            space.check_expr(&LinExpr::new(vec![1, 2, 3, 4]).unwrap()),
+            Err(SpaceError::DimensionMismatch { nvars: 3, dim: 2 })
+        );
+        assert!(space.check_same(&space.clone()).is_ok());
+        assert!(space.check_same(&Space::anonymous(2)).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::types::Integer;
-use dashu::base::RemEuclid;
+use dashu::base::{Abs, Gcd, RemEuclid};
 use std::ops::Rem;
 
 /// Symmetric modulo.
@@ -38,6 +38,26 @@ pub fn div_floor(num: Integer, den: Integer) -> Integer {
     }
 }
 
+/// Non-negative greatest common divisor of `a` and `b`, with gcd(0, 0) = 0
+pub fn gcd(a: &Integer, b: &Integer) -> Integer {
+    if a == &Integer::ZERO {
+        b.clone().abs()
+    } else if b == &Integer::ZERO {
+        a.clone().abs()
+    } else {
+        Integer::from(a.gcd(b))
+    }
+}
+
+/// Non-negative least common multiple of `a` and `b`, with lcm(a, 0) = 0
+pub fn lcm(a: &Integer, b: &Integer) -> Integer {
+    if a == &Integer::ZERO || b == &Integer::ZERO {
+        Integer::ZERO
+    } else {
+        (a / gcd(a, b) * b).abs()
+    }
+}
+
 #[cfg(test)]
 mod test_sym_mod {
     use super::*;
@@ -57,6 +77,18 @@ mod test_sym_mod {
         assert_eq!(symmod_alt(0.into(), 4.into()), 0.into());
     }
 
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(&Integer::from(-4), &Integer::from(6)), Integer::from(2));
+        assert_eq!(gcd(&Integer::ZERO, &Integer::from(-3)), Integer::from(3));
+        assert_eq!(gcd(&Integer::ZERO, &Integer::ZERO), Integer::ZERO);
+        assert_eq!(
+            lcm(&Integer::from(-4), &Integer::from(6)),
+            Integer::from(12)
+        );
+        assert_eq!(lcm(&Integer::from(4), &Integer::ZERO), Integer::ZERO);
+    }
+
     // proptest found bug: case a % b == b // 2 (int division) exposes the issue that
     // the test a < b/2 needs to be done over Q and not with integer division on the
     // RHS.
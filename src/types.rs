@@ -1 +1,29 @@
-pub use dashu::{Integer, Rational, ibig, rbig};
+pub use crate::rational::Rational;
+pub use dashu::{Integer, ibig};
+
+#[doc(hidden)]
+pub use dashu as __dashu;
+
+/// Create a [`Rational`] from a literal, with the same syntax as `dashu::rbig!`.
+///
+/// ```
+/// # use presburger::types::{Rational, rbig};
+/// assert_eq!(rbig!(-6 / 4), Rational::from(-3) / Rational::from(2));
+/// ```
+#[macro_export]
+macro_rules! rbig {
+    ($($t:tt)+) => {
+        $crate::types::Rational::from($crate::types::__dashu::rbig!($($t)+))
+    };
+}
+
+pub use crate::rbig;
+
+/// Domain of the variables of a formula, relation, or system
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Domain {
+    /// Variables range over the integers
+    Integer,
+    /// Variables range over the rationals
+    Rational,
+}
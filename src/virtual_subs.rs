@@ -0,0 +1,354 @@
+//! Quantifier elimination for linear arithmetic over the rationals by the virtual substitution of
+//! Loos and Weispfenning.
+//!
+//! Quantifiers are eliminated innermost first, and `forall x. p` is treated as
+//! `~(exists x. ~p)`. For a quantifier-free `p` in NNF with relations `e rel 0`, the satisfying
+//! values of `x` form a finite union of intervals, and each interval that is not unbounded below
+//! starts at a zero `s` of a relation that becomes true there:
+//!
+//! - `s` for `a x + t = 0` and for a lower bound `a x + t <= 0` with `a < 0`,
+//! - `s + eps` for `a x + t != 0` and for a lower bound `a x + t < 0` with `a < 0`,
+//!
+//! where `s = -t / a` and `eps` is a positive infinitesimal. Then `exists x. p` is equivalent to
+//! the disjunction of `p[-inf // x]` and of `p[tp // x]` for the test points `tp` above. The
+//! virtual substitution `[-inf // x]` and `[s + eps // x]` is exact, e.g. `a (s + eps) + t < 0`
+//! becomes `a s + t < 0` if `a > 0`, and `a s + t <= 0` if `a < 0`.
+//!
+//! Quantifiers over logical variables are expanded to `p[T/P] \/ p[F/P]`. Divisibility atoms have
+//! no counterpart over the rationals and are reported as errors.
+
+use crate::ast::{Atom, Formula, Term, Var};
+use crate::lin_expr::LinExpr;
+use crate::lin_rel::{Constraint, LinRel};
+use crate::lower::{LowerError, lower_atom, lower_negated_atom};
+use crate::qe::{self, Literal, to_term};
+use crate::space::Space;
+use crate::types::Rational;
+
+/// A quantifier-free formula in NNF over linear relations
+type Qf = qe::Qf<LinRel>;
+
+/// The relation `r`, or its truth value if it is constant
+fn rel(r: LinRel) -> Qf {
+    if r.lhs().is_const() {
+        Qf::Bool(r.is_trivial())
+    } else {
+        Qf::Lit(r)
+    }
+}
+
+impl Literal for LinRel {
+    type Error = LowerError;
+
+    fn from_atom(a: &Atom, negated: bool, space: &mut Space) -> Result<Qf, LowerError> {
+        if negated {
+            Ok(rel(lower_negated_atom(a, space)?))
+        } else {
+            Ok(rel(lower_atom(a, space)?))
+        }
+    }
+
+    fn negate(&self) -> LinRel {
+        match self.constraint() {
+            Constraint::Le => LinRel::mk_lt(-self.lhs()),
+            Constraint::Lt => LinRel::mk_le(-self.lhs()),
+            Constraint::Eq => LinRel::mk_ne(self.lhs().clone()),
+            Constraint::Ne => LinRel::mk_eq(self.lhs().clone()),
+        }
+    }
+
+    fn extend_to(self, nvars: usize) -> LinRel {
+        LinRel::extend_to(self, nvars)
+    }
+
+    fn to_formula(&self, space: &Space) -> Formula {
+        let e = to_term(self.lhs(), space);
+        match self.constraint() {
+            Constraint::Le => Formula::atom(Atom::less_eq(e, Term::num(0))),
+            // e < 0 iff ~(0 <= e)
+            Constraint::Lt => Formula::fnot(Formula::atom(Atom::less_eq(Term::num(0), e))),
+            Constraint::Eq => Formula::atom(Atom::equality(e, Term::num(0))),
+            Constraint::Ne => Formula::fnot(Formula::atom(Atom::equality(e, Term::num(0)))),
+        }
+    }
+
+    fn exists(p: Qf, k: usize, _nvars: usize) -> Qf {
+        virtual_subs(p, k)
+    }
+}
+
+/// A test point `s`, or `s + eps` if `eps`
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TestPoint {
+    s: LinExpr,
+    eps: bool,
+}
+
+/// The test points of `p` for `x_k`, besides `-inf`
+fn test_points(p: &Qf, k: usize) -> Vec<TestPoint> {
+    let mut points: Vec<TestPoint> = Vec::new();
+    p.for_each_lit(&mut |r| {
+        let a = r.lhs().coeff_unchecked(k);
+        let eps = match r.constraint() {
+            _ if a.is_zero() => return,
+            Constraint::Eq => false,
+            Constraint::Ne => true,
+            // upper bounds
+            _ if !a.is_negative() => return,
+            Constraint::Le => false,
+            Constraint::Lt => true,
+        };
+        let mut t = r.lhs().clone();
+        t.set_coeff_unchecked(k, Rational::ZERO);
+        let point = TestPoint { s: -t / a, eps };
+        if !points.contains(&point) {
+            points.push(point);
+        }
+    });
+    points
+}
+
+/// Substitute `-inf` for `x_k` in `r`
+fn subs_minus_inf(r: &LinRel, k: usize) -> Qf {
+    let a = r.lhs().coeff_unchecked(k);
+    if a.is_zero() {
+        return Qf::Lit(r.clone());
+    }
+    Qf::Bool(match r.constraint() {
+        Constraint::Eq => false,
+        Constraint::Ne => true,
+        Constraint::Le | Constraint::Lt => !a.is_negative(),
+    })
+}
+
+/// Substitute the test point `tp` for `x_k` in `r`
+fn subs_point(r: &LinRel, k: usize, tp: &TestPoint) -> Qf {
+    let a = r.lhs().coeff_unchecked(k).clone();
+    if a.is_zero() {
+        return Qf::Lit(r.clone());
+    }
+    let mut e = r.lhs().clone();
+    e.set_coeff_unchecked(k, Rational::ZERO);
+    let e = e + &tp.s * &a;
+    if !tp.eps {
+        return rel(match r.constraint() {
+            Constraint::Eq => LinRel::mk_eq(e),
+            Constraint::Le => LinRel::mk_le(e),
+            Constraint::Lt => LinRel::mk_lt(e),
+            Constraint::Ne => LinRel::mk_ne(e),
+        });
+    }
+    // a (s + eps) + t rel 0 where e = a s + t
+    match r.constraint() {
+        Constraint::Eq => Qf::Bool(false),
+        Constraint::Ne => Qf::Bool(true),
+        _ if a.is_negative() => rel(LinRel::mk_le(e)),
+        _ => rel(LinRel::mk_lt(e)),
+    }
+}
+
+/// Eliminate `x_k` from `exists x_k. p`
+fn virtual_subs(p: Qf, k: usize) -> Qf {
+    let mut res = p.map_lits(&mut |r| subs_minus_inf(r, k));
+    for tp in test_points(&p, k) {
+        if matches!(res, Qf::Bool(true)) {
+            break;
+        }
+        res = Qf::or(res, p.map_lits(&mut |r| subs_point(r, k, &tp)));
+    }
+    res
+}
+
+/// Eliminate `exists v. p` over the rationals, returning an equivalent quantifier-free formula
+pub fn eliminate_exists(v: &Var, p: &Formula) -> Result<Formula, LowerError> {
+    qe::eliminate_exists::<LinRel>(v, p)
+}
+
+/// Eliminate all quantifiers of `p` over the rationals, innermost first, returning an equivalent
+/// quantifier-free formula. The result is in NNF where a quantifier was eliminated.
+///
+/// ```
+/// # use presburger::ast::{Atom, Formula, Term, Var};
+/// # use presburger::types::Rational;
+/// # use presburger::virtual_subs::eliminate;
+/// # fn main () {
+/// let x = || Term::scalar_var(Rational::ONE, "x");
+/// let y = || Term::scalar_var(Rational::ONE, "y");
+/// // forall x. exists y. x < y /\ y < x + 1, which is false over the integers
+/// let lt = |t1, t2| Formula::fnot(Formula::atom(Atom::less_eq(t2, t1)));
+/// let p = Formula::forall(
+///     Var::new("x"),
+///     Formula::exists(
+///         Var::new("y"),
+///         Formula::and(lt(x(), y()), lt(y(), Term::tadd(x(), Term::num(1)))),
+///     ),
+/// );
+/// assert_eq!(eliminate(&p), Ok(Formula::atom(Atom::truth(true))));
+/// # }
+/// ```
+pub fn eliminate(p: &Formula) -> Result<Formula, LowerError> {
+    qe::eliminate::<LinRel>(p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qe_testing::{arb_qf_formula, env, eval, test_points, var};
+    use proptest::prelude::*;
+
+    fn lt(t1: Term, t2: Term) -> Formula {
+        Formula::fnot(Formula::atom(Atom::less_eq(t2, t1)))
+    }
+
+    fn half(n: i64) -> Rational {
+        Rational::from(n) / Rational::from(2)
+    }
+
+    #[test]
+    fn test_virtual_subs() {
+        // exists x. y < x /\ x < z, i.e. y < z
+        let p = Formula::exists(
+            Var::new("x"),
+            Formula::and(lt(var(1, "y"), var(1, "x")), lt(var(1, "x"), var(1, "z"))),
+        );
+        let q = eliminate(&p).unwrap();
+        for y in -4..4 {
+            for z in -4..4 {
+                let e = env(&[("y", half(y)), ("z", half(z))]);
+                assert_eq!(eval(&q, &e), y < z, "y = {y}/2, z = {z}/2");
+            }
+        }
+
+        // exists x. (x < y \/ x = 2) /\ 0 < x /\ x != 1, i.e. 0 < y \/ true
+        let p = Formula::exists(
+            Var::new("x"),
+            Formula::and(
+                Formula::or(
+                    lt(var(1, "x"), var(1, "y")),
+                    Formula::atom(Atom::equality(var(1, "x"), Term::num(2))),
+                ),
+                Formula::and(
+                    lt(Term::num(0), var(1, "x")),
+                    Formula::fnot(Formula::atom(Atom::equality(var(1, "x"), Term::num(1)))),
+                ),
+            ),
+        );
+        assert_eq!(eliminate(&p), Ok(Formula::atom(Atom::truth(true))));
+
+        // exists x. 3 x = y /\ x <= 0 /\ 0 <= x, i.e. y = 0
+        let p = Formula::exists(
+            Var::new("x"),
+            Formula::and(
+                Formula::atom(Atom::equality(var(3, "x"), var(1, "y"))),
+                Formula::and(
+                    Formula::atom(Atom::less_eq(var(1, "x"), Term::num(0))),
+                    Formula::atom(Atom::less_eq(Term::num(0), var(1, "x"))),
+                ),
+            ),
+        );
+        let q = eliminate(&p).unwrap();
+        for y in -4..4 {
+            assert_eq!(eval(&q, &env(&[("y", half(y))])), y == 0);
+        }
+    }
+
+    #[test]
+    fn test_virtual_subs_closed_formulas() {
+        // forall x. exists y. x < y
+        let p = Formula::forall(
+            Var::new("x"),
+            Formula::exists(Var::new("y"), lt(var(1, "x"), var(1, "y"))),
+        );
+        assert_eq!(eliminate(&p), Ok(Formula::atom(Atom::truth(true))));
+
+        // exists x. forall y. x <= y
+        let p = Formula::exists(
+            Var::new("x"),
+            Formula::forall(
+                Var::new("y"),
+                Formula::atom(Atom::less_eq(var(1, "x"), var(1, "y"))),
+            ),
+        );
+        assert_eq!(eliminate(&p), Ok(Formula::atom(Atom::truth(false))));
+
+        // forall x. exists y. x = 2 y, which is false over the integers
+        let p = Formula::forall(
+            Var::new("x"),
+            Formula::exists(
+                Var::new("y"),
+                Formula::atom(Atom::equality(var(1, "x"), var(2, "y"))),
+            ),
+        );
+        assert_eq!(eliminate(&p), Ok(Formula::atom(Atom::truth(true))));
+
+        // forall x. forall y. x < y ==> exists z. x < z /\ z < y
+        let p = Formula::forall(
+            Var::new("x"),
+            Formula::forall(
+                Var::new("y"),
+                Formula::implies(
+                    lt(var(1, "x"), var(1, "y")),
+                    Formula::exists(
+                        Var::new("z"),
+                        Formula::and(lt(var(1, "x"), var(1, "z")), lt(var(1, "z"), var(1, "y"))),
+                    ),
+                ),
+            ),
+        );
+        assert_eq!(eliminate(&p), Ok(Formula::atom(Atom::truth(true))));
+    }
+
+    #[test]
+    fn test_virtual_subs_errors_and_logical_vars() {
+        // divisibility has no counterpart over the rationals
+        let p = Formula::exists(Var::new("x"), Formula::atom(Atom::divides(2, var(1, "x"))));
+        assert!(matches!(eliminate(&p), Err(LowerError::Divides(_))));
+
+        // exists P. P /\ x <= 0 \/ ~P /\ 0 < x is true
+        let pvar = || Formula::atom(Atom::var("P"));
+        let p = Formula::exists(
+            Var::new("P"),
+            Formula::or(
+                Formula::and(
+                    pvar(),
+                    Formula::atom(Atom::less_eq(var(1, "x"), Term::num(0))),
+                ),
+                Formula::and(Formula::fnot(pvar()), lt(Term::num(0), var(1, "x"))),
+            ),
+        );
+        let q = eliminate(&Formula::forall(Var::new("x"), p)).unwrap();
+        assert_eq!(q, Formula::atom(Atom::truth(true)));
+    }
+
+    fn arb_term() -> impl Strategy<Value = Term> {
+        (-3i64..=3, 1i64..=3, -3i64..=3, -6i64..=6).prop_map(|(a, d, b, c)| {
+            let a = Rational::from(a) / Rational::from(d);
+            Term::tadd(
+                Term::scalar_var(a, "x"),
+                Term::tadd(var(b, "y"), Term::num(c)),
+            )
+        })
+    }
+
+    fn arb_atom() -> impl Strategy<Value = Atom> {
+        prop_oneof![
+            (arb_term(), arb_term()).prop_map(|(t1, t2)| Atom::less_eq(t1, t2)),
+            (arb_term(), arb_term()).prop_map(|(t1, t2)| Atom::equality(t1, t2)),
+        ]
+    }
+
+    proptest! {
+        /// `exists x. p(x, y)` agrees with evaluating `p` at the roots of its atoms, between
+        /// them, and beyond them, see `test_points`
+        #[test]
+        fn virtual_subs_agrees_with_roots(p in arb_qf_formula(arb_atom())) {
+            let q = eliminate(&Formula::exists(Var::new("x"), p.clone())).unwrap();
+            for y in (-6..=6).map(half) {
+                let expected = test_points(&p, "x", &env(&[("y", y.clone())]))
+                    .into_iter()
+                    .any(|x| eval(&p, &env(&[("x", x), ("y", y.clone())])));
+                prop_assert_eq!(eval(&q, &env(&[("y", y.clone())])), expected, "y = {}, q = {}", y, q);
+            }
+        }
+    }
+}
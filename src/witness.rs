@@ -0,0 +1,73 @@
+//! Construction of models shared by the decision procedures over the rationals: choosing a value
+//! between bounds, and moving a model off the hyperplanes of disequalities.
+
+use crate::lin_expr::LinExprBound;
+use crate::lin_rel::LinRel;
+use crate::model::Model;
+use crate::types::Rational;
+
+/// Pick a value between the lower and upper bounds evaluated in `model`. Every lower bound must
+/// be below (or strictly below) every upper bound, as after Fourier-Motzkin elimination.
+pub(crate) fn value_between(
+    model: &Model,
+    lower: &[LinExprBound],
+    upper: &[LinExprBound],
+) -> Rational {
+    // the tightest bound, which is strict if any bound with the same value is
+    let tightest = |bounds: &[LinExprBound], is_tighter: fn(&Rational, &Rational) -> bool| {
+        let mut tightest: Option<(Rational, bool)> = None;
+        for b in bounds {
+            let v = b.expr.eval(model).expect("model assigns all variables");
+            tightest = match tightest {
+                Some((t, s)) if t == v => Some((t, s || b.strict)),
+                Some((t, s)) if !is_tighter(&v, &t) => Some((t, s)),
+                _ => Some((v, b.strict)),
+            };
+        }
+        tightest
+    };
+    let lo = tightest(lower, |v, t| v > t);
+    let hi = tightest(upper, |v, t| v < t);
+    match (lo, hi) {
+        (Some((l, false)), _) => l,
+        (_, Some((h, false))) => h,
+        (Some((l, true)), None) => l + Rational::ONE,
+        (None, Some((h, true))) => h - Rational::ONE,
+        (Some((l, true)), Some((h, true))) => (l + h) / Rational::from(2),
+        (None, None) => Rational::ZERO,
+    }
+}
+
+/// Move `model`, a point of a convex set `P` satisfying all but the last of `disequalities`,
+/// towards `witness`, a point of `P` satisfying the last one, until it satisfies all of them.
+///
+/// Every point of the segment from `model` to `witness` is in `P`. Along the segment, each of
+/// the disequalities fails at at most one point, so one of the points at `1, 1/2, 1/3, ...` of
+/// the way to `witness` satisfies all of them.
+pub(crate) fn avoid_hyperplanes(model: Model, witness: &Model, disequalities: &[LinRel]) -> Model {
+    let satisfies_all = |point: &Model| {
+        disequalities
+            .iter()
+            .all(|r| r.holds(point).expect("model assigns all variables"))
+    };
+    if satisfies_all(&model) {
+        return model;
+    }
+    debug_assert_eq!(model.nvars(), witness.nvars());
+    let mut n = 1;
+    loop {
+        let t = Rational::ONE / Rational::from(n);
+        let point = Model::from_values(
+            model
+                .values()
+                .iter()
+                .zip(witness.values())
+                .map(|(p, w)| p + &t * (w - p))
+                .collect(),
+        );
+        if satisfies_all(&point) {
+            return point;
+        }
+        n += 1;
+    }
+}
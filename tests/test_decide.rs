@@ -0,0 +1,66 @@
+#[macro_use]
+extern crate lalrpop_util;
+
+#[cfg(test)]
+mod test_decide {
+    use presburger::decide;
+    use presburger::decide::DecideError;
+    use presburger::types::Domain;
+
+    lalrpop_mod!(
+        #[allow(clippy::all)]
+        pub grammer
+    ); // generated parser
+
+    fn decide_str(s: &str, domain: Domain) -> Result<bool, DecideError> {
+        let p = grammer::FormulaParser::new().parse(s).unwrap();
+        decide(&p, domain)
+    }
+
+    #[test]
+    fn test_decide_integer_and_rational() {
+        // (formula, true over the integers, true over the rationals if supported)
+        let cases = vec![
+            (
+                "forall x. exists y. x = 2 * y \\/ x = 2 * y + 1",
+                true,
+                Some(true),
+            ),
+            ("forall x. exists y. x = 2 * y", false, Some(true)),
+            (
+                "forall x. exists y. ~(y <= x) /\\ ~(x + 1 <= y)",
+                false,
+                Some(true),
+            ),
+            ("exists x. forall y. x <= y", false, Some(false)),
+            ("forall x. forall y. x <= y \\/ y <= x", true, Some(true)),
+            ("exists x. 3 * x = 1", false, Some(true)),
+            (
+                "forall x. 0 <= x ==> (exists y. x = y + y \\/ x = y + y + 1)",
+                true,
+                Some(true),
+            ),
+            ("forall x. 2 | x \\/ 2 | x + 1", true, None),
+            ("@T /\\ (forall x. x <= x)", true, Some(true)),
+        ];
+        for (s, int, rat) in cases {
+            assert_eq!(decide_str(s, Domain::Integer), Ok(int), "integers: {s}");
+            match rat {
+                Some(b) => assert_eq!(decide_str(s, Domain::Rational), Ok(b), "rationals: {s}"),
+                None => assert!(matches!(
+                    decide_str(s, Domain::Rational),
+                    Err(DecideError::Unsupported(_))
+                )),
+            }
+        }
+    }
+
+    #[test]
+    fn test_decide_errors() {
+        assert_eq!(
+            decide_str("exists x. x <= y", Domain::Integer),
+            Err(DecideError::FreeVar(presburger::ast::Var::new("y")))
+        );
+        assert!(decide_str("P ==> P", Domain::Rational).is_err());
+    }
+}
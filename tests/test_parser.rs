@@ -3,8 +3,10 @@ extern crate lalrpop_util;
 
 #[cfg(test)]
 mod test_parser {
-    use presburger::ast::Term;
-    use presburger::types::rbig;
+    use presburger::ast::{Atom, Term};
+    use presburger::ast_strategy::arb_divisor;
+    use presburger::types::{Rational, rbig};
+    use proptest::prelude::*;
 
     lalrpop_mod!(
         #[allow(clippy::all)]
@@ -59,6 +61,8 @@ mod test_parser {
             "y + x + 1 + z <= 0",
             "1/2 * x + 3 * y <= 0",
             "(-1) * x = 0",
+            "2 | x", // divisibility
+            "-3 | x + 2 * y + 1",
         ];
         for c in cases {
             assert!(grammer::AtomParser::new().parse(c).is_ok(), "case: {}", c);
@@ -93,6 +97,45 @@ mod test_parser {
         }
     }
 
+    #[test]
+    fn test_divides() {
+        // 2 | x + 1
+        assert_eq!(
+            grammer::AtomParser::new().parse("2 | x + 1"),
+            Ok(Atom::divides(
+                2,
+                Term::tadd(
+                    Term::scalar_var(Rational::from(1), "x"),
+                    Term::num(Rational::from(1))
+                )
+            ))
+        );
+        // 0 and negative divisors are part of the syntax
+        assert_eq!(
+            grammer::AtomParser::new().parse("0 | 3 * y"),
+            Ok(Atom::divides(0, Term::scalar_var(Rational::from(3), "y")))
+        );
+        assert_eq!(
+            grammer::AtomParser::new().parse("-4 | -1/2"),
+            Ok(Atom::divides(-4, Term::num(rbig!(-1 / 2))))
+        );
+        // the divisor is an integer literal
+        for c in ["1/2 | x", "x | 2", "(2) | x", "2 |"] {
+            assert!(grammer::AtomParser::new().parse(c).is_err(), "case: {}", c);
+        }
+    }
+
+    proptest! {
+        /// `d | t` parses back from its display, for constant terms, which display in the
+        /// grammar's syntax
+        #[test]
+        fn divides_display_round_trip(d in arb_divisor(), n in any::<i64>(), m in 1i64..100) {
+            let atom = Atom::divides(d, Term::num(Rational::from(n) / Rational::from(m)));
+            let displayed = atom.to_string();
+            prop_assert_eq!(grammer::AtomParser::new().parse(&displayed), Ok(atom));
+        }
+    }
+
     #[test]
     fn test_pred() {
         let cases = vec![